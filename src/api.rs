@@ -1,17 +1,35 @@
 use std::error::Error;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 use deser_incomplete::from_json_str;
 
+use super::validation;
+
 pub use super::dto_content::{
-    Blob, CodeExecutionResult, Content, ExecutableCode, FileData, FunctionCall, FunctionResponse,
-    JsonString, Part, VideoMetadata,
+    Blob, BlobData, CodeExecutionResult, Content, ExecutableCode, FileData, FunctionCall,
+    FunctionResponse, Json, JsonString, Language, Outcome, Part, TextPayload, TranscriptEntry,
+    VideoMetadata,
 };
+pub use super::dto_file::{File, FileState};
 pub use super::dto_request::{
-    GenerateContentRequest, GenerationConfig, GenerationConfigBuilder, MimeType, ResponseMimeType,
-    SafetyRating, SafetySetting,
+    AspectRatio, CodeExecution, GenerateContentRequest, GenerationConfig, GenerationConfigBuilder,
+    GoogleSearch, HarmBlockThreshold, HarmCategory, HarmProbability, ImageConfig, ImageSize,
+    MediaResolution, MimeType, MultiSpeakerVoiceConfig, ParseMimeTypeError, PrebuiltVoiceConfig,
+    ResponseMimeType, ResponseModality, SafetyRating, SafetySetting, SpeakerVoiceConfig,
+    SpeechConfig, ThinkingConfig, ThinkingLevel, Tool, UrlContext, VoiceConfig,
+};
+pub use super::dto_response::{
+    BlockReason, BlockedError, Candidate, CitationMetadata, CitationSource, FinishReason,
+    GenerateContentResponse, GroundingChunk, GroundingMetadata, GroundingSupport,
+    LogprobsCandidate, LogprobsResult, Modality, ModalityTokenCount, PromptFeedback,
+    SearchEntryPoint, Segment, TopCandidates, UrlContextMetadata, UrlMetadata, UrlRetrievalStatus,
+    UsageMetadata, WebSource,
 };
-pub use super::dto_response::{Candidate, GenerateContentResponse, PromptFeedback, UsageMetadata};
+pub use super::media::{GeneratedImage, MAX_INLINE_BYTES, MediaCategory, UnsupportedMediaError};
+pub use super::operation::{Operation, OperationError, OperationPoller};
 pub use super::stream_ext::BoxResponseStream;
+pub use super::validation::{RepairStrategy, ValidatedResponse};
 
 /// Trait for Gemini content generation API
 #[async_trait::async_trait]
@@ -28,7 +46,39 @@ pub trait GeminiApi {
         request: GenerateContentRequest<T>,
     ) -> Result<GenerateContentResponse<T>, Box<dyn Error>>
     where
-        T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static;
+        T: TextPayload + Send + 'static;
+
+    /// Generates content and validates the structured answer against the request's schema
+    ///
+    /// Invalid answers are repaired as configured by `strategy`: malformed JSON can be
+    /// fixed locally and the model can be re-prompted with the validation errors.
+    /// Requests without a schema are only checked for well-formed JSON.
+    ///
+    /// # Errors
+    /// Returns a `ResponseValidationError` if the answer is still invalid once the
+    /// strategy is exhausted, or the error of any underlying request.
+    async fn generate_content_validated<T>(
+        &self,
+        request: GenerateContentRequest<T>,
+        strategy: RepairStrategy,
+    ) -> Result<ValidatedResponse<T>, Box<dyn Error>>
+    where
+        Self: Sync,
+        T: TextPayload + Send + 'static,
+    {
+        validation::generate_validated(self, request, strategy).await
+    }
+}
+
+/// How the text of a streamed chunk relates to the chunks before it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StreamTextMode {
+    /// Each chunk carries only the text generated since the previous chunk
+    #[default]
+    Delta,
+    /// The first text part of a chunk's first candidate that is not a thought carries all
+    /// such text generated so far in the response; other parts are deltas
+    Cumulative,
 }
 
 #[async_trait::async_trait]
@@ -45,14 +95,284 @@ pub trait GeminiStreamingApi {
         request: GenerateContentRequest<T>,
     ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
     where
-        T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static;
+        T: TextPayload + Send + 'static;
+
+    /// Returns how the text of successive chunks relates, deltas unless overridden
+    ///
+    /// Chat streams rely on this to rebuild the full response text for history.
+    fn stream_text_mode(&self) -> StreamTextMode {
+        StreamTextMode::Delta
+    }
 
     fn parse_incomplete<T>(
         raw: String,
     ) -> Result<GenerateContentResponse<T>, deser_incomplete::Error<serde_json::Error>>
     where
-        T: serde::de::DeserializeOwned + 'static,
+        T: TextPayload,
     {
         from_json_str::<GenerateContentResponse<T>>(&raw)
     }
 }
+
+/// Trait for polling long-running operations
+#[async_trait::async_trait]
+pub trait GeminiOperationsApi {
+    /// Fetches the current state of a long-running operation
+    ///
+    /// # Arguments
+    /// * `name` - The operation resource name, e.g. `operations/abc123` or `batches/abc123`
+    ///
+    /// # Returns
+    /// The operation with its response typed as `T` once it is done
+    async fn get_operation<T>(&self, name: &str) -> Result<Operation<T>, Box<dyn Error>>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static;
+
+    /// Requests server-side cancellation of a long-running operation
+    ///
+    /// # Arguments
+    /// * `name` - The operation resource name
+    async fn cancel_operation(&self, name: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Creates a poller that waits for `operation` to complete
+    ///
+    /// # Arguments
+    /// * `operation` - The operation returned by the initiating endpoint
+    ///
+    /// # Returns
+    /// An `OperationPoller` that can be configured and then awaited
+    fn poll_operation<T>(&self, operation: Operation<T>) -> OperationPoller<'_, Self, T>
+    where
+        Self: Sync,
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        OperationPoller::new(self, operation)
+    }
+}
+
+/// Trait for counting tokens without generating content
+#[async_trait::async_trait]
+pub trait GeminiTokensApi {
+    /// Counts the tokens `contents` take up as a prompt
+    ///
+    /// # Arguments
+    /// * `contents` - The conversation to count, as it would be sent to `generate_content`
+    async fn count_tokens(&self, contents: &[Content]) -> Result<i32, Box<dyn Error>>;
+}
+
+/// Trait for the Files API, used for media too large to send inline
+#[async_trait::async_trait]
+pub trait GeminiFilesApi {
+    /// Uploads media and returns the created file
+    ///
+    /// # Arguments
+    /// * `data` - The raw file contents
+    /// * `mime_type` - The MIME type of the contents
+    /// * `display_name` - Optional human-readable name
+    async fn upload_file(
+        &self,
+        data: bytes::Bytes,
+        mime_type: MimeType,
+        display_name: Option<String>,
+    ) -> Result<File, Box<dyn Error>>;
+
+    /// Fetches the metadata of an uploaded file
+    ///
+    /// # Arguments
+    /// * `name` - The file resource name, e.g. `files/abc-123`
+    async fn get_file(&self, name: &str) -> Result<File, Box<dyn Error>>;
+
+    /// Deletes an uploaded file
+    ///
+    /// # Arguments
+    /// * `name` - The file resource name
+    async fn delete_file(&self, name: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Waits until an uploaded file has finished processing
+    ///
+    /// # Arguments
+    /// * `file` - The file returned by `upload_file`
+    /// * `timeout` - Maximum total time to spend polling
+    ///
+    /// # Errors
+    /// Returns `OperationError::DeadlineExceeded` if the file is still processing
+    /// after `timeout`, or an error if a poll request fails or processing failed.
+    async fn wait_for_file(&self, mut file: File, timeout: Duration) -> Result<File, Box<dyn Error>>
+    where
+        Self: Sync,
+    {
+        let started = Instant::now();
+        while file.is_processing() {
+            let elapsed = started.elapsed();
+            if elapsed >= timeout {
+                return Err(Box::new(OperationError::DeadlineExceeded {
+                    name: file.name,
+                    elapsed,
+                }));
+            }
+
+            tokio::time::sleep(FILE_POLL_INTERVAL.min(timeout - elapsed)).await;
+            file = self.get_file(&file.name).await?;
+        }
+
+        if file.state == Some(FileState::Failed) {
+            let message = file
+                .error
+                .map(|status| status.message)
+                .unwrap_or_else(|| "unknown error".to_string());
+            return Err(format!("Processing of {} failed: {}", file.name, message).into());
+        }
+
+        Ok(file)
+    }
+
+    /// Creates a media part, uploading the data if it is too large to send inline
+    ///
+    /// Payloads up to [`MAX_INLINE_BYTES`] become inline `Blob` parts. Larger payloads
+    /// are uploaded and referenced by URI once the file has finished processing,
+    /// waiting at most [`FILE_PROCESSING_TIMEOUT`].
+    ///
+    /// # Errors
+    /// Returns an error if the upload fails, the file fails processing, or it is
+    /// still processing when the timeout elapses.
+    async fn part_from_bytes<T>(
+        &self,
+        data: bytes::Bytes,
+        mime_type: MimeType,
+    ) -> Result<Part<T>, Box<dyn Error>>
+    where
+        Self: Sync,
+        T: Send + 'static,
+    {
+        if data.len() <= MAX_INLINE_BYTES {
+            return Ok(Part::from_bytes(data, mime_type));
+        }
+
+        let file = self.upload_file(data, mime_type, None).await?;
+        let file = self.wait_for_file(file, FILE_PROCESSING_TIMEOUT).await?;
+        Ok(file.to_part())
+    }
+
+    /// Creates a media part from a file, detecting its MIME type
+    ///
+    /// Behaves like [`GeminiFilesApi::part_from_bytes`] for the file contents.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, its type is not recognized, or
+    /// the upload fails.
+    async fn part_from_path<T>(&self, path: &Path) -> Result<Part<T>, Box<dyn Error>>
+    where
+        Self: Sync,
+        T: Send + 'static,
+    {
+        let data = tokio::fs::read(path).await?;
+        let mime_type = MimeType::detect(&data, Some(path))
+            .ok_or_else(|| format!("Cannot determine MIME type of {}", path.display()))?;
+        self.part_from_bytes(data.into(), mime_type).await
+    }
+}
+
+/// How often `GeminiFilesApi::wait_for_file` checks whether an upload is processed
+const FILE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long `GeminiFilesApi::part_from_bytes` waits for an upload to be processed
+pub const FILE_PROCESSING_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Mock Files API whose uploads stay in `PROCESSING` for a fixed number of polls
+    struct MockFilesApi {
+        polls_until_active: usize,
+        polls: Mutex<usize>,
+    }
+
+    impl MockFilesApi {
+        fn file(&self, state: FileState) -> File {
+            serde_json::from_value(serde_json::json!({
+                "name": "files/abc",
+                "mimeType": "video/mp4",
+                "uri": "https://example.com/files/abc",
+                "state": state,
+            }))
+            .unwrap()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl GeminiFilesApi for MockFilesApi {
+        async fn upload_file(
+            &self,
+            _data: bytes::Bytes,
+            _mime_type: MimeType,
+            _display_name: Option<String>,
+        ) -> Result<File, Box<dyn Error>> {
+            if self.polls_until_active == 0 {
+                Ok(self.file(FileState::Active))
+            } else {
+                Ok(self.file(FileState::Processing))
+            }
+        }
+
+        async fn get_file(&self, _name: &str) -> Result<File, Box<dyn Error>> {
+            let mut polls = self.polls.lock().unwrap();
+            *polls += 1;
+            if *polls < self.polls_until_active {
+                Ok(self.file(FileState::Processing))
+            } else {
+                Ok(self.file(FileState::Active))
+            }
+        }
+
+        async fn delete_file(&self, _name: &str) -> Result<(), Box<dyn Error>> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_file_times_out() {
+        let api = MockFilesApi {
+            polls_until_active: usize::MAX,
+            polls: Mutex::new(0),
+        };
+
+        let file = api.file(FileState::Processing);
+        let error = api
+            .wait_for_file(file, Duration::from_millis(20))
+            .await
+            .unwrap_err();
+
+        let error = error.downcast_ref::<OperationError>().unwrap();
+        assert!(
+            matches!(error, OperationError::DeadlineExceeded { name, .. } if name == "files/abc")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_part_from_bytes_uploads_large_payloads() {
+        let api = MockFilesApi {
+            polls_until_active: 0,
+            polls: Mutex::new(0),
+        };
+
+        let small: Part = api
+            .part_from_bytes(bytes::Bytes::from_static(b"\0\0\0"), MimeType::VideoMp4)
+            .await
+            .unwrap();
+        assert!(small.inline_data().is_some());
+
+        let large: Part = api
+            .part_from_bytes(
+                bytes::Bytes::from(vec![0; MAX_INLINE_BYTES + 1]),
+                MimeType::VideoMp4,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            large.file_data().unwrap().file_uri,
+            "https://example.com/files/abc"
+        );
+    }
+}
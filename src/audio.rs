@@ -0,0 +1,248 @@
+//! Speech output.
+//!
+//! Text-to-speech models return raw 16-bit little-endian PCM in `inline_data`
+//! parts, with a MIME type such as `audio/L16;codec=pcm;rate=24000`. This module
+//! decodes that audio and wraps it in a WAV container so it can be played or saved.
+
+use std::path::Path;
+use std::time::Duration;
+
+use super::dto_content::Part;
+use super::dto_request::MimeType;
+use super::dto_response::GenerateContentResponse;
+
+/// Sample rate assumed when the MIME type does not specify one
+const DEFAULT_SAMPLE_RATE: u32 = 24_000;
+
+/// Bytes per sample of 16-bit PCM
+const BYTES_PER_SAMPLE: u16 = 2;
+
+/// Raw 16-bit little-endian PCM audio
+#[derive(Debug, Clone, PartialEq)]
+pub struct PcmAudio {
+    /// Samples per second
+    pub sample_rate: u32,
+    /// Number of interleaved channels
+    pub channels: u16,
+    /// Sample data
+    pub data: bytes::Bytes,
+}
+
+impl PcmAudio {
+    /// Returns the playback duration of the audio
+    pub fn duration(&self) -> Duration {
+        let bytes_per_second =
+            u64::from(self.sample_rate) * u64::from(self.channels) * u64::from(BYTES_PER_SAMPLE);
+        if bytes_per_second == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(self.data.len() as f64 / bytes_per_second as f64)
+    }
+
+    /// Returns the audio as a WAV file
+    ///
+    /// # Errors
+    /// Returns an error if the header fields overflow, which happens when the data
+    /// exceeds the 4 GiB RIFF limit or the channel count or sample rate is too large.
+    pub fn to_wav(&self) -> std::io::Result<Vec<u8>> {
+        let invalid = |message: &str| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, message.to_string())
+        };
+
+        let (riff_len, data_len) = riff_lengths(self.data.len())
+            .ok_or_else(|| invalid("PCM data exceeds the 4 GiB RIFF limit"))?;
+        let block_align = self
+            .channels
+            .checked_mul(BYTES_PER_SAMPLE)
+            .ok_or_else(|| invalid("Too many channels for a WAV header"))?;
+        let byte_rate = self
+            .sample_rate
+            .checked_mul(u32::from(block_align))
+            .ok_or_else(|| invalid("Sample rate too high for a WAV header"))?;
+
+        let mut wav = Vec::with_capacity(44 + self.data.len());
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&riff_len.to_le_bytes());
+        wav.extend_from_slice(b"WAVE");
+        wav.extend_from_slice(b"fmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        // Format 1 is uncompressed PCM
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&self.channels.to_le_bytes());
+        wav.extend_from_slice(&self.sample_rate.to_le_bytes());
+        wav.extend_from_slice(&byte_rate.to_le_bytes());
+        wav.extend_from_slice(&block_align.to_le_bytes());
+        wav.extend_from_slice(&(BYTES_PER_SAMPLE * 8).to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        wav.extend_from_slice(&self.data);
+        Ok(wav)
+    }
+
+    /// Writes the audio to a WAV file
+    ///
+    /// # Errors
+    /// Returns an error if the audio does not fit in a WAV file or the file cannot
+    /// be written.
+    pub fn save_wav(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_wav()?)
+    }
+}
+
+/// Returns the RIFF chunk size and data chunk size for `data_len` bytes of samples
+///
+/// Returns `None` if either does not fit in the 32-bit size fields.
+fn riff_lengths(data_len: usize) -> Option<(u32, u32)> {
+    let data_len = u32::try_from(data_len).ok()?;
+    Some((data_len.checked_add(36)?, data_len))
+}
+
+/// Returns true for the raw PCM types returned by text-to-speech models
+fn is_pcm(mime_type: &MimeType) -> bool {
+    let essence = mime_type.essence();
+    essence.eq_ignore_ascii_case("audio/l16") || essence.eq_ignore_ascii_case("audio/pcm")
+}
+
+impl<T> GenerateContentResponse<T> {
+    /// Returns the PCM audio in the first candidate, if any
+    ///
+    /// Audio is requested with `ResponseModality::Audio` and a `SpeechConfig`.
+    /// Audio split over several parts is joined; the sample rate and channel count
+    /// are read from the first part's MIME parameters, defaulting to 24 kHz mono.
+    ///
+    /// # Errors
+    /// Returns an error if the audio is not valid base64.
+    pub fn audio(&self) -> Result<Option<PcmAudio>, base64::DecodeError> {
+        let Some(content) = self.first_content() else {
+            return Ok(None);
+        };
+
+        let mut blobs = content
+            .parts()
+            .iter()
+            .filter(|part| !part.is_thought())
+            .filter_map(Part::inline_data)
+            .filter(|blob| is_pcm(&blob.mime_type))
+            .peekable();
+        let Some(first) = blobs.peek() else {
+            return Ok(None);
+        };
+
+        let sample_rate = first
+            .mime_type
+            .parameter("rate")
+            .and_then(|rate| rate.parse().ok())
+            .unwrap_or(DEFAULT_SAMPLE_RATE);
+        let channels = first
+            .mime_type
+            .parameter("channels")
+            .and_then(|channels| channels.parse().ok())
+            .unwrap_or(1);
+
+        let mut data = Vec::new();
+        for blob in blobs {
+            data.extend_from_slice(&blob.data.to_bytes()?);
+        }
+
+        Ok(Some(PcmAudio {
+            sample_rate,
+            channels,
+            data: data.into(),
+        }))
+    }
+
+    /// Writes the PCM audio in the first candidate to a WAV file
+    ///
+    /// # Errors
+    /// Returns an error if the response contains no PCM audio, the audio cannot be
+    /// decoded, or the file cannot be written.
+    pub fn save_wav(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let audio = self
+            .audio()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "Response contains no PCM audio",
+                )
+            })?;
+        audio.save_wav(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(parts: &str) -> GenerateContentResponse {
+        let json = format!(
+            r#"{{"candidates": [{{"content": {{"role": "model", "parts": [{parts}]}}}}]}}"#
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_audio_joins_pcm_parts() {
+        // Four samples split across two parts
+        let response = response(
+            r#"{"inlineData": {"mimeType": "audio/L16;codec=pcm;rate=16000", "data": "AQACAA=="}},
+               {"inlineData": {"mimeType": "audio/L16;codec=pcm;rate=16000", "data": "AwAEAA=="}}"#,
+        );
+
+        let audio = response.audio().unwrap().unwrap();
+        assert_eq!(audio.sample_rate, 16_000);
+        assert_eq!(audio.channels, 1);
+        assert_eq!(&audio.data[..], &[1, 0, 2, 0, 3, 0, 4, 0]);
+        assert_eq!(audio.duration(), Duration::from_secs_f64(4.0 / 16_000.0));
+
+        let text_only = self::response(r#"{"text": "No audio"}"#);
+        assert_eq!(text_only.audio().unwrap(), None);
+        assert!(text_only.save_wav("unused.wav").is_err());
+    }
+
+    #[test]
+    fn test_to_wav_header() {
+        let audio = PcmAudio {
+            sample_rate: 24_000,
+            channels: 2,
+            data: bytes::Bytes::from_static(&[0; 8]),
+        };
+
+        let wav = audio.to_wav().unwrap();
+        assert_eq!(wav.len(), 44 + 8);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()), 36 + 8);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes(wav[22..24].try_into().unwrap()), 2);
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 24_000);
+        assert_eq!(u32::from_le_bytes(wav[28..32].try_into().unwrap()), 96_000);
+        assert_eq!(u16::from_le_bytes(wav[32..34].try_into().unwrap()), 4);
+        assert_eq!(u16::from_le_bytes(wav[34..36].try_into().unwrap()), 16);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(MimeType::sniff(&wav), Some(MimeType::AudioWav));
+    }
+
+    #[test]
+    fn test_to_wav_rejects_overflowing_headers() {
+        let audio = PcmAudio {
+            sample_rate: 24_000,
+            channels: u16::MAX,
+            data: bytes::Bytes::new(),
+        };
+        assert!(audio.to_wav().is_err());
+
+        let audio = PcmAudio {
+            sample_rate: u32::MAX,
+            channels: 2,
+            data: bytes::Bytes::new(),
+        };
+        assert!(audio.to_wav().is_err());
+
+        assert_eq!(riff_lengths(8), Some((44, 8)));
+        assert_eq!(
+            riff_lengths(u32::MAX as usize - 36),
+            Some((u32::MAX, u32::MAX - 36))
+        );
+        assert_eq!(riff_lengths(u32::MAX as usize - 35), None);
+    }
+}
@@ -0,0 +1,376 @@
+//! Spending limits for Gemini API clients.
+//!
+//! `Budget` wraps any `GeminiApi` or `GeminiStreamingApi` implementor, prices the
+//! usage reported by every response and refuses further requests once the
+//! cumulative cost reaches the configured limit.
+
+use std::error::Error;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use futures::StreamExt;
+
+use super::api::{BoxResponseStream, GeminiApi, GeminiStreamingApi, StreamTextMode};
+use super::dto_content::TextPayload;
+use super::dto_request::GenerateContentRequest;
+use super::dto_response::{BlockedError, GenerateContentResponse, UsageMetadata};
+use super::pricing::{Cost, ModelPricing};
+
+/// Error returned when a request is refused because the budget is spent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetExceededError {
+    /// The configured limit in US dollars
+    pub limit: f64,
+    /// The amount spent so far in US dollars
+    pub spent: f64,
+}
+
+impl std::fmt::Display for BudgetExceededError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Budget exceeded: spent ${:.6} of ${:.6}",
+            self.spent, self.limit
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceededError {}
+
+/// API wrapper that tracks spend and enforces a spending limit
+///
+/// The limit is checked before each request, so a request started just below the
+/// limit may overshoot it. Requests that fail are not charged, except for blocked
+/// responses turned into a `BlockedError` by `BlockPolicy::Error`, which are charged
+/// for the usage they report.
+///
+/// # Example
+/// ```ignore
+/// let pricing = PricingTable::gemini_defaults();
+/// let client = GeminiV1Beta::from_env()?;
+/// let budget = Budget::new(client, pricing.get("gemini-2.5-flash").unwrap().clone(), 5.0);
+///
+/// let response = budget.generate_content(request).await?;
+/// println!("Spent so far: {}", budget.spent());
+/// ```
+pub struct Budget<A> {
+    api: A,
+    pricing: ModelPricing,
+    limit: f64,
+    spent: Arc<Mutex<Cost>>,
+}
+
+impl<A> Budget<A> {
+    /// Wraps `api` with a spending limit in US dollars
+    pub fn new(api: A, pricing: ModelPricing, limit: f64) -> Self {
+        Self {
+            api,
+            pricing,
+            limit,
+            spent: Arc::new(Mutex::new(Cost::default())),
+        }
+    }
+
+    /// Returns the spending limit in US dollars
+    pub fn limit(&self) -> f64 {
+        self.limit
+    }
+
+    /// Returns the cost of all requests made through this budget
+    pub fn spent(&self) -> Cost {
+        *self.spent.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the amount left before the limit is reached, never negative
+    pub fn remaining(&self) -> f64 {
+        (self.limit - self.spent().total()).max(0.0)
+    }
+
+    /// Returns `true` if no further requests will be made
+    pub fn is_exceeded(&self) -> bool {
+        self.spent().total() >= self.limit
+    }
+
+    /// Resets the tracked spend to zero
+    pub fn reset(&self) {
+        *self.spent.lock().unwrap_or_else(PoisonError::into_inner) = Cost::default();
+    }
+
+    /// Returns a reference to the wrapped API
+    pub fn inner(&self) -> &A {
+        &self.api
+    }
+
+    /// Returns the wrapped API, discarding the budget
+    pub fn into_inner(self) -> A {
+        self.api
+    }
+
+    fn check(&self) -> Result<(), BudgetExceededError> {
+        let spent = self.spent().total();
+        if spent >= self.limit {
+            return Err(BudgetExceededError {
+                limit: self.limit,
+                spent,
+            });
+        }
+        Ok(())
+    }
+
+    fn charge(&self, usage: &UsageMetadata) {
+        *self.spent.lock().unwrap_or_else(PoisonError::into_inner) += self.pricing.cost(usage);
+    }
+}
+
+#[async_trait::async_trait]
+impl<A> GeminiApi for Budget<A>
+where
+    A: GeminiApi + Sync,
+{
+    async fn generate_content<T>(
+        &self,
+        request: GenerateContentRequest<T>,
+    ) -> Result<GenerateContentResponse<T>, Box<dyn Error>>
+    where
+        T: TextPayload + Send + 'static,
+    {
+        self.check()?;
+
+        let result = self.api.generate_content(request).await;
+        let usage = match &result {
+            Ok(response) => response.usage_metadata.as_ref(),
+            Err(e) => e
+                .downcast_ref::<BlockedError>()
+                .and_then(BlockedError::usage_metadata),
+        };
+        if let Some(usage) = usage {
+            self.charge(usage);
+        }
+
+        result
+    }
+}
+
+#[async_trait::async_trait]
+impl<A> GeminiStreamingApi for Budget<A>
+where
+    A: GeminiStreamingApi + Sync,
+{
+    async fn stream_generate_content<T>(
+        &self,
+        request: GenerateContentRequest<T>,
+    ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
+    where
+        T: TextPayload + Send + 'static,
+    {
+        self.check()?;
+
+        let stream = self.api.stream_generate_content(request).await?;
+
+        // Chunks report cumulative usage, so only the increase since the previous
+        // chunk is charged; blocked chunks report it on their `BlockedError`
+        let spent = Arc::clone(&self.spent);
+        let pricing = self.pricing.clone();
+        let mut charged = Cost::default();
+        let stream = stream.map(move |result| {
+            let usage = match &result {
+                Ok(response) => response.usage_metadata.as_ref(),
+                Err(e) => e
+                    .downcast_ref::<BlockedError>()
+                    .and_then(BlockedError::usage_metadata),
+            };
+            if let Some(usage) = usage {
+                let cost = pricing.cost(usage);
+                *spent.lock().unwrap_or_else(PoisonError::into_inner) += cost - charged;
+                charged = cost;
+            }
+            result
+        });
+
+        Ok(Box::pin(stream))
+    }
+
+    fn stream_text_mode(&self) -> StreamTextMode {
+        self.api.stream_text_mode()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto_content::{Content, JsonString, Part};
+    use crate::dto_response::Candidate;
+    use crate::pricing::TokenRates;
+
+    fn response<T>(prompt: u32, candidates: u32) -> GenerateContentResponse<T>
+    where
+        T: TextPayload,
+    {
+        let string_response = GenerateContentResponse::<String> {
+            candidates: vec![Candidate {
+                content: Content::model(vec![
+                    Part::builder()
+                        .text(JsonString::new("ok".to_string()))
+                        .build(),
+                ]),
+                ..Default::default()
+            }],
+            prompt_feedback: None,
+            usage_metadata: Some(UsageMetadata {
+                prompt_token_count: Some(prompt),
+                candidates_token_count: Some(candidates),
+                ..Default::default()
+            }),
+        };
+        let json = serde_json::to_string(&string_response).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn request() -> GenerateContentRequest<String> {
+        GenerateContentRequest::builder()
+            .add_content(Content::user(vec![
+                Part::builder()
+                    .text(JsonString::new("Hi".to_string()))
+                    .build(),
+            ]))
+            .build()
+    }
+
+    struct MockApi;
+
+    #[async_trait::async_trait]
+    impl GeminiApi for MockApi {
+        async fn generate_content<T>(
+            &self,
+            _request: GenerateContentRequest<T>,
+        ) -> Result<GenerateContentResponse<T>, Box<dyn Error>>
+        where
+            T: TextPayload + Send + 'static,
+        {
+            Ok(response(1_000_000, 0))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl GeminiStreamingApi for MockApi {
+        async fn stream_generate_content<T>(
+            &self,
+            _request: GenerateContentRequest<T>,
+        ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
+        where
+            T: TextPayload + Send + 'static,
+        {
+            let chunks = vec![
+                Ok(response(1_000_000, 0)),
+                Ok(response(1_000_000, 500_000)),
+                Ok(response(1_000_000, 1_000_000)),
+            ];
+            Ok(Box::pin(futures::stream::iter(chunks)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_budget_refuses_requests_once_exceeded() {
+        let budget = Budget::new(MockApi, ModelPricing::new(TokenRates::new(1.0, 2.0)), 2.0);
+
+        budget.generate_content(request()).await.unwrap();
+        assert_eq!(budget.spent().total(), 1.0);
+        assert_eq!(budget.remaining(), 1.0);
+
+        budget.generate_content(request()).await.unwrap();
+        assert!(budget.is_exceeded());
+
+        let error = budget.generate_content(request()).await.unwrap_err();
+        let error = error.downcast_ref::<BudgetExceededError>().unwrap();
+        assert_eq!(error.spent, 2.0);
+        assert_eq!(budget.spent().total(), 2.0);
+
+        budget.reset();
+        assert!(!budget.is_exceeded());
+    }
+
+    #[tokio::test]
+    async fn test_budget_charges_streams_once() {
+        let budget = Budget::new(MockApi, ModelPricing::new(TokenRates::new(1.0, 2.0)), 10.0);
+
+        let chunks: Vec<_> = budget
+            .stream_generate_content(request())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        assert_eq!(chunks.len(), 3);
+        let spent = budget.spent();
+        assert_eq!(spent.input, 1.0);
+        assert_eq!(spent.output, 2.0);
+    }
+
+    /// Blocks every request the way `BlockPolicy::Error` reports it
+    struct BlockedMockApi;
+
+    fn blocked(prompt: u32, candidates: u32) -> Box<BlockedError> {
+        Box::new(BlockedError::CandidateBlocked {
+            reason: crate::dto_response::FinishReason::Safety,
+            ratings: vec![],
+            usage: response::<String>(prompt, candidates)
+                .usage_metadata
+                .map(Box::new),
+        })
+    }
+
+    #[async_trait::async_trait]
+    impl GeminiApi for BlockedMockApi {
+        async fn generate_content<T>(
+            &self,
+            _request: GenerateContentRequest<T>,
+        ) -> Result<GenerateContentResponse<T>, Box<dyn Error>>
+        where
+            T: TextPayload + Send + 'static,
+        {
+            Err(blocked(1_000_000, 0))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl GeminiStreamingApi for BlockedMockApi {
+        async fn stream_generate_content<T>(
+            &self,
+            _request: GenerateContentRequest<T>,
+        ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
+        where
+            T: TextPayload + Send + 'static,
+        {
+            let chunks = vec![
+                Ok(response(1_000_000, 500_000)),
+                Err(blocked(1_000_000, 1_000_000) as Box<dyn Error + Send + Sync>),
+            ];
+            Ok(Box::pin(futures::stream::iter(chunks)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_budget_charges_blocked_errors() {
+        let budget = Budget::new(
+            BlockedMockApi,
+            ModelPricing::new(TokenRates::new(1.0, 2.0)),
+            10.0,
+        );
+
+        let error = budget.generate_content(request()).await.unwrap_err();
+        assert!(error.downcast_ref::<BlockedError>().is_some());
+        assert_eq!(budget.spent().total(), 1.0);
+
+        budget.reset();
+        let chunks: Vec<_> = budget
+            .stream_generate_content(request())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        assert!(chunks[1].is_err());
+        let spent = budget.spent();
+        assert_eq!(spent.input, 1.0);
+        assert_eq!(spent.output, 2.0);
+    }
+}
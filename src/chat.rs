@@ -74,6 +74,29 @@
 //! // History is automatically updated when stream completes
 //! ```
 //!
+//! # Session Defaults
+//!
+//! A system instruction, generation config, safety settings, tools and cached content
+//! can be set once when building the chat and apply to every turn:
+//!
+//! ```rust,ignore
+//! use gemini::{GeminiChat, GenerationConfig};
+//!
+//! let mut chat = GeminiChat::builder(client)
+//!     .system_instruction("You are a terse assistant. Answer in one sentence.")
+//!     .generation_config(GenerationConfig::builder().temperature(0.2).build()?)
+//!     .build();
+//!
+//! // Options set on a message override the defaults for that turn; a per-message
+//! // generation config is merged with the default one field by field
+//! let response = chat
+//!     .send_message()
+//!     .text("Brainstorm some names for a cat")
+//!     .generation_config(GenerationConfig::builder().temperature(1.2).build()?)
+//!     .send()
+//!     .await?;
+//! ```
+//!
 //! # Advanced Configuration
 //!
 //! Both chat types support per-message configuration:
@@ -137,6 +160,10 @@
 //!     .await?;
 //! ```
 //!
+//! `get_history()` only returns the active branch. Serialize `chat.tree()` to keep
+//! answers replaced by `regenerate_last()` or `edit_turn()`, and restore it with
+//! the builder's `tree()` method.
+//!
 //! # Sending Structured Data
 //!
 //! Send JSON-serialized data as part of messages:
@@ -185,14 +212,23 @@
 //!     .await?;
 //! ```
 
-use crate::api::{BoxResponseStream, GeminiApi, GeminiStreamingApi};
-use crate::dto_content::{Content, JsonString, Part};
-use crate::dto_request::{GenerateContentRequest, GenerationConfig, SafetySetting};
-use crate::dto_response::GenerateContentResponse;
+use crate::api::{BoxResponseStream, GeminiApi, GeminiStreamingApi, StreamTextMode};
+use crate::chat_tree::{BranchError, ChatTree};
+use crate::dto_content::{Content, JsonString, Part, TextPayload};
+use crate::dto_request::{GenerateContentRequest, GenerationConfig, SafetySetting, Tool};
+use crate::dto_response::{BlockedError, GenerateContentResponse, UsageMetadata};
+use crate::history::{FullHistory, HistoryStrategy, turn_starts};
+use crate::pricing::{Cost, ModelPricing};
+use crate::store::ChatSession;
 use futures::stream::Stream;
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::error::Error;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::SystemTime;
 
 /// Chat wrapper for managing multi-turn conversations with the Gemini API
 ///
@@ -204,8 +240,18 @@ use std::task::{Context, Poll};
 pub struct GeminiChat<A> {
     /// The underlying API client
     api: A,
-    /// Conversation history with typed Content messages
-    history: Vec<Content<String>>,
+    /// Conversation history, with every branch
+    tree: ChatTree,
+    /// Typed values of model turns
+    typed: TypedResponses,
+    /// Token usage of each request made by this chat
+    usage: Vec<UsageMetadata>,
+    /// When the conversation was started
+    created_at: SystemTime,
+    /// Request options applied to every turn
+    defaults: ChatDefaults,
+    /// Selects the history sent with each turn
+    history_strategy: Box<dyn HistoryStrategy>,
 }
 
 impl<A> GeminiChat<A>
@@ -225,7 +271,12 @@ where
     pub fn new(api: A) -> Self {
         Self {
             api,
-            history: Vec::new(),
+            tree: ChatTree::new(),
+            usage: Vec::new(),
+            created_at: SystemTime::now(),
+            typed: TypedResponses::default(),
+            defaults: ChatDefaults::default(),
+            history_strategy: Box::new(FullHistory),
         }
     }
 
@@ -244,7 +295,50 @@ where
     /// let chat = GeminiChat::from_history(client, saved_history);
     /// ```
     pub fn from_history(api: A, history: Vec<Content<String>>) -> Self {
-        Self { api, history }
+        Self {
+            api,
+            tree: ChatTree::from_history(history),
+            usage: Vec::new(),
+            created_at: SystemTime::now(),
+            typed: TypedResponses::default(),
+            defaults: ChatDefaults::default(),
+            history_strategy: Box::new(FullHistory),
+        }
+    }
+
+    /// Starts building a chat session with options applied to every turn
+    ///
+    /// # Example
+    /// ```ignore
+    /// let chat = GeminiChat::builder(client)
+    ///     .system_instruction("You are a helpful travel agent.")
+    ///     .safety_settings(settings)
+    ///     .build();
+    /// ```
+    pub fn builder(api: A) -> GeminiChatBuilder<A> {
+        GeminiChatBuilder::new(api)
+    }
+
+    /// Restores a chat from a saved session
+    ///
+    /// Use [`GeminiChatBuilder::session`] to also set a history strategy.
+    pub fn from_session(api: A, session: ChatSession) -> Self {
+        Self::builder(api).session(session).build()
+    }
+
+    /// Captures the chat as a session to save under `id`, updated now
+    ///
+    /// # Example
+    /// ```ignore
+    /// store.save(&chat.to_session("support-42")).await?;
+    /// ```
+    pub fn to_session(&self, id: impl Into<String>) -> ChatSession {
+        self.defaults.to_session(
+            id.into(),
+            self.tree.clone(),
+            self.usage.clone(),
+            self.created_at,
+        )
     }
 
     /// Start building a message to send
@@ -262,7 +356,7 @@ where
     /// ```
     pub fn send_message<T>(&mut self) -> SendMessageBuilder<'_, A, T>
     where
-        T: serde::de::DeserializeOwned + serde::Serialize + Send + Clone + 'static,
+        T: TextPayload + Send + Sync + Clone + 'static,
     {
         SendMessageBuilder::new(self)
     }
@@ -278,14 +372,492 @@ where
     /// // Store json for later restoration
     /// ```
     pub fn get_history(&self) -> &[Content<String>] {
-        &self.history
+        self.tree.history()
     }
 
     /// Clears the conversation history
     ///
     /// Resets the chat to an empty state while keeping the same API client.
     pub fn clear_history(&mut self) {
-        self.history.clear();
+        self.tree.clear();
+        self.typed = TypedResponses::default();
+        self.history_strategy.reset();
+    }
+
+    /// Returns the conversation tree, with every branch and the active one
+    ///
+    /// Serialize the tree instead of [`Self::get_history`] to keep alternative branches.
+    pub fn tree(&self) -> &ChatTree {
+        &self.tree
+    }
+
+    /// Returns the typed value of the model turn at `index` of the history
+    ///
+    /// A model turn keeps the value of its first text part, in the type its message
+    /// was sent with, so earlier structured responses need not be parsed again.
+    /// Returns `None` if the turn was requested as another type, or restored from
+    /// saved history, which only holds the raw text.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let plan: Option<&Json<TripPlan>> = chat.typed_response(1);
+    /// ```
+    pub fn typed_response<T: 'static>(&self, index: usize) -> Option<&T> {
+        let node = *self.tree.active_path().get(index)?;
+        self.typed.get(node)
+    }
+
+    /// Returns the model turns of the history that were requested as `T`, with their index
+    pub fn typed_responses<T: 'static>(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.tree
+            .active_path()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &node)| Some((index, self.typed.get(node)?)))
+    }
+
+    /// Continues the conversation from the branch ending at node `id` of the tree
+    ///
+    /// # Errors
+    /// Returns `BranchError::NoSuchNode` if the tree has no node `id`.
+    pub fn checkout(&mut self, id: usize) -> Result<(), BranchError> {
+        self.tree.checkout(id)?;
+        self.history_strategy.reset();
+        Ok(())
+    }
+
+    /// Starts a message asking the model for another answer to the last user message
+    ///
+    /// Once sent, the new answer is the active branch; the previous one is kept in
+    /// the tree. The message can be configured like any other before sending.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let response = chat.regenerate_last()?.send().await?;
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `BranchError::NothingToRegenerate` if the history has no user message.
+    pub fn regenerate_last<T>(&mut self) -> Result<SendMessageBuilder<'_, A, T>, BranchError>
+    where
+        T: TextPayload + Send + Sync + Clone + 'static,
+    {
+        let history = self.tree.history();
+        let index = *turn_starts(history)
+            .last()
+            .ok_or(BranchError::NothingToRegenerate)?;
+        let parts = history[index].parts().to_vec();
+        Ok(SendMessageBuilder::new(self).branch_at(index).parts(parts))
+    }
+
+    /// Starts a message replacing the user message at `index` of the history
+    ///
+    /// Once sent, the edited message and its answer are the active branch, and
+    /// the original message with everything after it is kept in the tree.
+    ///
+    /// # Errors
+    /// Returns an error if `index` is not the index of a user message starting a turn.
+    pub fn edit_turn<T>(
+        &mut self,
+        index: usize,
+        parts: Vec<Part<String>>,
+    ) -> Result<SendMessageBuilder<'_, A, T>, BranchError>
+    where
+        T: TextPayload + Send + Sync + Clone + 'static,
+    {
+        let history = self.tree.history();
+        if index >= history.len() {
+            return Err(BranchError::NoSuchTurn(index));
+        }
+        if !turn_starts(history).contains(&index) {
+            return Err(BranchError::NotAUserTurn(index));
+        }
+        Ok(SendMessageBuilder::new(self).branch_at(index).parts(parts))
+    }
+
+    /// Creates an independent chat continuing from the active branch
+    ///
+    /// The fork shares the history so far, options and a copy of the history
+    /// strategy, but not the other branches or the recorded usage.
+    pub fn fork(&self) -> Self
+    where
+        A: Clone,
+    {
+        Self {
+            api: self.api.clone(),
+            tree: ChatTree::from_history(self.get_history().to_vec()),
+            usage: Vec::new(),
+            created_at: SystemTime::now(),
+            typed: self.typed.for_branch(self.tree.active_path()),
+            defaults: self.defaults.clone(),
+            history_strategy: self.history_strategy.clone(),
+        }
+    }
+
+    /// Returns the token usage summed over all turns sent through this chat
+    ///
+    /// Blocked turns are included, since their prompt tokens are still counted
+    /// by the API. Usage is not reset by `clear_history`.
+    pub fn usage(&self) -> UsageMetadata {
+        self.usage.iter().cloned().sum()
+    }
+
+    /// Returns the token usage of each request sent through this chat
+    pub fn turn_usage(&self) -> &[UsageMetadata] {
+        &self.usage
+    }
+
+    /// Returns the cost of all turns sent through this chat
+    ///
+    /// Each turn is priced separately, so tiered rates follow that turn's prompt length.
+    pub fn cost(&self, pricing: &ModelPricing) -> Cost {
+        self.usage.iter().map(|usage| pricing.cost(usage)).sum()
+    }
+}
+
+/// Setters shared by `GeminiChatBuilder` and `GeminiStreamChatBuilder`
+///
+/// Expects `tree`, `usage`, `created_at` and `defaults` fields on the builder.
+macro_rules! chat_default_setters {
+    () => {
+        /// Restores a saved session: its history, options, usage and start time
+        ///
+        /// Options set after this call take precedence over the saved ones.
+        #[must_use]
+        pub fn session(mut self, session: ChatSession) -> Self {
+            self.tree = session.tree;
+            self.usage = session.turn_usage;
+            self.created_at = Some(session.created_at);
+            self.defaults = ChatDefaults {
+                system_instruction: session.system_instruction,
+                generation_config: session.generation_config,
+                safety_settings: session.safety_settings,
+                tools: session.tools,
+                cached_content: session.cached_content,
+            };
+            self
+        }
+
+        /// Sets the system instruction sent with every turn
+        #[must_use]
+        pub fn system_instruction(mut self, instruction: impl Into<String>) -> Self {
+            self.defaults.system_instruction = Some(system_content(instruction));
+            self
+        }
+
+        /// Sets the default generation config
+        ///
+        /// A generation config set on a message is merged with this one, taking precedence
+        /// for every option it sets.
+        #[must_use]
+        pub fn generation_config(mut self, config: GenerationConfig) -> Self {
+            self.defaults.generation_config = Some(config);
+            self
+        }
+
+        /// Sets the default safety settings
+        #[must_use]
+        pub fn safety_settings(mut self, settings: Vec<SafetySetting>) -> Self {
+            self.defaults.safety_settings = Some(settings);
+            self
+        }
+
+        /// Sets the tools the model may use on every turn
+        #[must_use]
+        pub fn tools(mut self, tools: Vec<Tool>) -> Self {
+            self.defaults.tools = Some(tools);
+            self
+        }
+
+        /// Adds a single tool
+        #[must_use]
+        pub fn add_tool(mut self, tool: Tool) -> Self {
+            self.defaults.tools.get_or_insert_with(Vec::new).push(tool);
+            self
+        }
+
+        /// Sets the cached content used as context, e.g. `cachedContents/abc123`
+        ///
+        /// The API rejects cached content combined with a system instruction or tools;
+        /// include those in the cache instead.
+        #[must_use]
+        pub fn cached_content(mut self, name: impl Into<String>) -> Self {
+            self.defaults.cached_content = Some(name.into());
+            self
+        }
+    };
+}
+
+/// Per-message overrides shared by `SendMessageBuilder` and `SendMessageStreamBuilder`
+///
+/// Expects an `options: MessageOptions<T>` field on the builder.
+macro_rules! message_override_setters {
+    () => {
+        /// Set a system instruction for this message, replacing the chat's default
+        pub fn system_instruction(mut self, instruction: impl Into<String>) -> Self {
+            self.options.system_instruction = Some(system_content(instruction));
+            self
+        }
+
+        /// Set the tools for this message, replacing the chat's default tools
+        pub fn tools(mut self, tools: Vec<Tool>) -> Self {
+            self.options.tools = Some(tools);
+            self
+        }
+
+        /// Set the cached content for this message, replacing the chat's default
+        pub fn cached_content(mut self, name: impl Into<String>) -> Self {
+            self.options.cached_content = Some(name.into());
+            self
+        }
+    };
+}
+
+/// Request options a chat applies to every turn unless the message overrides them
+#[derive(Debug, Clone, Default)]
+struct ChatDefaults {
+    system_instruction: Option<Content>,
+    generation_config: Option<GenerationConfig>,
+    safety_settings: Option<Vec<SafetySetting>>,
+    tools: Option<Vec<Tool>>,
+    cached_content: Option<String>,
+}
+
+impl ChatDefaults {
+    /// Builds a session holding these options and the given chat state
+    fn to_session(
+        &self,
+        id: String,
+        tree: ChatTree,
+        turn_usage: Vec<UsageMetadata>,
+        created_at: SystemTime,
+    ) -> ChatSession {
+        ChatSession {
+            id,
+            tree,
+            system_instruction: self.system_instruction.clone(),
+            generation_config: self.generation_config.clone(),
+            safety_settings: self.safety_settings.clone(),
+            tools: self.tools.clone(),
+            cached_content: self.cached_content.clone(),
+            turn_usage,
+            created_at,
+            updated_at: SystemTime::now(),
+        }
+    }
+
+    /// Builds the request of a turn, preferring the options set on the message
+    ///
+    /// A message's generation config is merged with the default one, other options
+    /// replace their defaults.
+    fn build_request<T>(
+        &self,
+        contents: Vec<Content>,
+        overrides: MessageOptions<T>,
+    ) -> GenerateContentRequest<T> {
+        let mut builder = GenerateContentRequest::<T>::builder().contents(contents);
+
+        let generation_config = match (overrides.generation_config, &self.generation_config) {
+            (Some(config), Some(defaults)) => Some(config.with_defaults(defaults)),
+            (Some(config), None) => Some(config),
+            (None, Some(defaults)) => Some(defaults.clone().retyped()),
+            (None, None) => None,
+        };
+        if let Some(config) = generation_config {
+            builder = builder.generation_config(config);
+        }
+
+        if let Some(instruction) = overrides
+            .system_instruction
+            .or_else(|| self.system_instruction.clone())
+        {
+            builder = builder.system_instruction(instruction);
+        }
+
+        if let Some(settings) = overrides
+            .safety_settings
+            .or_else(|| self.safety_settings.clone())
+        {
+            builder = builder.safety_settings(settings);
+        }
+
+        if let Some(tools) = overrides.tools.or_else(|| self.tools.clone()) {
+            builder = builder.tools(tools);
+        }
+
+        if let Some(name) = overrides
+            .cached_content
+            .or_else(|| self.cached_content.clone())
+        {
+            builder = builder.cached_content(name);
+        }
+
+        builder.build()
+    }
+}
+
+/// Options set on a single message, overriding the chat's defaults for that turn
+struct MessageOptions<T> {
+    system_instruction: Option<Content>,
+    generation_config: Option<GenerationConfig<T>>,
+    safety_settings: Option<Vec<SafetySetting>>,
+    tools: Option<Vec<Tool>>,
+    cached_content: Option<String>,
+}
+
+impl<T> Default for MessageOptions<T> {
+    fn default() -> Self {
+        Self {
+            system_instruction: None,
+            generation_config: None,
+            safety_settings: None,
+            tools: None,
+            cached_content: None,
+        }
+    }
+}
+
+/// Creates the content of a text system instruction
+fn system_content(text: impl Into<String>) -> Content {
+    Content::unspecified(vec![
+        Part::builder().text(JsonString::new(text.into())).build(),
+    ])
+}
+
+/// Returns the text of a part as received, or serialized if it was built locally
+fn part_text<T: TextPayload>(part: &Part<T>) -> Option<Cow<'_, str>> {
+    match part.raw_text() {
+        Some(raw) => Some(Cow::Borrowed(raw)),
+        None => part.text()?.to_text().ok(),
+    }
+}
+
+/// Returns whether a response part belongs in history
+///
+/// Thought summaries are dropped unless they carry a thought signature.
+fn keep_in_history<T>(part: &Part<T>) -> bool {
+    !part.is_thought() || part.thought_signature().is_some()
+}
+
+/// Converts a response part to a string part for history, keeping its thought signature
+fn history_part<T: TextPayload>(part: &Part<T>) -> Part<String> {
+    let mut builder = Part::builder();
+    if let Some(thought) = part.thought_text() {
+        builder = builder.thought_text(thought);
+    } else if let Some(text) = part_text(part) {
+        builder = builder.text(JsonString::new(text.into_owned()));
+    } else if let Some(function_call) = part.function_call() {
+        builder = builder.function_call(function_call.clone());
+    } else if let Some(blob) = part.inline_data() {
+        builder = builder.inline_data(blob.clone());
+    } else if let Some(file_data) = part.file_data() {
+        builder = builder.file_data(file_data.clone());
+    } else {
+        builder = builder.text(JsonString::new("".to_string()));
+    }
+    if let Some(signature) = part.thought_signature() {
+        builder = builder.thought_signature(signature);
+    }
+    builder.build()
+}
+
+/// Typed values of model turns, keyed by their node in the chat tree
+///
+/// History keeps the raw text of every turn; these are the values it was parsed
+/// into, in whatever type each message was sent with.
+#[derive(Clone, Default)]
+struct TypedResponses(HashMap<usize, Arc<dyn Any + Send + Sync>>);
+
+impl TypedResponses {
+    fn insert(&mut self, node: usize, value: Arc<dyn Any + Send + Sync>) {
+        self.0.insert(node, value);
+    }
+
+    /// Parses text as `T`, for recording without knowing `T`
+    fn parse<T>(text: String) -> Option<Arc<dyn Any + Send + Sync>>
+    where
+        T: TextPayload + Send + Sync + 'static,
+    {
+        Some(Arc::new(T::from_text(text).ok()?))
+    }
+
+    fn get<T: 'static>(&self, node: usize) -> Option<&T> {
+        self.0.get(&node)?.downcast_ref()
+    }
+
+    /// Returns the values of a branch, keyed by position as in a tree built from it
+    fn for_branch(&self, path: &[usize]) -> Self {
+        let values = path
+            .iter()
+            .enumerate()
+            .filter_map(|(index, node)| Some((index, Arc::clone(self.0.get(node)?))))
+            .collect();
+        Self(values)
+    }
+}
+
+/// Builder for a [`GeminiChat`] with options applied to every turn
+///
+/// Created via `GeminiChat::builder()`. Options set on a message override these
+/// defaults for that turn only.
+pub struct GeminiChatBuilder<A> {
+    api: A,
+    tree: ChatTree,
+    usage: Vec<UsageMetadata>,
+    created_at: Option<SystemTime>,
+    defaults: ChatDefaults,
+    history_strategy: Box<dyn HistoryStrategy>,
+}
+
+impl<A> GeminiChatBuilder<A>
+where
+    A: GeminiApi,
+{
+    fn new(api: A) -> Self {
+        Self {
+            api,
+            tree: ChatTree::new(),
+            usage: Vec::new(),
+            created_at: None,
+            defaults: ChatDefaults::default(),
+            history_strategy: Box::new(FullHistory),
+        }
+    }
+
+    /// Restores previously saved conversation history
+    #[must_use]
+    pub fn history(mut self, history: Vec<Content<String>>) -> Self {
+        self.tree = ChatTree::from_history(history);
+        self
+    }
+
+    /// Restores a previously saved conversation tree, with all its branches
+    #[must_use]
+    pub fn tree(mut self, tree: ChatTree) -> Self {
+        self.tree = tree;
+        self
+    }
+
+    chat_default_setters!();
+
+    /// Sets how much of the history is sent with each turn, all of it by default
+    #[must_use]
+    pub fn history_strategy(mut self, strategy: impl HistoryStrategy + 'static) -> Self {
+        self.history_strategy = Box::new(strategy);
+        self
+    }
+
+    /// Creates the chat session
+    pub fn build(self) -> GeminiChat<A> {
+        GeminiChat {
+            api: self.api,
+            tree: self.tree,
+            usage: self.usage,
+            created_at: self.created_at.unwrap_or_else(SystemTime::now),
+            typed: TypedResponses::default(),
+            defaults: self.defaults,
+            history_strategy: self.history_strategy,
+        }
     }
 }
 
@@ -301,25 +873,32 @@ where
 pub struct SendMessageBuilder<'a, A, T> {
     chat: &'a mut GeminiChat<A>,
     message_parts: Option<Vec<Part<String>>>,
-    generation_config: Option<GenerationConfig<T>>,
-    safety_settings: Option<Vec<SafetySetting>>,
+    options: MessageOptions<T>,
+    /// Length of the history prefix the turn branches from, if not the whole history
+    branch_at: Option<usize>,
 }
 
 impl<'a, A, T> SendMessageBuilder<'a, A, T>
 where
     A: GeminiApi,
-    T: serde::de::DeserializeOwned + serde::Serialize + Send + Clone + 'static,
+    T: TextPayload + Send + Sync + Clone + 'static,
 {
     /// Creates a new builder for the given chat
     fn new(chat: &'a mut GeminiChat<A>) -> Self {
         Self {
             chat,
             message_parts: None,
-            generation_config: None,
-            safety_settings: None,
+            options: MessageOptions::default(),
+            branch_at: None,
         }
     }
 
+    /// Sends the message as a new branch after the first `len` messages of history
+    fn branch_at(mut self, len: usize) -> Self {
+        self.branch_at = Some(len);
+        self
+    }
+
     /// Set the message as plain text
     ///
     /// # Arguments
@@ -391,6 +970,9 @@ where
 
     /// Set optional generation configuration for this message
     ///
+    /// Merged with the chat's default generation config, taking precedence for every
+    /// option it sets.
+    ///
     /// # Arguments
     /// * `config` - Generation configuration with temperature, tokens, etc.
     ///
@@ -405,7 +987,7 @@ where
     ///     .await?;
     /// ```
     pub fn generation_config(mut self, config: GenerationConfig<T>) -> Self {
-        self.generation_config = Some(config);
+        self.options.generation_config = Some(config);
         self
     }
 
@@ -418,80 +1000,84 @@ where
     /// ```ignore
     /// chat.send_message()
     ///     .text("Tell me about...")
-    ///     .safety_settings(vec![SafetySetting {
-    ///         category: "HARM_CATEGORY_DANGEROUS".into(),
-    ///         threshold: "BLOCK_MEDIUM_AND_ABOVE".into(),
-    ///     }])
+    ///     .safety_settings(vec![SafetySetting::block_medium_and_above(
+    ///         HarmCategory::DangerousContent,
+    ///     )])
     ///     .send()
     ///     .await?;
     /// ```
     pub fn safety_settings(mut self, settings: Vec<SafetySetting>) -> Self {
-        self.safety_settings = Some(settings);
+        self.options.safety_settings = Some(settings);
         self
     }
 
-    /// Adds user message to history
-    fn add_user_message_to_history(&mut self, parts: Vec<Part<String>>) {
-        self.chat.history.push(Content::user(parts));
-    }
-
-    /// Converts history to Content objects for the request
-    fn convert_history_to_contents(&self) -> Vec<Content> {
-        self.chat.history.clone()
+    message_override_setters!();
+
+    /// Selects the history to send, followed by the pending user message
+    async fn convert_history_to_contents(
+        &mut self,
+        message: &Content,
+    ) -> Result<Vec<Content>, Box<dyn Error>> {
+        let history = self.chat.tree.history();
+        let mut contents = history[..self.branch_at.unwrap_or(history.len())].to_vec();
+        contents.push(message.clone());
+        self.chat.history_strategy.select(contents).await
     }
 
-    /// Builds the request with contents and optional config
-    fn build_request(&self, contents: Vec<Content>) -> GenerateContentRequest<T> {
-        let mut request_builder = GenerateContentRequest::<T>::builder().contents(contents);
-
-        if let Some(config) = &self.generation_config {
-            request_builder = request_builder.generation_config(config.clone());
-        }
-
-        if let Some(settings) = &self.safety_settings {
-            request_builder = request_builder.safety_settings(settings.clone());
-        }
-
-        request_builder.build()
+    /// Builds the request with contents, message options and chat defaults
+    fn build_request(&mut self, contents: Vec<Content>) -> GenerateContentRequest<T> {
+        let options = std::mem::take(&mut self.options);
+        self.chat.defaults.build_request(contents, options)
     }
 
     /// Converts response parts to string parts for history storage
+    ///
+    /// Thought summaries are dropped unless they carry a thought signature, and
+    /// signatures and function calls are kept so thinking models can continue the turn.
+    /// Generated media is kept in place, so interleaved text and images survive.
     fn convert_response_to_string_parts<U>(parts: &[Part<U>]) -> Vec<Part<String>>
     where
-        U: serde::Serialize,
+        U: TextPayload,
     {
         parts
             .iter()
-            .map(|part| {
-                if let Some(text) = part.text() {
-                    let json_str = serde_json::to_string(text).unwrap_or_else(|_| "".to_string());
-                    Part::builder().text(JsonString::new(json_str)).build()
-                } else {
-                    Part::builder()
-                        .text(JsonString::new("".to_string()))
-                        .build()
-                }
-            })
+            .filter(|part| keep_in_history(part))
+            .map(history_part)
             .collect()
     }
 
-    /// Adds model response to history
-    fn add_model_response_to_history(&mut self, response: &GenerateContentResponse<T>) {
+    /// Commits the user message and model response of a completed turn to history
+    fn commit_turn(&mut self, message: Content, response: &GenerateContentResponse<T>) {
         if let Some(candidate) = response.candidates.first() {
             let string_parts = Self::convert_response_to_string_parts(candidate.content.parts());
-            self.chat.history.push(Content::model(string_parts));
+            if let Some(len) = self.branch_at {
+                self.chat.tree.truncate(len);
+            }
+            self.chat.tree.push(message);
+            self.chat.tree.push(Content::model(string_parts));
+            if let Some(node) = self.chat.tree.active()
+                && let Some(value) = candidate.content.parts().iter().find_map(Part::text)
+            {
+                self.chat.typed.insert(node, Arc::new(value.clone()));
+            }
         }
     }
 
     /// Send the message and update chat history
     ///
     /// This method:
-    /// 1. Wraps the message in `Content::User` and adds to history
-    /// 2. Builds a request with full conversation history
+    /// 1. Wraps the message in `Content::User`
+    /// 2. Builds a request with the history selected by the chat's `HistoryStrategy`,
+    ///    followed by the message
     /// 3. Calls the API with optional config and safety settings
-    /// 4. Extracts the response and adds it to history as `Content::Model`
+    /// 4. Adds the message and the response, as `Content::Model`, to history
     /// 5. Returns the typed response
     ///
+    /// The turn is transactional: history is only updated once a response candidate is
+    /// received, so a failed request leaves it untouched and can simply be retried.
+    /// Blocked turns are never added to history. Depending on the client's `BlockPolicy`,
+    /// the blocked response is either returned as-is or as a `BlockedError`.
+    ///
     /// # Returns
     /// The model's response, or an error if the request fails
     ///
@@ -499,21 +1085,31 @@ where
     /// Returns an error if:
     /// - No message parts were set
     /// - The API call fails
+    /// - The response was blocked and the client uses `BlockPolicy::Error`
     pub async fn send(mut self) -> Result<GenerateContentResponse<T>, Box<dyn Error>> {
         let parts = self
             .message_parts
             .take()
             .ok_or("Message parts must be set before sending")?;
 
-        self.add_user_message_to_history(parts);
+        let message = Content::user(parts);
 
-        let contents = self.convert_history_to_contents();
+        if self.branch_at.is_some() {
+            self.chat.history_strategy.reset();
+        }
+        let contents = self.convert_history_to_contents(&message).await?;
 
         let request = self.build_request(contents);
 
         let response = self.chat.api.generate_content(request).await?;
 
-        self.add_model_response_to_history(&response);
+        if let Some(usage) = &response.usage_metadata {
+            self.chat.usage.push(usage.clone());
+        }
+
+        if !response.is_blocked() {
+            self.commit_turn(message, &response);
+        }
 
         Ok(response)
     }
@@ -529,8 +1125,20 @@ where
 pub struct GeminiStreamChat<A> {
     /// The underlying streaming API client
     api: A,
-    /// Conversation history with typed Content messages
-    history: Vec<Content<String>>,
+    /// Conversation history, with every branch
+    tree: ChatTree,
+    /// Typed values of model turns
+    typed: TypedResponses,
+    /// Token usage of each request made by this chat
+    usage: Vec<UsageMetadata>,
+    /// When the conversation was started
+    created_at: SystemTime,
+    /// Request options applied to every turn
+    defaults: ChatDefaults,
+    /// Selects the history sent with each turn
+    history_strategy: Box<dyn HistoryStrategy>,
+    /// How streams that end early are recorded in history
+    partial_turn_policy: PartialTurnPolicy,
 }
 
 impl<A> GeminiStreamChat<A>
@@ -549,7 +1157,13 @@ where
     pub fn new(api: A) -> Self {
         Self {
             api,
-            history: Vec::new(),
+            tree: ChatTree::new(),
+            usage: Vec::new(),
+            created_at: SystemTime::now(),
+            typed: TypedResponses::default(),
+            defaults: ChatDefaults::default(),
+            history_strategy: Box::new(FullHistory),
+            partial_turn_policy: PartialTurnPolicy::default(),
         }
     }
 
@@ -559,7 +1173,43 @@ where
     /// * `api` - The GeminiStreamingApi implementor to use for API calls
     /// * `history` - Previous conversation history to restore
     pub fn from_history(api: A, history: Vec<Content<String>>) -> Self {
-        Self { api, history }
+        Self {
+            api,
+            tree: ChatTree::from_history(history),
+            usage: Vec::new(),
+            created_at: SystemTime::now(),
+            typed: TypedResponses::default(),
+            defaults: ChatDefaults::default(),
+            history_strategy: Box::new(FullHistory),
+            partial_turn_policy: PartialTurnPolicy::default(),
+        }
+    }
+
+    /// Starts building a chat session with options applied to every turn
+    pub fn builder(api: A) -> GeminiStreamChatBuilder<A> {
+        GeminiStreamChatBuilder::new(api)
+    }
+
+    /// Restores a chat from a saved session
+    ///
+    /// Use [`GeminiStreamChatBuilder::session`] to also set a history strategy.
+    pub fn from_session(api: A, session: ChatSession) -> Self {
+        Self::builder(api).session(session).build()
+    }
+
+    /// Captures the chat as a session to save under `id`, updated now
+    ///
+    /// # Example
+    /// ```ignore
+    /// store.save(&chat.to_session("support-42")).await?;
+    /// ```
+    pub fn to_session(&self, id: impl Into<String>) -> ChatSession {
+        self.defaults.to_session(
+            id.into(),
+            self.tree.clone(),
+            self.usage.clone(),
+            self.created_at,
+        )
     }
 
     /// Begin building a streaming message to send
@@ -576,7 +1226,7 @@ where
     /// ```
     pub fn send_message_stream<T>(&mut self) -> SendMessageStreamBuilder<'_, A, T>
     where
-        T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+        T: TextPayload + Send + Sync + 'static,
     {
         SendMessageStreamBuilder::new(self)
     }
@@ -586,39 +1236,258 @@ where
     /// Returns a slice of `Content<String>` that can be serialized directly
     /// for persistence using `serde_json::to_string()`.
     pub fn get_history(&self) -> &[Content<String>] {
-        &self.history
+        self.tree.history()
     }
 
     /// Clear the conversation history
     pub fn clear_history(&mut self) {
-        self.history.clear();
+        self.tree.clear();
+        self.typed = TypedResponses::default();
+        self.history_strategy.reset();
     }
-}
 
-/// Builder for sending streaming messages with optional configuration
-///
-/// Created via `GeminiStreamChat::send_message_stream()`
-pub struct SendMessageStreamBuilder<'a, A, T> {
-    chat: &'a mut GeminiStreamChat<A>,
-    message_parts: Option<Vec<Part<String>>>,
-    generation_config: Option<GenerationConfig<T>>,
-    safety_settings: Option<Vec<SafetySetting>>,
-}
+    /// Returns the conversation tree, with every branch and the active one
+    ///
+    /// Serialize the tree instead of [`Self::get_history`] to keep alternative branches.
+    pub fn tree(&self) -> &ChatTree {
+        &self.tree
+    }
 
-impl<'a, A, T> SendMessageStreamBuilder<'a, A, T>
-where
-    A: GeminiStreamingApi,
-    T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
-{
-    fn new(chat: &'a mut GeminiStreamChat<A>) -> Self {
-        Self {
-            chat,
+    /// Returns the typed value of the model turn at `index` of the history
+    ///
+    /// A model turn keeps the value of its first text part, in the type its message
+    /// was sent with, so earlier structured responses need not be parsed again.
+    /// Returns `None` if the turn was requested as another type, or restored from
+    /// saved history, which only holds the raw text.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let plan: Option<&Json<TripPlan>> = chat.typed_response(1);
+    /// ```
+    pub fn typed_response<T: 'static>(&self, index: usize) -> Option<&T> {
+        let node = *self.tree.active_path().get(index)?;
+        self.typed.get(node)
+    }
+
+    /// Returns the model turns of the history that were requested as `T`, with their index
+    pub fn typed_responses<T: 'static>(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.tree
+            .active_path()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &node)| Some((index, self.typed.get(node)?)))
+    }
+
+    /// Continues the conversation from the branch ending at node `id` of the tree
+    ///
+    /// # Errors
+    /// Returns `BranchError::NoSuchNode` if the tree has no node `id`.
+    pub fn checkout(&mut self, id: usize) -> Result<(), BranchError> {
+        self.tree.checkout(id)?;
+        self.history_strategy.reset();
+        Ok(())
+    }
+
+    /// Starts a message asking the model for another answer to the last user message
+    ///
+    /// Once sent, the new answer is the active branch; the previous one is kept in
+    /// the tree. The message can be configured like any other before sending.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let response = chat.regenerate_last()?.send().await?;
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `BranchError::NothingToRegenerate` if the history has no user message.
+    pub fn regenerate_last<T>(&mut self) -> Result<SendMessageStreamBuilder<'_, A, T>, BranchError>
+    where
+        T: TextPayload + Send + Sync + 'static,
+    {
+        let history = self.tree.history();
+        let index = *turn_starts(history)
+            .last()
+            .ok_or(BranchError::NothingToRegenerate)?;
+        let parts = history[index].parts().to_vec();
+        Ok(SendMessageStreamBuilder::new(self)
+            .branch_at(index)
+            .parts(parts))
+    }
+
+    /// Starts a message replacing the user message at `index` of the history
+    ///
+    /// Once sent, the edited message and its answer are the active branch, and
+    /// the original message with everything after it is kept in the tree.
+    ///
+    /// # Errors
+    /// Returns an error if `index` is not the index of a user message starting a turn.
+    pub fn edit_turn<T>(
+        &mut self,
+        index: usize,
+        parts: Vec<Part<String>>,
+    ) -> Result<SendMessageStreamBuilder<'_, A, T>, BranchError>
+    where
+        T: TextPayload + Send + Sync + 'static,
+    {
+        let history = self.tree.history();
+        if index >= history.len() {
+            return Err(BranchError::NoSuchTurn(index));
+        }
+        if !turn_starts(history).contains(&index) {
+            return Err(BranchError::NotAUserTurn(index));
+        }
+        Ok(SendMessageStreamBuilder::new(self)
+            .branch_at(index)
+            .parts(parts))
+    }
+
+    /// Creates an independent chat continuing from the active branch
+    ///
+    /// The fork shares the history so far, options and a copy of the history
+    /// strategy, but not the other branches or the recorded usage.
+    pub fn fork(&self) -> Self
+    where
+        A: Clone,
+    {
+        Self {
+            api: self.api.clone(),
+            tree: ChatTree::from_history(self.get_history().to_vec()),
+            usage: Vec::new(),
+            created_at: SystemTime::now(),
+            typed: self.typed.for_branch(self.tree.active_path()),
+            defaults: self.defaults.clone(),
+            history_strategy: self.history_strategy.clone(),
+            partial_turn_policy: self.partial_turn_policy,
+        }
+    }
+
+    /// Returns the token usage summed over all completed streams of this chat
+    ///
+    /// Streamed chunks report cumulative usage, so only the last reported usage of
+    /// each stream is added once the stream completes or is dropped.
+    pub fn usage(&self) -> UsageMetadata {
+        self.usage.iter().cloned().sum()
+    }
+
+    /// Returns the token usage of each completed stream of this chat
+    pub fn turn_usage(&self) -> &[UsageMetadata] {
+        &self.usage
+    }
+
+    /// Returns the cost of all completed streams of this chat
+    ///
+    /// Each turn is priced separately, so tiered rates follow that turn's prompt length.
+    pub fn cost(&self, pricing: &ModelPricing) -> Cost {
+        self.usage.iter().map(|usage| pricing.cost(usage)).sum()
+    }
+}
+
+/// Builder for a [`GeminiStreamChat`] with options applied to every turn
+///
+/// Created via `GeminiStreamChat::builder()`. Options set on a message override these
+/// defaults for that turn only.
+pub struct GeminiStreamChatBuilder<A> {
+    api: A,
+    tree: ChatTree,
+    usage: Vec<UsageMetadata>,
+    created_at: Option<SystemTime>,
+    defaults: ChatDefaults,
+    history_strategy: Box<dyn HistoryStrategy>,
+    partial_turn_policy: PartialTurnPolicy,
+}
+
+impl<A> GeminiStreamChatBuilder<A>
+where
+    A: GeminiStreamingApi,
+{
+    fn new(api: A) -> Self {
+        Self {
+            api,
+            tree: ChatTree::new(),
+            usage: Vec::new(),
+            created_at: None,
+            defaults: ChatDefaults::default(),
+            history_strategy: Box::new(FullHistory),
+            partial_turn_policy: PartialTurnPolicy::default(),
+        }
+    }
+
+    /// Restores previously saved conversation history
+    #[must_use]
+    pub fn history(mut self, history: Vec<Content<String>>) -> Self {
+        self.tree = ChatTree::from_history(history);
+        self
+    }
+
+    /// Restores a previously saved conversation tree, with all its branches
+    #[must_use]
+    pub fn tree(mut self, tree: ChatTree) -> Self {
+        self.tree = tree;
+        self
+    }
+
+    chat_default_setters!();
+
+    /// Sets how streams that end early are recorded in history
+    #[must_use]
+    pub fn partial_turn_policy(mut self, policy: PartialTurnPolicy) -> Self {
+        self.partial_turn_policy = policy;
+        self
+    }
+
+    /// Sets how much of the history is sent with each turn, all of it by default
+    #[must_use]
+    pub fn history_strategy(mut self, strategy: impl HistoryStrategy + 'static) -> Self {
+        self.history_strategy = Box::new(strategy);
+        self
+    }
+
+    /// Creates the chat session
+    pub fn build(self) -> GeminiStreamChat<A> {
+        GeminiStreamChat {
+            api: self.api,
+            tree: self.tree,
+            usage: self.usage,
+            created_at: self.created_at.unwrap_or_else(SystemTime::now),
+            typed: TypedResponses::default(),
+            defaults: self.defaults,
+            history_strategy: self.history_strategy,
+            partial_turn_policy: self.partial_turn_policy,
+        }
+    }
+}
+
+/// Builder for sending streaming messages with optional configuration
+///
+/// Created via `GeminiStreamChat::send_message_stream()`
+pub struct SendMessageStreamBuilder<'a, A, T> {
+    chat: &'a mut GeminiStreamChat<A>,
+    message_parts: Option<Vec<Part<String>>>,
+    options: MessageOptions<T>,
+    /// Length of the history prefix the turn branches from, if not the whole history
+    branch_at: Option<usize>,
+}
+
+impl<'a, A, T> SendMessageStreamBuilder<'a, A, T>
+where
+    A: GeminiStreamingApi,
+    T: TextPayload + Send + Sync + 'static,
+{
+    fn new(chat: &'a mut GeminiStreamChat<A>) -> Self {
+        Self {
+            chat,
             message_parts: None,
-            generation_config: None,
-            safety_settings: None,
+            options: MessageOptions::default(),
+            branch_at: None,
         }
     }
 
+    /// Sends the message as a new branch after the first `len` messages of history
+    fn branch_at(mut self, len: usize) -> Self {
+        self.branch_at = Some(len);
+        self
+    }
+
     /// Set the message as plain text
     ///
     /// # Arguments
@@ -661,7 +1530,7 @@ where
     /// # Arguments
     /// * `config` - The generation config to use for this message
     pub fn generation_config(mut self, config: GenerationConfig<T>) -> Self {
-        self.generation_config = Some(config);
+        self.options.generation_config = Some(config);
         self
     }
 
@@ -670,39 +1539,39 @@ where
     /// # Arguments
     /// * `settings` - Vector of safety settings to apply
     pub fn safety_settings(mut self, settings: Vec<SafetySetting>) -> Self {
-        self.safety_settings = Some(settings);
+        self.options.safety_settings = Some(settings);
         self
     }
 
-    fn add_user_message_to_history(&mut self, parts: Vec<Part<String>>) {
-        self.chat.history.push(Content::User { parts });
-    }
+    message_override_setters!();
 
-    fn convert_history_to_contents(&self) -> Vec<Content> {
-        self.chat.history.clone()
+    async fn convert_history_to_contents(
+        &mut self,
+        message: &Content,
+    ) -> Result<Vec<Content>, Box<dyn Error>> {
+        let history = self.chat.tree.history();
+        let mut contents = history[..self.branch_at.unwrap_or(history.len())].to_vec();
+        contents.push(message.clone());
+        self.chat.history_strategy.select(contents).await
     }
 
     fn build_request(&mut self, contents: Vec<Content>) -> GenerateContentRequest<T> {
-        let mut builder = GenerateContentRequest::builder().contents(contents);
-
-        if let Some(config) = self.generation_config.take() {
-            builder = builder.generation_config(config);
-        }
-
-        if let Some(settings) = self.safety_settings.take() {
-            builder = builder.safety_settings(settings);
-        }
-
-        builder.build()
+        let options = std::mem::take(&mut self.options);
+        self.chat.defaults.build_request(contents, options)
     }
 
     /// Send the message and return a streaming response
     ///
     /// This method:
-    /// 1. Wraps the message in `Content::User` and adds to history
-    /// 2. Builds a request with full conversation history
+    /// 1. Wraps the message in `Content::User`
+    /// 2. Builds a request with the history selected by the chat's `HistoryStrategy`,
+    ///    followed by the message
     /// 3. Calls the streaming API with optional config and safety settings
-    /// 4. Returns a `BufferedChatStream` that buffers the response and updates history on completion
+    /// 4. Returns a `BufferedChatStream` that buffers the response and adds the message
+    ///    and response to history on completion
+    ///
+    /// History is left untouched if the request fails. Streams that end early are handled
+    /// according to the chat's [`PartialTurnPolicy`].
     ///
     /// # Returns
     /// A stream that yields response chunks and updates history when complete
@@ -717,85 +1586,295 @@ where
             .take()
             .ok_or("Message parts must be set before sending")?;
 
-        self.add_user_message_to_history(parts);
+        let message = Content::user(parts);
 
-        let contents = self.convert_history_to_contents();
+        if self.branch_at.is_some() {
+            self.chat.history_strategy.reset();
+        }
+        let contents = self.convert_history_to_contents(&message).await?;
 
         let request = self.build_request(contents);
 
         let stream = self.chat.api.stream_generate_content(request).await?;
 
-        Ok(BufferedChatStream::new(stream, &mut self.chat.history))
+        let text_mode = self.chat.api.stream_text_mode();
+        Ok(BufferedChatStream::new(
+            stream,
+            message,
+            self.chat.partial_turn_policy,
+            &mut self.chat.tree,
+            self.branch_at,
+            &mut self.chat.typed,
+            &mut self.chat.usage,
+        )
+        .text_mode(text_mode))
     }
 }
 
+/// Text of the part appended to interrupted turns kept with [`PartialTurnPolicy::KeepMarked`]
+pub const INCOMPLETE_TURN_MARKER: &str = "[response interrupted]";
+
+/// How a streamed turn that did not complete is recorded in history
+///
+/// A turn is partial when its stream is dropped before the end or yields an error.
+/// Blocked turns are always discarded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PartialTurnPolicy {
+    /// Leave history untouched, as if the message was never sent
+    #[default]
+    Discard,
+    /// Keep the message and the partial response, followed by an
+    /// [`INCOMPLETE_TURN_MARKER`] part so the model knows its answer was cut off
+    KeepMarked,
+    /// Keep the message and the partial response as if it had completed
+    Keep,
+}
+
 /// A streaming response wrapper that buffers content and updates chat history on completion
 ///
-/// This stream forwards chunks to the caller while buffering text content internally.
-/// When the stream completes, it constructs a `Content::Model` from the buffered content
-/// and appends it to the conversation history.
+/// This stream forwards chunks to the caller while buffering their raw text internally,
+/// appending deltas or taking the new end of cumulative text as the API's
+/// [`StreamTextMode`] declares. Function calls, media and thought signatures are buffered
+/// in order, as non-streaming turns keep them. When the stream completes, it appends the user message and a `Content::Model` built
+/// from the buffered content to the conversation history, and records the text parsed as
+/// `T` for [`GeminiStreamChat::typed_response`]. Streams that are dropped early
+/// or yield an error are handled according to the chat's [`PartialTurnPolicy`].
+///
+/// If any chunk reports a blocked prompt or candidate (either as a blocked response or as
+/// a `BlockedError`), the turn is discarded and history is left untouched.
+///
+/// The last usage metadata seen on the stream is recorded as the turn's usage when the
+/// stream finishes.
 pub struct BufferedChatStream<'a, T> {
     inner: BoxResponseStream<T>,
-    history: &'a mut Vec<Content<String>>,
-    buffer: Vec<String>,
+    text_mode: StreamTextMode,
+    /// Length of the cumulative answer text seen so far, with `StreamTextMode::Cumulative`
+    received_text: usize,
+    message: Option<Content<String>>,
+    partial_turn_policy: PartialTurnPolicy,
+    tree: &'a mut ChatTree,
+    branch_at: Option<usize>,
+    typed: &'a mut TypedResponses,
+    parse_typed: fn(String) -> Option<Arc<dyn Any + Send + Sync>>,
+    usage: &'a mut Vec<UsageMetadata>,
+    last_usage: Option<UsageMetadata>,
+    buffer: Vec<BufferedPart>,
+    blocked: bool,
+    failed: bool,
     completed: bool,
 }
 
+/// Content buffered from a response stream until it is added to history
+enum BufferedPart {
+    /// Text from consecutive chunks, joined together, and the signature the model put on it
+    Text {
+        text: String,
+        signature: Option<String>,
+    },
+    /// Any other part kept in history, such as a function call or generated image
+    Other(Box<Part<String>>),
+}
+
 impl<'a, T> BufferedChatStream<'a, T> {
-    fn new(stream: BoxResponseStream<T>, history: &'a mut Vec<Content<String>>) -> Self {
+    fn new(
+        stream: BoxResponseStream<T>,
+        message: Content<String>,
+        partial_turn_policy: PartialTurnPolicy,
+        tree: &'a mut ChatTree,
+        branch_at: Option<usize>,
+        typed: &'a mut TypedResponses,
+        usage: &'a mut Vec<UsageMetadata>,
+    ) -> Self
+    where
+        T: TextPayload + Send + Sync + 'static,
+    {
         Self {
             inner: stream,
-            history,
+            text_mode: StreamTextMode::default(),
+            received_text: 0,
+            message: Some(message),
+            partial_turn_policy,
+            tree,
+            branch_at,
+            typed,
+            parse_typed: TypedResponses::parse::<T>,
+            usage,
+            last_usage: None,
             buffer: Vec::new(),
+            blocked: false,
+            failed: false,
             completed: false,
         }
     }
 
-    fn extract_text_from_response(response: &GenerateContentResponse<T>) -> Vec<String>
+    /// Sets how the text of successive chunks relates, as declared by the API
+    fn text_mode(mut self, text_mode: StreamTextMode) -> Self {
+        self.text_mode = text_mode;
+        self
+    }
+
+    /// Buffers the parts of a chunk, merging text with preceding text
+    ///
+    /// Text parts are appended as is, except that with `StreamTextMode::Cumulative` only
+    /// the first answer text of a chunk is cut to the part beyond the text received so
+    /// far; if it is shorter than that it breaks the contract and its text is dropped.
+    /// Text is not merged across two thought signatures, so each stays on its own part.
+    fn buffer_response(&mut self, response: &GenerateContentResponse<T>)
     where
-        T: Clone + ToString,
+        T: TextPayload,
     {
-        response
-            .candidates
+        let Some(content) = response.first_content() else {
+            return;
+        };
+        let mut cumulative = self.text_mode == StreamTextMode::Cumulative;
+        for part in content.parts().iter().filter(|part| keep_in_history(part)) {
+            if let Some(text) = part_text(part) {
+                let delta = if std::mem::take(&mut cumulative) {
+                    match text.get(self.received_text..) {
+                        Some(delta) => {
+                            self.received_text = text.len();
+                            delta
+                        }
+                        None => {
+                            tracing::warn!(
+                                "Dropping cumulative text of {} bytes after {} bytes of text",
+                                text.len(),
+                                self.received_text
+                            );
+                            ""
+                        }
+                    }
+                } else {
+                    &*text
+                };
+                let part_signature = part.thought_signature();
+                if delta.is_empty() && part_signature.is_none() {
+                    continue;
+                }
+                match self.buffer.last_mut() {
+                    Some(BufferedPart::Text { text, signature })
+                        if signature.is_none() || part_signature.is_none() =>
+                    {
+                        text.push_str(delta);
+                        if let Some(part_signature) = part_signature {
+                            *signature = Some(part_signature.to_string());
+                        }
+                    }
+                    _ => self.buffer.push(BufferedPart::Text {
+                        text: delta.to_string(),
+                        signature: part_signature.map(str::to_string),
+                    }),
+                }
+            } else {
+                self.buffer
+                    .push(BufferedPart::Other(Box::new(history_part(part))));
+            }
+        }
+    }
+
+    /// Commits the turn to history once, when the stream ends or is dropped
+    ///
+    /// `finished` is true when the inner stream was read to the end. Completed turns
+    /// also record the first text part parsed as `T`.
+    fn finalize_history(&mut self, finished: bool) {
+        if self.completed {
+            return;
+        }
+        self.completed = true;
+
+        if let Some(usage) = self.last_usage.take() {
+            self.usage.push(usage);
+        }
+
+        let Some(message) = self.message.take() else {
+            return;
+        };
+        if self.blocked {
+            return;
+        }
+
+        let complete = finished && !self.failed;
+        let typed_value = self
+            .buffer
             .iter()
-            .flat_map(|candidate| {
-                candidate
-                    .content
-                    .parts()
-                    .iter()
-                    .filter_map(|part| part.text().map(|text| text.to_string()))
+            .find_map(|buffered| match buffered {
+                BufferedPart::Text { text, .. } if complete => Some(text.clone()),
+                _ => None,
             })
-            .collect()
-    }
+            .and_then(self.parse_typed);
+
+        let mut parts: Vec<Part<String>> = std::mem::take(&mut self.buffer)
+            .into_iter()
+            .map(|buffered| match buffered {
+                BufferedPart::Text { text, signature } => {
+                    let mut builder = Part::builder().text(JsonString::new(text));
+                    if let Some(signature) = signature {
+                        builder = builder.thought_signature(signature);
+                    }
+                    builder.build()
+                }
+                BufferedPart::Other(part) => *part,
+            })
+            .collect();
 
-    fn finalize_history(&mut self) {
-        if !self.completed && !self.buffer.is_empty() {
-            let combined_text = self.buffer.join("");
-            let parts = vec![Part::builder().text(JsonString::new(combined_text)).build()];
-            self.history.push(Content::Model { parts });
-            self.completed = true;
+        if !complete {
+            match self.partial_turn_policy {
+                PartialTurnPolicy::Discard => return,
+                PartialTurnPolicy::KeepMarked => parts.push(
+                    Part::builder()
+                        .text(JsonString::new(INCOMPLETE_TURN_MARKER.to_string()))
+                        .build(),
+                ),
+                PartialTurnPolicy::Keep => {}
+            }
+        }
+
+        if !parts.is_empty() {
+            if let Some(len) = self.branch_at {
+                self.tree.truncate(len);
+            }
+            self.tree.push(message);
+            self.tree.push(Content::Model { parts });
+            if let Some(node) = self.tree.active()
+                && let Some(value) = typed_value
+            {
+                self.typed.insert(node, value);
+            }
         }
     }
 }
 
 impl<'a, T> Stream for BufferedChatStream<'a, T>
 where
-    T: Unpin + Clone + ToString,
+    T: TextPayload + Send + Sync + 'static,
 {
     type Item = Result<GenerateContentResponse<T>, Box<dyn Error + Send + Sync>>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         match self.inner.as_mut().poll_next(cx) {
             Poll::Ready(Some(Ok(response))) => {
-                // Buffer text content from this chunk
-                let texts = Self::extract_text_from_response(&response);
-                self.buffer.extend(texts);
+                if let Some(usage) = &response.usage_metadata {
+                    self.last_usage = Some(usage.clone());
+                }
+                if response.is_blocked() {
+                    self.blocked = true;
+                } else {
+                    // Buffer the content of this chunk
+                    self.buffer_response(&response);
+                }
                 Poll::Ready(Some(Ok(response)))
             }
-            Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e))),
+            Poll::Ready(Some(Err(e))) => {
+                if e.downcast_ref::<BlockedError>().is_some() {
+                    self.blocked = true;
+                } else {
+                    self.failed = true;
+                }
+                Poll::Ready(Some(Err(e)))
+            }
             Poll::Ready(None) => {
                 // Stream complete - finalize history
-                self.finalize_history();
+                self.finalize_history(true);
                 Poll::Ready(None)
             }
             Poll::Pending => Poll::Pending,
@@ -805,8 +1884,8 @@ where
 
 impl<'a, T> Drop for BufferedChatStream<'a, T> {
     fn drop(&mut self) {
-        // Ensure history is updated even if stream is dropped early
-        self.finalize_history();
+        // A stream dropped before its end is a partial turn
+        self.finalize_history(false);
     }
 }
 
@@ -816,6 +1895,25 @@ mod tests {
     use crate::api::GeminiApi;
     use futures::stream::StreamExt;
 
+    /// Builds a model candidate answering with `text`
+    fn candidate(text: &str) -> crate::dto_response::Candidate<String> {
+        crate::dto_response::Candidate {
+            content: Content::model(vec![
+                Part::builder()
+                    .text(JsonString::new(text.to_string()))
+                    .build(),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_chats_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<GeminiChat<crate::client::GeminiV1Beta>>();
+        assert_send_sync::<GeminiStreamChat<crate::client::GeminiV1Beta>>();
+    }
+
     #[test]
     fn test_gemini_chat_new() {
         struct MockApi;
@@ -826,14 +1924,14 @@ mod tests {
                 _request: GenerateContentRequest<T>,
             ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 unimplemented!()
             }
         }
 
         let chat = GeminiChat::new(MockApi);
-        assert_eq!(chat.history.len(), 0);
+        assert_eq!(chat.get_history().len(), 0);
     }
 
     #[test]
@@ -846,7 +1944,7 @@ mod tests {
                 _request: GenerateContentRequest<T>,
             ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 unimplemented!()
             }
@@ -866,9 +1964,9 @@ mod tests {
         ];
 
         let chat = GeminiChat::from_history(MockApi, history);
-        assert_eq!(chat.history.len(), 2);
-        assert!(chat.history[0].is_user());
-        assert!(chat.history[1].is_model());
+        assert_eq!(chat.get_history().len(), 2);
+        assert!(chat.get_history()[0].is_user());
+        assert!(chat.get_history()[1].is_model());
     }
 
     #[test]
@@ -881,7 +1979,7 @@ mod tests {
                 _request: GenerateContentRequest<T>,
             ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 unimplemented!()
             }
@@ -907,7 +2005,7 @@ mod tests {
                 _request: GenerateContentRequest<T>,
             ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 unimplemented!()
             }
@@ -920,10 +2018,10 @@ mod tests {
         ])];
 
         let mut chat = GeminiChat::from_history(MockApi, history);
-        assert_eq!(chat.history.len(), 1);
+        assert_eq!(chat.get_history().len(), 1);
 
         chat.clear_history();
-        assert_eq!(chat.history.len(), 0);
+        assert_eq!(chat.get_history().len(), 0);
     }
 
     #[tokio::test]
@@ -936,7 +2034,7 @@ mod tests {
                 request: GenerateContentRequest<T>,
             ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 // Verify request has content
                 assert_eq!(request.contents().len(), 1);
@@ -945,13 +2043,8 @@ mod tests {
                 // Return mock response with String type
                 let string_response = GenerateContentResponse {
                     candidates: vec![crate::dto_response::Candidate {
-                        content: Content::model(vec![
-                            Part::builder()
-                                .text(JsonString::new("Mock response".to_string()))
-                                .build(),
-                        ]),
-                        finish_reason: Some("STOP".to_string()),
-                        safety_ratings: vec![],
+                        finish_reason: Some(crate::dto_response::FinishReason::Stop),
+                        ..candidate("Mock response")
                     }],
                     prompt_feedback: None,
                     usage_metadata: None,
@@ -971,9 +2064,9 @@ mod tests {
             .await
             .expect("Failed to send message");
 
-        assert_eq!(chat.history.len(), 2); // User + Model
-        assert!(chat.history[0].is_user());
-        assert!(chat.history[1].is_model());
+        assert_eq!(chat.get_history().len(), 2); // User + Model
+        assert!(chat.get_history()[0].is_user());
+        assert!(chat.get_history()[1].is_model());
         assert_eq!(response.first_text().unwrap(), "Mock response");
     }
 
@@ -987,20 +2080,15 @@ mod tests {
                 request: GenerateContentRequest<T>,
             ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 // Verify config was passed
                 assert!(request.generation_config().is_some());
 
                 let string_response = GenerateContentResponse {
                     candidates: vec![crate::dto_response::Candidate {
-                        content: Content::model(vec![
-                            Part::builder()
-                                .text(JsonString::new("Response".to_string()))
-                                .build(),
-                        ]),
-                        finish_reason: Some("STOP".to_string()),
-                        safety_ratings: vec![],
+                        finish_reason: Some(crate::dto_response::FinishReason::Stop),
+                        ..candidate("Response")
                     }],
                     prompt_feedback: None,
                     usage_metadata: None,
@@ -1025,7 +2113,7 @@ mod tests {
             .await
             .expect("Failed to send message");
 
-        assert_eq!(chat.history.len(), 2);
+        assert_eq!(chat.get_history().len(), 2);
         assert!(response.first_text().is_some());
     }
 
@@ -1039,21 +2127,13 @@ mod tests {
                 request: GenerateContentRequest<T>,
             ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 // Return response with incremented history
                 let string_response = GenerateContentResponse {
                     candidates: vec![crate::dto_response::Candidate {
-                        content: Content::model(vec![
-                            Part::builder()
-                                .text(JsonString::new(format!(
-                                    "Response {}",
-                                    request.contents().len()
-                                )))
-                                .build(),
-                        ]),
-                        finish_reason: Some("STOP".to_string()),
-                        safety_ratings: vec![],
+                        finish_reason: Some(crate::dto_response::FinishReason::Stop),
+                        ..candidate(&format!("Response {}", request.contents().len()))
                     }],
                     prompt_feedback: None,
                     usage_metadata: None,
@@ -1073,7 +2153,7 @@ mod tests {
             .send()
             .await
             .expect("Failed to send");
-        assert_eq!(chat.history.len(), 2);
+        assert_eq!(chat.get_history().len(), 2);
 
         // Send second message - should include previous history
         let _: GenerateContentResponse<String> = chat
@@ -1082,13 +2162,13 @@ mod tests {
             .send()
             .await
             .expect("Failed to send");
-        assert_eq!(chat.history.len(), 4);
+        assert_eq!(chat.get_history().len(), 4);
 
         // Verify history order
-        assert!(chat.history[0].is_user());
-        assert!(chat.history[1].is_model());
-        assert!(chat.history[2].is_user());
-        assert!(chat.history[3].is_model());
+        assert!(chat.get_history()[0].is_user());
+        assert!(chat.get_history()[1].is_model());
+        assert!(chat.get_history()[2].is_user());
+        assert!(chat.get_history()[3].is_model());
     }
 
     #[test]
@@ -1199,7 +2279,7 @@ mod tests {
                 _request: GenerateContentRequest<T>,
             ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 unimplemented!()
             }
@@ -1219,7 +2299,7 @@ mod tests {
                 _request: GenerateContentRequest<T>,
             ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 unimplemented!()
             }
@@ -1256,7 +2336,7 @@ mod tests {
                 _request: GenerateContentRequest<T>,
             ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 unimplemented!()
             }
@@ -1289,7 +2369,7 @@ mod tests {
                 request: GenerateContentRequest<T>,
             ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 // Verify request contains history
                 assert!(!request.contents().is_empty());
@@ -1297,21 +2377,11 @@ mod tests {
                 // Helper to create properly typed response
                 fn create_response<T>(text: &str) -> GenerateContentResponse<T>
                 where
-                    T: serde::de::DeserializeOwned + serde::Serialize + 'static,
+                    T: TextPayload,
                 {
                     // Create a response with String type first
                     let string_response = GenerateContentResponse::<String> {
-                        candidates: vec![crate::dto_response::Candidate {
-                            content: Content::Model {
-                                parts: vec![
-                                    Part::builder()
-                                        .text(JsonString::new(text.to_string()))
-                                        .build(),
-                                ],
-                            },
-                            finish_reason: None,
-                            safety_ratings: vec![],
-                        }],
+                        candidates: vec![candidate(text)],
                         prompt_feedback: None,
                         usage_metadata: None,
                     };
@@ -1372,24 +2442,14 @@ mod tests {
                 _request: GenerateContentRequest<T>,
             ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 fn create_response<T>(text: &str) -> GenerateContentResponse<T>
                 where
-                    T: serde::de::DeserializeOwned + serde::Serialize + 'static,
+                    T: TextPayload,
                 {
                     let string_response = GenerateContentResponse::<String> {
-                        candidates: vec![crate::dto_response::Candidate {
-                            content: Content::Model {
-                                parts: vec![
-                                    Part::builder()
-                                        .text(JsonString::new(text.to_string()))
-                                        .build(),
-                                ],
-                            },
-                            finish_reason: None,
-                            safety_ratings: vec![],
-                        }],
+                        candidates: vec![candidate(text)],
                         prompt_feedback: None,
                         usage_metadata: None,
                     };
@@ -1404,9 +2464,7 @@ mod tests {
             }
         }
 
-        let mut chat = GeminiStreamChat::new(MockStreamApi);
-
-        {
+        async fn send_and_drop(chat: &mut GeminiStreamChat<MockStreamApi>) {
             let stream = chat
                 .send_message_stream::<String>()
                 .text("Question")
@@ -1421,11 +2479,119 @@ mod tests {
             // Stream is dropped here without being fully consumed
         }
 
-        // History should still be updated with the partial response
+        // By default a partial turn is discarded entirely
+        let mut chat = GeminiStreamChat::new(MockStreamApi);
+        send_and_drop(&mut chat).await;
+        assert!(chat.get_history().is_empty());
+
+        // History should be updated with the partial response when keeping partial turns
+        let mut chat = GeminiStreamChat::builder(MockStreamApi)
+            .partial_turn_policy(PartialTurnPolicy::Keep)
+            .build();
+        send_and_drop(&mut chat).await;
         let history = chat.get_history();
         assert_eq!(history.len(), 2);
         assert!(history[0].is_user());
         assert!(history[1].is_model());
+        assert_eq!(history[1].parts().len(), 1);
+
+        let mut chat = GeminiStreamChat::builder(MockStreamApi)
+            .partial_turn_policy(PartialTurnPolicy::KeepMarked)
+            .build();
+        send_and_drop(&mut chat).await;
+        let parts = chat.get_history()[1].parts();
+        assert_eq!(parts[0].text().unwrap(), "Partial response");
+        assert_eq!(parts[1].text().unwrap(), INCOMPLETE_TURN_MARKER);
+    }
+
+    #[tokio::test]
+    async fn test_send_message_failure_leaves_history_untouched() {
+        struct MockApi;
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                if request.contents().len() > 1 {
+                    return Err("503 Service Unavailable".into());
+                }
+                let json = r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "Hi"}]}}]}"#;
+                Ok(serde_json::from_str(json)?)
+            }
+        }
+
+        let mut chat = GeminiChat::from_history(
+            MockApi,
+            vec![
+                Content::user(vec![
+                    Part::builder()
+                        .text(JsonString::new("Hello".to_string()))
+                        .build(),
+                ]),
+                Content::model(vec![
+                    Part::builder()
+                        .text(JsonString::new("Hi".to_string()))
+                        .build(),
+                ]),
+            ],
+        );
+
+        let result: Result<GenerateContentResponse<String>, _> =
+            chat.send_message().text("Still there?").send().await;
+        assert!(result.is_err());
+        assert_eq!(chat.get_history().len(), 2);
+        assert!(chat.get_history()[1].is_model());
+    }
+
+    #[tokio::test]
+    async fn test_send_message_stream_error_is_partial_turn() {
+        use futures::stream;
+
+        struct MockStreamApi;
+        #[async_trait::async_trait]
+        impl GeminiStreamingApi for MockStreamApi {
+            async fn stream_generate_content<T>(
+                &self,
+                _request: GenerateContentRequest<T>,
+            ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let json = r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "Once upon"}]}}]}"#;
+                let response = serde_json::from_str(json)?;
+                Ok(Box::pin(stream::iter(vec![
+                    Ok(response),
+                    Err("connection reset".into()),
+                ])))
+            }
+        }
+
+        for (policy, expected) in [
+            (PartialTurnPolicy::Discard, 0),
+            (PartialTurnPolicy::KeepMarked, 2),
+        ] {
+            let mut chat = GeminiStreamChat::builder(MockStreamApi)
+                .partial_turn_policy(policy)
+                .build();
+            let mut stream = chat
+                .send_message_stream::<String>()
+                .text("Tell me a story")
+                .send()
+                .await
+                .unwrap();
+            let mut errors = 0;
+            while let Some(chunk) = stream.next().await {
+                errors += usize::from(chunk.is_err());
+            }
+            drop(stream);
+
+            assert_eq!(errors, 1);
+            assert_eq!(chat.get_history().len(), expected);
+        }
     }
 
     #[tokio::test]
@@ -1440,27 +2606,17 @@ mod tests {
                 request: GenerateContentRequest<T>,
             ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 // Verify config was passed through
                 assert!(request.generation_config().is_some());
 
                 fn create_response<T>(text: &str) -> GenerateContentResponse<T>
                 where
-                    T: serde::de::DeserializeOwned + serde::Serialize + 'static,
+                    T: TextPayload,
                 {
                     let string_response = GenerateContentResponse::<String> {
-                        candidates: vec![crate::dto_response::Candidate {
-                            content: Content::Model {
-                                parts: vec![
-                                    Part::builder()
-                                        .text(JsonString::new(text.to_string()))
-                                        .build(),
-                                ],
-                            },
-                            finish_reason: None,
-                            safety_ratings: vec![],
-                        }],
+                        candidates: vec![candidate(text)],
                         prompt_feedback: None,
                         usage_metadata: None,
                     };
@@ -1514,7 +2670,7 @@ mod tests {
                 request: GenerateContentRequest<T>,
             ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
             where
-                T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+                T: TextPayload + Send + 'static,
             {
                 let mut count = self.call_count.lock().unwrap();
                 *count += 1;
@@ -1525,20 +2681,10 @@ mod tests {
 
                 fn create_response<T>(text: &str) -> GenerateContentResponse<T>
                 where
-                    T: serde::de::DeserializeOwned + serde::Serialize + 'static,
+                    T: TextPayload,
                 {
                     let string_response = GenerateContentResponse::<String> {
-                        candidates: vec![crate::dto_response::Candidate {
-                            content: Content::Model {
-                                parts: vec![
-                                    Part::builder()
-                                        .text(JsonString::new(text.to_string()))
-                                        .build(),
-                                ],
-                            },
-                            finish_reason: None,
-                            safety_ratings: vec![],
-                        }],
+                        candidates: vec![candidate(text)],
                         prompt_feedback: None,
                         usage_metadata: None,
                     };
@@ -1599,4 +2745,972 @@ mod tests {
             "Response 2"
         );
     }
+
+    #[tokio::test]
+    async fn test_send_message_blocked_prompt_not_added_to_history() {
+        struct MockApi;
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                _request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let json = r#"{"prompt_feedback": {"block_reason": "SAFETY"}}"#;
+                Ok(serde_json::from_str(json)?)
+            }
+        }
+
+        let mut chat = GeminiChat::new(MockApi);
+        let response: GenerateContentResponse<String> = chat
+            .send_message()
+            .text("Something unsafe")
+            .send()
+            .await
+            .expect("Passthrough responses should not error");
+
+        assert!(response.is_blocked());
+        assert!(chat.get_history().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_send_message_blocked_error_not_added_to_history() {
+        struct MockApi;
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                _request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                Err(Box::new(BlockedError::CandidateBlocked {
+                    reason: crate::dto_response::FinishReason::Safety,
+                    ratings: vec![],
+                    usage: None,
+                }))
+            }
+        }
+
+        let history = vec![Content::user(vec![
+            Part::builder()
+                .text(JsonString::new("Earlier".to_string()))
+                .build(),
+        ])];
+
+        let mut chat = GeminiChat::from_history(MockApi, history);
+        let result: Result<GenerateContentResponse<String>, _> =
+            chat.send_message().text("Blocked").send().await;
+
+        let error = result.unwrap_err();
+        assert!(error.downcast_ref::<BlockedError>().is_some());
+        assert_eq!(chat.get_history().len(), 1);
+        assert_eq!(chat.get_history()[0].first_text().unwrap(), "Earlier");
+    }
+
+    #[tokio::test]
+    async fn test_send_message_stream_blocked_not_added_to_history() {
+        use futures::stream;
+
+        struct MockStreamApi;
+        #[async_trait::async_trait]
+        impl GeminiStreamingApi for MockStreamApi {
+            async fn stream_generate_content<T>(
+                &self,
+                _request: GenerateContentRequest<T>,
+            ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let partial: GenerateContentResponse<T> = serde_json::from_str(
+                    r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "Once"}]}}]}"#,
+                )?;
+                let blocked: GenerateContentResponse<T> =
+                    serde_json::from_str(r#"{"candidates": [{"finish_reason": "SAFETY"}]}"#)?;
+
+                let stream = stream::iter(vec![Ok(partial), Ok(blocked)]);
+                Ok(Box::pin(stream))
+            }
+        }
+
+        let mut chat = GeminiStreamChat::new(MockStreamApi);
+
+        {
+            let stream = chat
+                .send_message_stream::<String>()
+                .text("Tell me something")
+                .send()
+                .await
+                .expect("Failed to send message");
+            let mut pinned = Box::pin(stream);
+            while (pinned.next().await).is_some() {}
+        }
+
+        assert!(chat.get_history().is_empty());
+    }
+
+    fn usage_response<T>(text: &str, usage: UsageMetadata) -> GenerateContentResponse<T>
+    where
+        T: TextPayload,
+    {
+        let string_response = GenerateContentResponse::<String> {
+            candidates: vec![candidate(text)],
+            prompt_feedback: None,
+            usage_metadata: Some(usage),
+        };
+        let json = serde_json::to_string(&string_response).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn usage(prompt: u32, candidates: u32) -> UsageMetadata {
+        UsageMetadata {
+            prompt_token_count: Some(prompt),
+            candidates_token_count: Some(candidates),
+            total_token_count: Some(prompt + candidates),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_message_sums_usage_across_turns() {
+        struct MockApi;
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let prompt = request.contents().len() as u32 * 10;
+                Ok(usage_response("ok", usage(prompt, 3)))
+            }
+        }
+
+        let mut chat = GeminiChat::new(MockApi);
+        let _: GenerateContentResponse<String> =
+            chat.send_message().text("One").send().await.unwrap();
+        let _: GenerateContentResponse<String> =
+            chat.send_message().text("Two").send().await.unwrap();
+
+        assert_eq!(chat.usage().prompt_token_count, Some(10 + 30));
+        assert_eq!(chat.usage().candidates_token_count, Some(6));
+        assert_eq!(chat.usage().total_token_count, Some(46));
+        assert_eq!(chat.turn_usage().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_chat_cost_prices_each_turn() {
+        use crate::pricing::TokenRates;
+
+        struct MockApi;
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let prompt = request.contents().len() as u32 * 100_000;
+                Ok(usage_response("ok", usage(prompt, 0)))
+            }
+        }
+
+        // Prompts above 200k tokens cost twice as much
+        let pricing = ModelPricing::new(TokenRates::new(1.0, 1.0))
+            .with_tier(200_000, TokenRates::new(2.0, 2.0));
+
+        let mut chat = GeminiChat::new(MockApi);
+        let _: GenerateContentResponse<String> =
+            chat.send_message().text("One").send().await.unwrap();
+        let _: GenerateContentResponse<String> =
+            chat.send_message().text("Two").send().await.unwrap();
+
+        // 100k tokens at $1 plus 300k tokens at $2
+        let cost = chat.cost(&pricing);
+        assert!((cost.total() - 0.7).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_send_message_stream_counts_last_usage_only() {
+        use futures::stream;
+
+        struct MockStreamApi;
+        #[async_trait::async_trait]
+        impl GeminiStreamingApi for MockStreamApi {
+            async fn stream_generate_content<T>(
+                &self,
+                _request: GenerateContentRequest<T>,
+            ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                // Streamed usage is cumulative, only the final chunk holds the totals
+                let chunks = vec![
+                    Ok(usage_response("Hello ", usage(10, 2))),
+                    Ok(usage_response("world!", usage(10, 5))),
+                ];
+                Ok(Box::pin(stream::iter(chunks)))
+            }
+        }
+
+        let mut chat = GeminiStreamChat::new(MockStreamApi);
+        for _ in 0..2 {
+            let mut stream = chat
+                .send_message_stream::<String>()
+                .text("Hi")
+                .send()
+                .await
+                .unwrap();
+            while stream.next().await.is_some() {}
+        }
+
+        assert_eq!(chat.usage().prompt_token_count, Some(20));
+        assert_eq!(chat.usage().candidates_token_count, Some(10));
+        assert_eq!(chat.usage().total_token_count, Some(30));
+    }
+
+    #[tokio::test]
+    async fn test_chat_defaults_apply_to_every_turn() {
+        use std::sync::{Arc, Mutex};
+
+        struct MockApi {
+            requests: Arc<Mutex<Vec<serde_json::Value>>>,
+        }
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .push(serde_json::to_value(&request)?);
+                let json = r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "Ok"}]}}]}"#;
+                Ok(serde_json::from_str(json)?)
+            }
+        }
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let mut chat = GeminiChat::builder(MockApi {
+            requests: requests.clone(),
+        })
+        .system_instruction("Be brief")
+        .generation_config(
+            GenerationConfig::builder()
+                .temperature(0.5)
+                .max_output_tokens(64)
+                .build()
+                .unwrap(),
+        )
+        .add_tool(Tool::google_search())
+        .cached_content("cachedContents/default")
+        .build();
+
+        let _: GenerateContentResponse<String> =
+            chat.send_message().text("First").send().await.unwrap();
+        let _: GenerateContentResponse<String> = chat
+            .send_message()
+            .text("Second")
+            .system_instruction("Be verbose")
+            .generation_config(
+                GenerationConfig::builder()
+                    .temperature(1.0)
+                    .build()
+                    .unwrap(),
+            )
+            .tools(vec![])
+            .cached_content("cachedContents/other")
+            .send()
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            requests[0]["system_instruction"]["parts"][0]["text"],
+            "Be brief"
+        );
+        assert_eq!(requests[0]["generation_config"]["temperature"], 0.5);
+        assert_eq!(requests[0]["generation_config"]["maxOutputTokens"], 64);
+        assert!(requests[0]["tools"][0]["google_search"].is_object());
+        assert_eq!(requests[0]["cached_content"], "cachedContents/default");
+
+        assert_eq!(
+            requests[1]["system_instruction"]["parts"][0]["text"],
+            "Be verbose"
+        );
+        assert_eq!(requests[1]["generation_config"]["temperature"], 1.0);
+        assert_eq!(requests[1]["generation_config"]["maxOutputTokens"], 64);
+        assert_eq!(requests[1]["tools"], serde_json::json!([]));
+        assert_eq!(requests[1]["cached_content"], "cachedContents/other");
+        assert_eq!(requests[1]["contents"].as_array().unwrap().len(), 3);
+
+        drop(requests);
+        assert_eq!(chat.get_history().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_stream_chat_builder_sends_cached_content() {
+        use futures::stream;
+
+        struct MockStreamApi;
+        #[async_trait::async_trait]
+        impl GeminiStreamingApi for MockStreamApi {
+            async fn stream_generate_content<T>(
+                &self,
+                request: GenerateContentRequest<T>,
+            ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                assert_eq!(request.cached_content(), Some("cachedContents/manual"));
+                assert!(request.system_instruction().is_none());
+                assert_eq!(request.contents().len(), 3);
+
+                let json = r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "Ok"}]}}]}"#;
+                let response = serde_json::from_str(json)?;
+                Ok(Box::pin(stream::iter(vec![Ok(response)])))
+            }
+        }
+
+        let history = vec![
+            Content::user(vec![
+                Part::builder()
+                    .text(JsonString::new("Hi".to_string()))
+                    .build(),
+            ]),
+            Content::model(vec![
+                Part::builder()
+                    .text(JsonString::new("Hello".to_string()))
+                    .build(),
+            ]),
+        ];
+        let mut chat = GeminiStreamChat::builder(MockStreamApi)
+            .history(history)
+            .cached_content("cachedContents/manual")
+            .build();
+
+        let mut stream = chat
+            .send_message_stream::<String>()
+            .text("Summarize the manual")
+            .send()
+            .await
+            .unwrap();
+        while stream.next().await.is_some() {}
+        drop(stream);
+
+        assert_eq!(chat.get_history().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_history_strategy_limits_sent_contents() {
+        use crate::history::SlidingWindow;
+
+        struct MockApi;
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                assert!(request.contents().len() <= 3);
+                assert!(request.contents()[0].is_user());
+                let json = r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "Ok"}]}}]}"#;
+                Ok(serde_json::from_str(json)?)
+            }
+        }
+
+        let mut chat = GeminiChat::builder(MockApi)
+            .history_strategy(SlidingWindow::new(2))
+            .build();
+        for i in 0..4 {
+            let _: GenerateContentResponse<String> = chat
+                .send_message()
+                .text(format!("Message {i}"))
+                .send()
+                .await
+                .unwrap();
+        }
+
+        // The full conversation is still kept
+        assert_eq!(chat.get_history().len(), 8);
+    }
+
+    #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct TripPlan {
+        city: String,
+        days: u32,
+    }
+
+    #[tokio::test]
+    async fn test_typed_turns_keep_raw_text_and_value() {
+        use crate::dto_content::Json;
+
+        #[derive(Clone)]
+        struct MockApi;
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                _request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let json = r#"{"candidates": [{"content": {"role": "model", "parts": [
+                    {"text": "{\"city\": \"Rome\",  \"days\": 3, \"budget\": 900}"}
+                ]}}]}"#;
+                Ok(serde_json::from_str(json)?)
+            }
+        }
+
+        let mut chat = GeminiChat::new(MockApi);
+        let _: GenerateContentResponse<Json<TripPlan>> = chat
+            .send_message()
+            .text("Plan a trip")
+            .send()
+            .await
+            .unwrap();
+
+        // The model's text is kept verbatim, including fields the type ignores
+        assert_eq!(
+            chat.get_history()[1].first_text().unwrap(),
+            r#"{"city": "Rome",  "days": 3, "budget": 900}"#
+        );
+        let plan = chat.typed_response::<Json<TripPlan>>(1).unwrap();
+        assert_eq!(plan.0.days, 3);
+        assert!(chat.typed_response::<String>(1).is_none());
+        assert!(chat.typed_response::<Json<TripPlan>>(0).is_none());
+
+        let fork = chat.fork();
+        assert_eq!(fork.typed_responses::<Json<TripPlan>>().count(), 1);
+        chat.clear_history();
+        assert_eq!(chat.typed_responses::<Json<TripPlan>>().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_chat_text_modes() {
+        use futures::stream;
+
+        /// Streams text and image chunks in the given text mode
+        struct MockStreamApi {
+            mode: StreamTextMode,
+            chunks: Vec<Vec<serde_json::Value>>,
+        }
+        #[async_trait::async_trait]
+        impl GeminiStreamingApi for MockStreamApi {
+            async fn stream_generate_content<T>(
+                &self,
+                _request: GenerateContentRequest<T>,
+            ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let chunks = self
+                    .chunks
+                    .iter()
+                    .map(|parts| {
+                        let json = serde_json::json!({"candidates": [{"content": {
+                            "role": "model", "parts": parts
+                        }}]});
+                        Ok(serde_json::from_value(json).unwrap())
+                    })
+                    .collect::<Vec<_>>();
+                Ok(Box::pin(stream::iter(chunks)))
+            }
+
+            fn stream_text_mode(&self) -> StreamTextMode {
+                self.mode
+            }
+        }
+
+        async fn history_texts(
+            mode: StreamTextMode,
+            chunks: Vec<Vec<serde_json::Value>>,
+        ) -> Vec<String> {
+            let mut chat = GeminiStreamChat::new(MockStreamApi { mode, chunks });
+            let stream = chat
+                .send_message_stream::<String>()
+                .text("Hi")
+                .send()
+                .await
+                .unwrap();
+            stream.count().await;
+            chat.get_history()[1]
+                .parts()
+                .iter()
+                .map(|part| {
+                    part.text()
+                        .cloned()
+                        .unwrap_or_else(|| "<image>".to_string())
+                })
+                .collect()
+        }
+
+        let text = |text: &str| serde_json::json!({"text": text});
+        let image = serde_json::json!({"inlineData": {"mimeType": "image/png", "data": "AAAA"}});
+
+        // Deltas are appended even when they repeat the text so far
+        assert_eq!(
+            history_texts(
+                StreamTextMode::Delta,
+                vec![vec![text("ab")], vec![text("ab")], vec![text("c")]]
+            )
+            .await,
+            vec!["ababc"]
+        );
+        assert_eq!(
+            history_texts(
+                StreamTextMode::Delta,
+                vec![vec![text("A")], vec![image.clone()], vec![text("B")]]
+            )
+            .await,
+            vec!["A", "<image>", "B"]
+        );
+
+        // Cumulative chunks contribute only the text beyond what was received
+        assert_eq!(
+            history_texts(
+                StreamTextMode::Cumulative,
+                vec![vec![text("ab")], vec![text("abab")], vec![text("ababc")]]
+            )
+            .await,
+            vec!["ababc"]
+        );
+        assert_eq!(
+            history_texts(
+                StreamTextMode::Cumulative,
+                vec![vec![text("A")], vec![image.clone()], vec![text("AB")]]
+            )
+            .await,
+            vec!["A", "<image>", "B"]
+        );
+
+        // Only the first answer text of a chunk is cumulative; later parts are deltas
+        assert_eq!(
+            history_texts(
+                StreamTextMode::Cumulative,
+                vec![
+                    vec![text("A"), image, text("Done")],
+                    vec![text("AB"), text("!")]
+                ]
+            )
+            .await,
+            vec!["A", "<image>", "DoneB!"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_typed_stream_chat_records_value() {
+        use crate::dto_content::Json;
+        use futures::stream;
+
+        struct MockStreamApi;
+        #[async_trait::async_trait]
+        impl GeminiStreamingApi for MockStreamApi {
+            async fn stream_generate_content<T>(
+                &self,
+                request: GenerateContentRequest<T>,
+            ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                // Like the client, each chunk carries all text received so far
+                let texts: &[&str] = match request.contents()[0].first_text().unwrap().as_str() {
+                    "plan" => &[r#"{"city": "Rome", "days": 3}"#],
+                    _ => &["Hel", "Hello"],
+                };
+                let chunks = texts
+                    .iter()
+                    .map(|text| {
+                        let json = serde_json::json!({"candidates": [{"content": {
+                            "role": "model", "parts": [{"text": text}]
+                        }}]});
+                        Ok(serde_json::from_value(json).unwrap())
+                    })
+                    .collect::<Vec<_>>();
+                Ok(Box::pin(stream::iter(chunks)))
+            }
+
+            fn stream_text_mode(&self) -> StreamTextMode {
+                StreamTextMode::Cumulative
+            }
+        }
+
+        let mut chat = GeminiStreamChat::new(MockStreamApi);
+        let stream = chat
+            .send_message_stream::<Json<TripPlan>>()
+            .text("plan")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(stream.count().await, 1);
+        assert_eq!(
+            chat.typed_response::<Json<TripPlan>>(1),
+            Some(&Json(TripPlan {
+                city: "Rome".to_string(),
+                days: 3
+            }))
+        );
+
+        chat.clear_history();
+        let stream = chat
+            .send_message_stream::<String>()
+            .text("greet")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(stream.count().await, 2);
+        assert_eq!(chat.get_history()[1].first_text().unwrap(), "Hello");
+    }
+
+    #[tokio::test]
+    async fn test_session_restores_options_and_usage() {
+        struct MockApi;
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let request = serde_json::to_value(&request)?;
+                assert_eq!(
+                    request["system_instruction"]["parts"][0]["text"],
+                    "Be brief"
+                );
+                let json = r#"{
+                    "candidates": [{"content": {"role": "model", "parts": [{"text": "Ok"}]}}],
+                    "usageMetadata": {"promptTokenCount": 10, "totalTokenCount": 12}
+                }"#;
+                Ok(serde_json::from_str(json)?)
+            }
+        }
+
+        let mut chat = GeminiChat::builder(MockApi)
+            .system_instruction("Be brief")
+            .build();
+        let _: GenerateContentResponse<String> =
+            chat.send_message().text("Hello").send().await.unwrap();
+
+        let json = chat.to_session("s").to_json().unwrap();
+        let session = ChatSession::from_json("s", &json).unwrap();
+        let created_at = session.created_at;
+
+        let mut restored = GeminiChat::from_session(MockApi, session);
+        assert_eq!(restored.created_at, created_at);
+        assert_eq!(restored.get_history().len(), 2);
+        assert_eq!(restored.usage().total_token_count, Some(12));
+        let _: GenerateContentResponse<String> =
+            restored.send_message().text("Again").send().await.unwrap();
+        assert_eq!(restored.usage().total_token_count, Some(24));
+    }
+
+    #[tokio::test]
+    async fn test_regenerate_edit_and_fork_create_branches() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Clone)]
+        struct MockApi {
+            calls: Arc<AtomicUsize>,
+        }
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let call = self.calls.fetch_add(1, Ordering::SeqCst);
+                let prompt = request.contents().last().unwrap().first_text().unwrap();
+                let json = serde_json::json!({
+                    "candidates": [{"content": {"role": "model", "parts": [
+                        {"text": format!("{prompt} {call}")}
+                    ]}}]
+                });
+                Ok(serde_json::from_value(json)?)
+            }
+        }
+
+        fn texts<A: GeminiApi>(chat: &GeminiChat<A>) -> Vec<String> {
+            chat.get_history()
+                .iter()
+                .map(|content| content.first_text().unwrap().clone())
+                .collect()
+        }
+
+        let mut chat = GeminiChat::new(MockApi {
+            calls: Arc::new(AtomicUsize::new(0)),
+        });
+        assert_eq!(
+            chat.regenerate_last::<String>().err(),
+            Some(BranchError::NothingToRegenerate)
+        );
+        for text in ["a", "b"] {
+            let _: GenerateContentResponse<String> =
+                chat.send_message().text(text).send().await.unwrap();
+        }
+
+        let _: GenerateContentResponse<String> =
+            chat.regenerate_last().unwrap().send().await.unwrap();
+        assert_eq!(texts(&chat), vec!["a", "a 0", "b", "b 2"]);
+
+        let parts = vec![
+            Part::builder()
+                .text(JsonString::new("B".to_string()))
+                .build(),
+        ];
+        let _: GenerateContentResponse<String> =
+            chat.edit_turn(2, parts).unwrap().send().await.unwrap();
+        assert_eq!(texts(&chat), vec!["a", "a 0", "B", "B 3"]);
+        assert_eq!(
+            chat.edit_turn::<String>(1, Vec::new()).err(),
+            Some(BranchError::NotAUserTurn(1))
+        );
+        assert_eq!(
+            chat.edit_turn::<String>(9, Vec::new()).err(),
+            Some(BranchError::NoSuchTurn(9))
+        );
+
+        // All three answers to the second message are kept
+        assert_eq!(chat.tree().leaves().len(), 3);
+        chat.checkout(3).unwrap();
+        assert_eq!(texts(&chat), vec!["a", "a 0", "b", "b 1"]);
+
+        let mut fork = chat.fork();
+        let _: GenerateContentResponse<String> =
+            fork.send_message().text("c").send().await.unwrap();
+        assert_eq!(fork.get_history().len(), 6);
+        assert_eq!(chat.get_history().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_send_message_keeps_thought_signature_in_history() {
+        struct MockApi;
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                _request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let json = r#"{
+                    "candidates": [{
+                        "content": {
+                            "role": "model",
+                            "parts": [
+                                {"text": "Thinking it over", "thought": true},
+                                {"text": "Done", "thoughtSignature": "c2ln"}
+                            ]
+                        }
+                    }]
+                }"#;
+                Ok(serde_json::from_str(json)?)
+            }
+        }
+
+        let mut chat = GeminiChat::new(MockApi);
+        let response: GenerateContentResponse<String> =
+            chat.send_message().text("Go").send().await.unwrap();
+
+        assert_eq!(response.first_text().unwrap(), "Done");
+
+        let model_parts = chat.get_history()[1].parts();
+        assert_eq!(model_parts.len(), 1);
+        assert!(!model_parts[0].is_thought());
+        assert_eq!(model_parts[0].thought_signature(), Some("c2ln"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_chat_keeps_function_calls_and_signatures_in_history() {
+        use futures::stream;
+
+        /// Streams one chunk per given part list
+        struct MockStreamApi {
+            chunks: Vec<serde_json::Value>,
+        }
+        #[async_trait::async_trait]
+        impl GeminiStreamingApi for MockStreamApi {
+            async fn stream_generate_content<T>(
+                &self,
+                _request: GenerateContentRequest<T>,
+            ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let chunks = self
+                    .chunks
+                    .iter()
+                    .map(|parts| {
+                        let json = serde_json::json!({"candidates": [{"content": {
+                            "role": "model", "parts": parts
+                        }}]});
+                        Ok(serde_json::from_value(json).unwrap())
+                    })
+                    .collect::<Vec<_>>();
+                Ok(Box::pin(stream::iter(chunks)))
+            }
+        }
+
+        async fn history_parts(chunks: Vec<serde_json::Value>) -> Vec<Part<String>> {
+            let mut chat = GeminiStreamChat::new(MockStreamApi { chunks });
+            let stream = chat
+                .send_message_stream::<String>()
+                .text("Go")
+                .send()
+                .await
+                .unwrap();
+            stream.count().await;
+            chat.get_history()[1].parts().to_vec()
+        }
+
+        let parts = history_parts(vec![
+            serde_json::json!([{"text": "Weather first", "thought": true}]),
+            serde_json::json!([{"text": "Checking. "}]),
+            serde_json::json!([
+                {"functionCall": {"name": "get_weather", "args": {"city": "Paris"}},
+                 "thoughtSignature": "c2lnMQ=="},
+                {"functionCall": {"name": "get_time"}}
+            ]),
+        ])
+        .await;
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].text().unwrap(), "Checking. ");
+        assert_eq!(parts[0].thought_signature(), None);
+        assert_eq!(parts[1].function_call().unwrap().name, "get_weather");
+        assert_eq!(parts[1].thought_signature(), Some("c2lnMQ=="));
+        assert_eq!(parts[2].function_call().unwrap().name, "get_time");
+        assert_eq!(parts[2].thought_signature(), None);
+
+        // A signature stays with the text it arrived on, and a second one starts a new part
+        let parts = history_parts(vec![
+            serde_json::json!([{"text": "A", "thoughtSignature": "c2lnMQ=="}]),
+            serde_json::json!([{"text": "B"}]),
+            serde_json::json!([{"text": "C", "thoughtSignature": "c2lnMg=="}]),
+            serde_json::json!([{"text": "", "thoughtSignature": "c2lnMw=="}]),
+        ])
+        .await;
+        let parts = parts
+            .iter()
+            .map(|part| (part.text().unwrap().as_str(), part.thought_signature()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parts,
+            vec![
+                ("AB", Some("c2lnMQ==")),
+                ("C", Some("c2lnMg==")),
+                ("", Some("c2lnMw=="))
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_message_keeps_interleaved_images_in_history() {
+        use crate::dto_request::MimeType;
+
+        struct MockApi;
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                _request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn std::error::Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let json = r#"{
+                    "candidates": [{
+                        "content": {
+                            "role": "model",
+                            "parts": [
+                                {"text": "Step one"},
+                                {"inlineData": {"mimeType": "image/png", "data": "aW1nMQ=="}},
+                                {"text": "Step two"}
+                            ]
+                        }
+                    }]
+                }"#;
+                Ok(serde_json::from_str(json)?)
+            }
+        }
+
+        let mut chat = GeminiChat::new(MockApi);
+        let _: GenerateContentResponse<String> =
+            chat.send_message().text("Draw it").send().await.unwrap();
+
+        let model_parts = chat.get_history()[1].parts();
+        assert_eq!(model_parts.len(), 3);
+        assert_eq!(model_parts[0].text().unwrap(), "Step one");
+        let blob = model_parts[1].inline_data().unwrap();
+        assert_eq!(blob.mime_type, MimeType::ImagePng);
+        assert_eq!(blob.data.to_base64(), "aW1nMQ==");
+        assert_eq!(model_parts[2].text().unwrap(), "Step two");
+    }
+
+    #[tokio::test]
+    async fn test_send_message_stream_keeps_interleaved_images_in_history() {
+        use futures::stream;
+
+        struct MockStreamApi;
+        #[async_trait::async_trait]
+        impl GeminiStreamingApi for MockStreamApi {
+            async fn stream_generate_content<T>(
+                &self,
+                _request: GenerateContentRequest<T>,
+            ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                let chunk = |part: &str| {
+                    let json = format!(
+                        r#"{{"candidates": [{{"content": {{"role": "model", "parts": [{part}]}}}}]}}"#
+                    );
+                    Ok(serde_json::from_str(&json).unwrap())
+                };
+                let chunks = vec![
+                    chunk(r#"{"text": "Here "}"#),
+                    chunk(r#"{"text": "you go"}"#),
+                    chunk(r#"{"inlineData": {"mimeType": "image/png", "data": "aW1n"}}"#),
+                    chunk(r#"{"text": "Enjoy"}"#),
+                ];
+                Ok(Box::pin(stream::iter(chunks)))
+            }
+        }
+
+        let mut chat = GeminiStreamChat::new(MockStreamApi);
+        let mut stream = chat
+            .send_message_stream::<String>()
+            .text("Draw it")
+            .send()
+            .await
+            .unwrap();
+        while stream.next().await.is_some() {}
+        drop(stream);
+
+        let model_parts = chat.get_history()[1].parts();
+        assert_eq!(model_parts.len(), 3);
+        assert_eq!(model_parts[0].text().unwrap(), "Here you go");
+        assert!(model_parts[1].inline_data().is_some());
+        assert_eq!(model_parts[2].text().unwrap(), "Enjoy");
+    }
 }
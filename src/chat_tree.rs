@@ -0,0 +1,291 @@
+//! Tree-structured chat history with branches
+//!
+//! Regenerating an answer or editing an earlier message starts a new branch
+//! instead of overwriting history. A [`ChatTree`] keeps every branch, and the
+//! active branch is the conversation sent with the next turn. The tree
+//! serializes with all branches and the active one, so alternatives survive
+//! persistence.
+
+use serde::{Deserialize, Serialize};
+
+use crate::dto_content::Content;
+
+/// A message in a [`ChatTree`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatNode {
+    content: Content,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<usize>,
+}
+
+impl ChatNode {
+    /// Returns the message
+    pub fn content(&self) -> &Content {
+        &self.content
+    }
+
+    /// Returns the id of the preceding message, or `None` for the first message of a branch
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+/// Error returned by operations on the branches of a chat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BranchError {
+    /// No node has the given id
+    NoSuchNode(usize),
+    /// The active branch has no message at the given index
+    NoSuchTurn(usize),
+    /// The message at the given index is not a user message starting a turn
+    NotAUserTurn(usize),
+    /// The active branch contains no user message to regenerate an answer for
+    NothingToRegenerate,
+}
+
+impl std::fmt::Display for BranchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BranchError::NoSuchNode(id) => write!(f, "Chat tree has no node {id}"),
+            BranchError::NoSuchTurn(index) => write!(f, "Chat history has no message {index}"),
+            BranchError::NotAUserTurn(index) => {
+                write!(f, "Message {index} is not a user message starting a turn")
+            }
+            BranchError::NothingToRegenerate => {
+                write!(
+                    f,
+                    "Chat history has no user message to regenerate an answer for"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BranchError {}
+
+/// Chat history keeping every branch of the conversation
+///
+/// Nodes are identified by their position in [`ChatTree::nodes`] and never removed,
+/// except by [`ChatTree::clear`]. The active branch runs from a root to the active node.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(try_from = "ChatTreeRepr", into = "ChatTreeRepr")]
+pub struct ChatTree {
+    nodes: Vec<ChatNode>,
+    active: Option<usize>,
+    /// Node ids of the active branch, first message first
+    path: Vec<usize>,
+    /// Contents of the active branch
+    history: Vec<Content>,
+}
+
+/// Serialized form of a `ChatTree`, without the cached active branch
+#[derive(Serialize, Deserialize)]
+struct ChatTreeRepr {
+    nodes: Vec<ChatNode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active: Option<usize>,
+}
+
+impl TryFrom<ChatTreeRepr> for ChatTree {
+    type Error = String;
+
+    fn try_from(repr: ChatTreeRepr) -> Result<Self, String> {
+        for (id, node) in repr.nodes.iter().enumerate() {
+            if node.parent.is_some_and(|parent| parent >= id) {
+                return Err(format!("Chat tree node {id} must follow its parent"));
+            }
+        }
+        let mut tree = ChatTree {
+            nodes: repr.nodes,
+            ..ChatTree::default()
+        };
+        if let Some(active) = repr.active {
+            tree.checkout(active).map_err(|e| e.to_string())?;
+        }
+        Ok(tree)
+    }
+}
+
+impl From<ChatTree> for ChatTreeRepr {
+    fn from(tree: ChatTree) -> Self {
+        ChatTreeRepr {
+            nodes: tree.nodes,
+            active: tree.active,
+        }
+    }
+}
+
+impl ChatTree {
+    /// Creates an empty tree
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a tree with a single branch holding `history`
+    pub fn from_history(history: Vec<Content>) -> Self {
+        let mut tree = Self::new();
+        for content in history {
+            tree.push(content);
+        }
+        tree
+    }
+
+    /// Returns the messages of the active branch
+    pub fn history(&self) -> &[Content] {
+        &self.history
+    }
+
+    /// Returns the node ids of the active branch, first message first
+    pub fn active_path(&self) -> &[usize] {
+        &self.path
+    }
+
+    /// Returns the id of the last message of the active branch
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    /// Returns every node of every branch
+    pub fn nodes(&self) -> &[ChatNode] {
+        &self.nodes
+    }
+
+    /// Returns the node with the given id
+    pub fn node(&self, id: usize) -> Option<&ChatNode> {
+        self.nodes.get(id)
+    }
+
+    /// Returns the ids of the messages following `parent`, or of the first messages for `None`
+    ///
+    /// A node with several children is where branches diverge.
+    pub fn children(&self, parent: Option<usize>) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&id| self.nodes[id].parent == parent)
+            .collect()
+    }
+
+    /// Returns the last node of every branch
+    pub fn leaves(&self) -> Vec<usize> {
+        let mut has_children = vec![false; self.nodes.len()];
+        for node in &self.nodes {
+            if let Some(parent) = node.parent {
+                has_children[parent] = true;
+            }
+        }
+        (0..self.nodes.len())
+            .filter(|&id| !has_children[id])
+            .collect()
+    }
+
+    /// Makes the branch ending at `id` the active one
+    ///
+    /// # Errors
+    /// Returns `BranchError::NoSuchNode` if the tree has no node `id`.
+    pub fn checkout(&mut self, id: usize) -> Result<(), BranchError> {
+        if id >= self.nodes.len() {
+            return Err(BranchError::NoSuchNode(id));
+        }
+
+        let mut path = vec![id];
+        let mut node = id;
+        while let Some(parent) = self.nodes[node].parent {
+            path.push(parent);
+            node = parent;
+        }
+        path.reverse();
+
+        self.history = path
+            .iter()
+            .map(|&node| self.nodes[node].content.clone())
+            .collect();
+        self.path = path;
+        self.active = Some(id);
+        Ok(())
+    }
+
+    /// Removes every branch
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Appends a message to the active branch
+    pub(crate) fn push(&mut self, content: Content) {
+        let id = self.nodes.len();
+        self.nodes.push(ChatNode {
+            content: content.clone(),
+            parent: self.active,
+        });
+        self.path.push(id);
+        self.history.push(content);
+        self.active = Some(id);
+    }
+
+    /// Moves the end of the active branch back to its first `len` messages
+    ///
+    /// The following messages stay in the tree, so pushing afterwards starts a new branch.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.path.truncate(len);
+        self.history.truncate(len);
+        self.active = self.path.last().copied();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto_content::{JsonString, Part};
+
+    fn message(text: &str) -> Content {
+        Content::user(vec![
+            Part::builder()
+                .text(JsonString::new(text.to_string()))
+                .build(),
+        ])
+    }
+
+    fn texts(tree: &ChatTree) -> Vec<&str> {
+        tree.history()
+            .iter()
+            .filter_map(|content| content.first_text().map(String::as_str))
+            .collect()
+    }
+
+    #[test]
+    fn test_branches_keep_earlier_messages() {
+        let mut tree = ChatTree::from_history(vec![message("a"), message("b"), message("c")]);
+        tree.truncate(1);
+        tree.push(message("B"));
+
+        assert_eq!(texts(&tree), vec!["a", "B"]);
+        assert_eq!(tree.nodes().len(), 4);
+        assert_eq!(tree.children(Some(0)), vec![1, 3]);
+        assert_eq!(tree.leaves(), vec![2, 3]);
+        assert_eq!(tree.active_path(), &[0, 3]);
+
+        tree.checkout(2).unwrap();
+        assert_eq!(texts(&tree), vec!["a", "b", "c"]);
+        assert_eq!(tree.checkout(9), Err(BranchError::NoSuchNode(9)));
+    }
+
+    #[test]
+    fn test_tree_serialization_roundtrip() {
+        let mut tree = ChatTree::from_history(vec![message("a"), message("b")]);
+        tree.truncate(0);
+        tree.push(message("x"));
+        tree.checkout(1).unwrap();
+
+        let json = serde_json::to_value(&tree).unwrap();
+        assert_eq!(json["active"], 1);
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 3);
+        assert!(json.get("history").is_none());
+
+        let restored: ChatTree = serde_json::from_value(json).unwrap();
+        assert_eq!(texts(&restored), vec!["a", "b"]);
+        assert_eq!(restored.children(None), vec![0, 2]);
+
+        let invalid = serde_json::json!({
+            "nodes": [{"content": {"role": "user", "parts": []}, "parent": 0}]
+        });
+        assert!(serde_json::from_value::<ChatTree>(invalid).is_err());
+    }
+}
@@ -7,12 +7,18 @@ use async_trait::async_trait;
 use futures::StreamExt;
 
 use crate::api::{
-    BoxResponseStream, GeminiApi, GeminiStreamingApi, GenerateContentRequest,
-    GenerateContentResponse,
+    BoxResponseStream, Content, File, GeminiApi, GeminiFilesApi, GeminiOperationsApi,
+    GeminiStreamingApi, GeminiTokensApi, GenerateContentRequest, GenerateContentResponse, MimeType,
+    Operation, StreamTextMode,
 };
-use crate::config::GeminiConfig;
+use crate::config::{BlockPolicy, GeminiConfig};
+use crate::dto_content::TextPayload;
+use crate::dto_response::BlockedError;
 
 /// Gemini V1 Beta API client implementation
+///
+/// Cloning is cheap and shares the underlying connection pool.
+#[derive(Clone)]
 pub struct GeminiV1Beta {
     config: GeminiConfig,
     client: reqwest::Client,
@@ -57,6 +63,50 @@ impl GeminiV1Beta {
         )
     }
 
+    /// Builds the URL for the countTokens endpoint.
+    fn build_count_tokens_url(&self) -> String {
+        format!(
+            "{}/models/{}:countTokens",
+            self.config.base_url(),
+            self.config.model()
+        )
+    }
+
+    /// Builds the URL for a long-running operation resource.
+    fn build_operation_url(&self, name: &str) -> String {
+        format!("{}/{}", self.config.base_url(), name)
+    }
+
+    /// Builds the URL for cancelling a long-running operation.
+    fn build_cancel_operation_url(&self, name: &str) -> String {
+        format!("{}/{}:cancel", self.config.base_url(), name)
+    }
+
+    /// Builds the URL for starting a resumable file upload.
+    ///
+    /// Uploads go to the same API version under an `/upload` path prefix.
+    fn build_upload_url(&self) -> String {
+        let base_url = self.config.base_url();
+        let path_start = base_url
+            .find("://")
+            .and_then(|scheme_end| {
+                base_url[scheme_end + 3..]
+                    .find('/')
+                    .map(|i| scheme_end + 3 + i)
+            })
+            .unwrap_or(base_url.len());
+        format!(
+            "{}/upload{}/files",
+            &base_url[..path_start],
+            &base_url[path_start..]
+        )
+    }
+
+    /// Builds the URL for an uploaded file resource.
+    fn build_file_url(&self, name: &str) -> String {
+        format!("{}/{}", self.config.base_url(), name)
+    }
+
     /// Processes incoming bytes and appends them to the buffer.
     ///
     /// # Arguments
@@ -177,7 +227,7 @@ impl GeminiV1Beta {
         text_accumulator: &mut String,
     ) -> Option<Result<GenerateContentResponse<T>, Box<dyn Error + Send + Sync>>>
     where
-        T: serde::de::DeserializeOwned + 'static,
+        T: TextPayload,
     {
         Self::process_bytes_chunk(bytes, sse_buffer);
         let json_data = Self::extract_sse_message(sse_buffer)?;
@@ -191,27 +241,14 @@ impl GeminiV1Beta {
             }
         };
 
-        // Extract the raw text string from the response (before JsonString deserializes it)
-        if let Some(text_str) = raw_json["candidates"][0]["content"]["parts"][0]["text"].as_str() {
-            // Accumulate the raw text string
-            text_accumulator.push_str(text_str);
-
+        // Replace the answer text with all answer text so far (before JsonString deserializes it)
+        let mut modified_json = raw_json;
+        if Self::accumulate_answer_text(&mut modified_json, text_accumulator) {
             tracing::debug!(
-                "📝 Accumulated {} chars (total: {} chars): {}",
-                text_str.len(),
-                text_accumulator.len(),
-                if text_accumulator.len() > 200 {
-                    format!("{}...", &text_accumulator[..200])
-                } else {
-                    text_accumulator.clone()
-                }
+                "📝 Accumulated answer text (total: {} chars)",
+                text_accumulator.len()
             );
 
-            // Modify the JSON to replace the text field with the accumulated text
-            let mut modified_json = raw_json;
-            modified_json["candidates"][0]["content"]["parts"][0]["text"] =
-                serde_json::Value::String(text_accumulator.clone());
-
             // Now parse the modified JSON into the response type
             let json_str =
                 serde_json::to_string(&modified_json).unwrap_or_else(|_| json_data.clone());
@@ -239,12 +276,55 @@ impl GeminiV1Beta {
         Some(result)
     }
 
+    /// Replaces the first answer text of a chunk with all answer text received so far
+    ///
+    /// Only the first text part of the first candidate that is not a thought is
+    /// accumulated, so thought summaries never leak into the answer; other parts are
+    /// left as deltas. Returns false if the chunk has no answer text.
+    fn accumulate_answer_text(
+        raw_json: &mut serde_json::Value,
+        text_accumulator: &mut String,
+    ) -> bool {
+        let Some(parts) = raw_json
+            .pointer_mut("/candidates/0/content/parts")
+            .and_then(serde_json::Value::as_array_mut)
+        else {
+            return false;
+        };
+        let Some(text) = parts
+            .iter_mut()
+            .filter(|part| part["thought"] != serde_json::Value::Bool(true))
+            .find_map(|part| part.get_mut("text"))
+        else {
+            return false;
+        };
+        let Some(delta) = text.as_str() else {
+            return false;
+        };
+        text_accumulator.push_str(delta);
+        *text = serde_json::Value::String(text_accumulator.clone());
+        true
+    }
+
+    /// Applies the configured block policy to a parsed response.
+    ///
+    /// With `BlockPolicy::Error`, blocked prompts and candidates become a `BlockedError`.
+    fn apply_block_policy<T>(
+        policy: BlockPolicy,
+        response: GenerateContentResponse<T>,
+    ) -> Result<GenerateContentResponse<T>, BlockedError> {
+        match policy {
+            BlockPolicy::Passthrough => Ok(response),
+            BlockPolicy::Error => response.check_blocked(),
+        }
+    }
+
     /// Converts stream error to boxed error result.
     fn handle_stream_error<T>(
         e: reqwest::Error,
     ) -> Result<GenerateContentResponse<T>, Box<dyn Error + Send + Sync>>
     where
-        T: serde::de::DeserializeOwned + 'static,
+        T: TextPayload,
     {
         Err(Box::new(e) as Box<dyn Error + Send + Sync>)
     }
@@ -257,7 +337,7 @@ impl GeminiApi for GeminiV1Beta {
         request: GenerateContentRequest<T>,
     ) -> Result<GenerateContentResponse<T>, Box<dyn Error>>
     where
-        T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+        T: TextPayload + Send + 'static,
     {
         let url = self.build_generate_url();
 
@@ -280,7 +360,10 @@ impl GeminiApi for GeminiV1Beta {
             .await
             .map_err(|e| Box::new(e) as Box<dyn Error>)?;
 
-        Ok(result)
+        Ok(Self::apply_block_policy(
+            self.config.block_policy(),
+            result,
+        )?)
     }
 }
 
@@ -291,7 +374,7 @@ impl GeminiStreamingApi for GeminiV1Beta {
         request: GenerateContentRequest<T>,
     ) -> Result<BoxResponseStream<T>, Box<dyn Error>>
     where
-        T: serde::de::DeserializeOwned + serde::Serialize + Send + 'static,
+        T: TextPayload + Send + 'static,
     {
         let url = self.build_stream_url();
 
@@ -342,16 +425,9 @@ impl GeminiStreamingApi for GeminiV1Beta {
 
                         // Parse the JSON to extract raw text and accumulate
                         match serde_json::from_str::<serde_json::Value>(json_data) {
-                            Ok(raw_json) => {
-                                // Extract and accumulate the raw text string
-                                if let Some(text_str) = raw_json["candidates"][0]["content"]["parts"][0]["text"].as_str() {
-                                    text_accumulator.push_str(text_str);
-
-                                    // Modify the JSON to use accumulated text
-                                    let mut modified_json = raw_json;
-                                    modified_json["candidates"][0]["content"]["parts"][0]["text"] =
-                                        serde_json::Value::String(text_accumulator.clone());
-
+                            Ok(mut modified_json) => {
+                                // Use the accumulated answer text
+                                if Self::accumulate_answer_text(&mut modified_json, &mut text_accumulator) {
                                     if let Ok(json_str) = serde_json::to_string(&modified_json) {
                                         yield Self::parse_incomplete::<T>(json_str)
                                             .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>);
@@ -379,8 +455,195 @@ impl GeminiStreamingApi for GeminiV1Beta {
             }
         };
 
+        // Surface blocked chunks according to the configured policy
+        let block_policy = self.config.block_policy();
+        let stream = stream.map(move |result| {
+            result.and_then(|response| {
+                Self::apply_block_policy(block_policy, response)
+                    .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)
+            })
+        });
+
         Ok(Box::pin(stream))
     }
+
+    /// Chunks carry all text received so far, see `handle_stream_bytes`
+    fn stream_text_mode(&self) -> StreamTextMode {
+        StreamTextMode::Cumulative
+    }
+}
+
+#[async_trait]
+impl GeminiOperationsApi for GeminiV1Beta {
+    async fn get_operation<T>(&self, name: &str) -> Result<Operation<T>, Box<dyn Error>>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let url = self.build_operation_url(name);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("x-goog-api-key", self.config.api_key())
+            .send()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?
+            .error_for_status()
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+        let operation: Operation<T> = response
+            .json()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+        Ok(operation)
+    }
+
+    async fn cancel_operation(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        let url = self.build_cancel_operation_url(name);
+
+        self.client
+            .post(&url)
+            .header("x-goog-api-key", self.config.api_key())
+            .header("Content-Type", "application/json")
+            .body("{}")
+            .send()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?
+            .error_for_status()
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+        Ok(())
+    }
+}
+
+/// Response body of countTokens
+#[derive(serde::Deserialize)]
+struct CountTokensResponse {
+    #[serde(alias = "totalTokens")]
+    total_tokens: i32,
+}
+
+#[async_trait]
+impl GeminiTokensApi for GeminiV1Beta {
+    async fn count_tokens(&self, contents: &[Content]) -> Result<i32, Box<dyn Error>> {
+        let url = self.build_count_tokens_url();
+
+        let response = self
+            .client
+            .post(&url)
+            .header("x-goog-api-key", self.config.api_key())
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "contents": contents }))
+            .send()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?
+            .error_for_status()
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+        let count: CountTokensResponse = response
+            .json()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+        Ok(count.total_tokens)
+    }
+}
+
+/// Response body of a finalized upload
+#[derive(serde::Deserialize)]
+struct UploadFileResponse {
+    file: File,
+}
+
+#[async_trait]
+impl GeminiFilesApi for GeminiV1Beta {
+    async fn upload_file(
+        &self,
+        data: bytes::Bytes,
+        mime_type: MimeType,
+        display_name: Option<String>,
+    ) -> Result<File, Box<dyn Error>> {
+        let url = self.build_upload_url();
+        let metadata = serde_json::json!({ "file": { "display_name": display_name } });
+
+        let start = self
+            .client
+            .post(&url)
+            .header("x-goog-api-key", self.config.api_key())
+            .header("X-Goog-Upload-Protocol", "resumable")
+            .header("X-Goog-Upload-Command", "start")
+            .header("X-Goog-Upload-Header-Content-Length", data.len())
+            .header("X-Goog-Upload-Header-Content-Type", mime_type.as_str())
+            .json(&metadata)
+            .send()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?
+            .error_for_status()
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+        let upload_url = start
+            .headers()
+            .get("x-goog-upload-url")
+            .and_then(|value| value.to_str().ok())
+            .ok_or("Upload response did not include an upload URL")?
+            .to_string();
+
+        let response = self
+            .client
+            .post(&upload_url)
+            .header("X-Goog-Upload-Offset", 0)
+            .header("X-Goog-Upload-Command", "upload, finalize")
+            .body(data)
+            .send()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?
+            .error_for_status()
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+        let uploaded: UploadFileResponse = response
+            .json()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+        Ok(uploaded.file)
+    }
+
+    async fn get_file(&self, name: &str) -> Result<File, Box<dyn Error>> {
+        let url = self.build_file_url(name);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("x-goog-api-key", self.config.api_key())
+            .send()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?
+            .error_for_status()
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+        let file: File = response
+            .json()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+        Ok(file)
+    }
+
+    async fn delete_file(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        let url = self.build_file_url(name);
+
+        self.client
+            .delete(&url)
+            .header("x-goog-api-key", self.config.api_key())
+            .send()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?
+            .error_for_status()
+            .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -436,6 +699,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_count_tokens_url() {
+        let config = GeminiConfig::new("test-key".to_string(), "gemini-2.5-flash".to_string());
+        let client = GeminiV1Beta::new(config);
+
+        let url = client.build_count_tokens_url();
+        assert_eq!(
+            url,
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-flash:countTokens"
+        );
+    }
+
+    #[test]
+    fn test_build_operation_urls() {
+        let config = GeminiConfig::new("test-key".to_string(), "gemini-2.5-flash".to_string());
+        let client = GeminiV1Beta::new(config);
+
+        assert_eq!(
+            client.build_operation_url("batches/abc123"),
+            "https://generativelanguage.googleapis.com/v1beta/batches/abc123"
+        );
+        assert_eq!(
+            client.build_cancel_operation_url("batches/abc123"),
+            "https://generativelanguage.googleapis.com/v1beta/batches/abc123:cancel"
+        );
+    }
+
+    #[test]
+    fn test_build_file_urls() {
+        let config = GeminiConfig::new("test-key".to_string(), "gemini-2.5-flash".to_string());
+        let client = GeminiV1Beta::new(config);
+
+        assert_eq!(
+            client.build_upload_url(),
+            "https://generativelanguage.googleapis.com/upload/v1beta/files"
+        );
+        assert_eq!(
+            client.build_file_url("files/abc-123"),
+            "https://generativelanguage.googleapis.com/v1beta/files/abc-123"
+        );
+    }
+
+    #[test]
+    fn test_apply_block_policy() {
+        let json = r#"{"prompt_feedback": {"block_reason": "OTHER"}}"#;
+
+        let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
+        let response = GeminiV1Beta::apply_block_policy(BlockPolicy::Passthrough, response)
+            .expect("Passthrough should not reject blocked responses");
+        assert!(response.is_blocked());
+
+        let error = GeminiV1Beta::apply_block_policy(BlockPolicy::Error, response).unwrap_err();
+        assert!(matches!(error, BlockedError::PromptBlocked { .. }));
+    }
+
     #[tokio::test]
     async fn test_generate_content_request_serialization() {
         let request: GenerateContentRequest<String> = GenerateContentRequest::builder()
@@ -493,6 +811,35 @@ mod tests {
         assert_eq!(response.first_text(), Some(&"Partial response".to_string()));
     }
 
+    #[test]
+    fn test_handle_stream_bytes_keeps_thoughts_out_of_answer() {
+        let chunk = |part: serde_json::Value| {
+            let json = serde_json::json!({"candidates": [{"content": {"role": "model", "parts": [part]}}]});
+            bytes::Bytes::from(format!("data: {json}\n\n"))
+        };
+        let mut sse_buffer = String::new();
+        let mut text_accumulator = String::new();
+        let mut handle = |part| {
+            GeminiV1Beta::handle_stream_bytes::<String>(
+                &chunk(part),
+                &mut sse_buffer,
+                &mut text_accumulator,
+            )
+            .unwrap()
+            .unwrap()
+        };
+
+        let thought = handle(serde_json::json!({"text": "THINK ", "thought": true}));
+        assert_eq!(thought.thought_summary().as_deref(), Some("THINK "));
+        assert_eq!(thought.first_text(), None);
+
+        let first = handle(serde_json::json!({"text": "Ans"}));
+        assert_eq!(first.first_text().map(String::as_str), Some("Ans"));
+        let second = handle(serde_json::json!({"text": "wer"}));
+        assert_eq!(second.first_text().map(String::as_str), Some("Answer"));
+        assert_eq!(second.thought_summary(), None);
+    }
+
     #[test]
     fn test_process_bytes_chunk() {
         let mut buffer = String::from("existing data");
@@ -583,6 +930,7 @@ mod tests {
     #[cfg(feature = "json")]
     mod typed_json_tests {
         use super::*;
+        use crate::dto_content::Json;
         use crate::dto_request::GenerationConfigBuilder;
         use schemars::JsonSchema;
         use serde::{Deserialize, Serialize};
@@ -603,14 +951,15 @@ mod tests {
                 .build()
                 .unwrap();
 
-            let request: GenerateContentRequest<Character> = GenerateContentRequest::builder()
-                .add_content(Content::user(vec![
-                    Part::builder()
-                        .text(JsonString::new("Create a character".to_string()))
-                        .build(),
-                ]))
-                .generation_config(config)
-                .build();
+            let request: GenerateContentRequest<Json<Character>> =
+                GenerateContentRequest::builder()
+                    .add_content(Content::user(vec![
+                        Part::builder()
+                            .text(JsonString::new("Create a character".to_string()))
+                            .build(),
+                    ]))
+                    .generation_config(config)
+                    .build();
 
             // Verify serialization works
             let json = serde_json::to_string(&request).unwrap();
@@ -651,6 +1000,7 @@ mod tests {
     #[cfg(feature = "openapi")]
     mod typed_openapi_tests {
         use super::*;
+        use crate::dto_content::Json;
         use crate::dto_request::GenerationConfigBuilder;
         use serde::{Deserialize, Serialize};
         use utoipa::ToSchema;
@@ -671,7 +1021,7 @@ mod tests {
                 .build()
                 .unwrap();
 
-            let request: GenerateContentRequest<Item> = GenerateContentRequest::builder()
+            let request: GenerateContentRequest<Json<Item>> = GenerateContentRequest::builder()
                 .add_content(Content::user(vec![
                     Part::builder()
                         .text(JsonString::new("Create an item".to_string()))
@@ -721,7 +1071,7 @@ mod tests {
     mod integration_tests {
         use super::*;
         #[allow(unused_imports)] // Used in #[ignore] integration tests
-        use crate::GenerationConfigBuilder;
+        use crate::{GenerationConfigBuilder, Json};
 
         #[tokio::test]
         #[ignore]
@@ -769,7 +1119,7 @@ mod tests {
                 .build()
                 .unwrap();
 
-            let request: GenerateContentRequest<Greeting> = GenerateContentRequest::builder()
+            let request: GenerateContentRequest<Json<Greeting>> = GenerateContentRequest::builder()
                 .add_content(Content::user(vec![
                     Part::builder()
                         .text(JsonString::new("Create a greeting in English".to_string()))
@@ -815,7 +1165,7 @@ mod tests {
                 .build()
                 .unwrap();
 
-            let request: GenerateContentRequest<Animal> = GenerateContentRequest::builder()
+            let request: GenerateContentRequest<Json<Animal>> = GenerateContentRequest::builder()
                 .add_content(Content::user(vec![
                     Part::builder()
                         .text(JsonString::new(
@@ -874,18 +1224,19 @@ mod tests {
                 .build()
                 .unwrap();
 
-            let request: GenerateContentRequest<Character> = GenerateContentRequest::builder()
-                .add_content(Content::user(vec![
-                    Part::builder()
-                        .text(JsonString::new(
-                            "Create a fantasy RPG character with name, age, class, \
+            let request: GenerateContentRequest<Json<Character>> =
+                GenerateContentRequest::builder()
+                    .add_content(Content::user(vec![
+                        Part::builder()
+                            .text(JsonString::new(
+                                "Create a fantasy RPG character with name, age, class, \
                              a list of 3 skills, and stats (strength, intelligence, charisma)"
-                                .to_string(),
-                        ))
-                        .build(),
-                ]))
-                .generation_config(config)
-                .build();
+                                    .to_string(),
+                            ))
+                            .build(),
+                    ]))
+                    .generation_config(config)
+                    .build();
 
             let response = client
                 .generate_content(request)
@@ -963,7 +1314,7 @@ mod tests {
                 .build()
                 .unwrap();
 
-            let request: GenerateContentRequest<Story> = GenerateContentRequest::builder()
+            let request: GenerateContentRequest<Json<Story>> = GenerateContentRequest::builder()
                 .add_content(Content::user(vec![
                     Part::builder()
                         .text(JsonString::new(
@@ -988,7 +1339,7 @@ mod tests {
                     Ok(response) => {
                         if let Some(story) = response.first_text() {
                             println!("Chunk {}: {:?}", chunk_count, story);
-                            last_story = Some(story.clone());
+                            last_story = Some(story.clone().into_inner());
                         }
                         chunk_count += 1;
                     }
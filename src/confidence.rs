@@ -0,0 +1,288 @@
+//! Per-field confidence for structured outputs.
+//!
+//! When `response_logprobs` is enabled, the chosen token of every decoding step is
+//! returned in `LogprobsResult::chosen_candidates`. Concatenating those tokens gives
+//! the raw JSON text of a structured response. This module locates every leaf value
+//! of that JSON and combines the log probabilities of the tokens that produced it.
+
+use std::ops::Range;
+
+use super::dto_response::LogprobsResult;
+
+/// Confidence of a single leaf value in a structured response
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldConfidence {
+    /// JSON Pointer to the value, e.g. `/items/0/name`; empty for a scalar root
+    pub path: String,
+    /// Raw JSON text of the value
+    pub raw: String,
+    /// Joint probability of all tokens that produced the value
+    pub probability: f64,
+    /// Probability of the least likely token that produced the value
+    pub min_token_probability: f64,
+    /// Number of tokens that produced the value
+    pub token_count: usize,
+}
+
+/// Computes the confidence of every leaf value in the JSON produced by the chosen tokens
+///
+/// Tokens that only partly overlap a value, such as a token holding both the
+/// opening quote and the first characters of a string, are attributed to it.
+/// Returns the leaves found before the first syntax error, so truncated output still
+/// yields confidence for the complete values.
+pub fn field_confidence(logprobs: &LogprobsResult) -> Vec<FieldConfidence> {
+    let mut text = String::new();
+    let mut tokens: Vec<(Range<usize>, f64)> = Vec::new();
+    for candidate in &logprobs.chosen_candidates {
+        let start = text.len();
+        text.push_str(&candidate.token);
+        tokens.push((start..text.len(), f64::from(candidate.log_probability)));
+    }
+
+    let mut scanner = Scanner {
+        text: &text,
+        pos: 0,
+        depth: 0,
+        leaves: Vec::new(),
+    };
+    scanner.value(String::new());
+
+    scanner
+        .leaves
+        .into_iter()
+        .map(|(path, span)| {
+            let logprobs: Vec<f64> = tokens
+                .iter()
+                .filter(|(range, _)| range.start < span.end && span.start < range.end)
+                .map(|(_, logprob)| *logprob)
+                .collect();
+            FieldConfidence {
+                path,
+                raw: text[span].to_string(),
+                probability: logprobs.iter().sum::<f64>().exp(),
+                min_token_probability: logprobs.iter().copied().fold(0.0, f64::min).exp(),
+                token_count: logprobs.len(),
+            }
+        })
+        .collect()
+}
+
+/// Minimal JSON scanner that records the byte span of every leaf value
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+    /// Number of enclosing objects and arrays
+    depth: usize,
+    leaves: Vec<(String, Range<usize>)>,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.skip_whitespace();
+        (self.peek()? == byte).then(|| self.pos += 1)
+    }
+
+    fn value(&mut self, path: String) -> Option<()> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'{' => {
+                self.depth += 1;
+                let result = self.object(&path);
+                self.depth -= 1;
+                result
+            }
+            b'[' => {
+                self.depth += 1;
+                let result = self.array(&path);
+                self.depth -= 1;
+                result
+            }
+            b'"' => {
+                let span = self.string()?;
+                self.leaves.push((path, span));
+                Some(())
+            }
+            _ => {
+                let start = self.pos;
+                while !matches!(
+                    self.peek(),
+                    None | Some(b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r')
+                ) {
+                    self.pos += 1;
+                }
+                // Inside a container, a literal cut off at EOF may be missing digits
+                if self.peek().is_none() && self.depth > 0 {
+                    return None;
+                }
+                (self.pos > start).then(|| self.leaves.push((path, start..self.pos)))
+            }
+        }
+    }
+
+    fn object(&mut self, path: &str) -> Option<()> {
+        self.pos += 1;
+        self.skip_whitespace();
+        if self.peek()? == b'}' {
+            self.pos += 1;
+            return Some(());
+        }
+        loop {
+            self.skip_whitespace();
+            let key_span = self.string()?;
+            let key: String = serde_json::from_str(&self.text[key_span]).ok()?;
+            self.expect(b':')?;
+            self.value(format!("{path}/{}", escape_pointer(&key)))?;
+            self.skip_whitespace();
+            match self.peek()? {
+                b',' => self.pos += 1,
+                b'}' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn array(&mut self, path: &str) -> Option<()> {
+        self.pos += 1;
+        self.skip_whitespace();
+        if self.peek()? == b']' {
+            self.pos += 1;
+            return Some(());
+        }
+        for index in 0.. {
+            self.value(format!("{path}/{index}"))?;
+            self.skip_whitespace();
+            match self.peek()? {
+                b',' => self.pos += 1,
+                b']' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// Consumes a string literal and returns its span including the quotes
+    fn string(&mut self) -> Option<Range<usize>> {
+        let start = self.pos;
+        if self.peek()? != b'"' {
+            return None;
+        }
+        self.pos += 1;
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return Some(start..self.pos);
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+}
+
+/// Escapes a key for use in a JSON Pointer (RFC 6901)
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto_response::LogprobsCandidate;
+
+    fn logprobs(tokens: &[(&str, f32)]) -> LogprobsResult {
+        LogprobsResult {
+            top_candidates: vec![],
+            chosen_candidates: tokens
+                .iter()
+                .map(|(token, log_probability)| LogprobsCandidate {
+                    token: token.to_string(),
+                    token_id: None,
+                    log_probability: *log_probability,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_field_confidence() {
+        let result = logprobs(&[
+            ("{\"", 0.0),
+            ("name", 0.0),
+            ("\": \"", 0.0),
+            ("Al", -0.1),
+            ("ice", -0.2),
+            ("\", \"", 0.0),
+            ("tags", 0.0),
+            ("\": [", 0.0),
+            ("1", -0.5),
+            (", ", 0.0),
+            ("true", -1.0),
+            ("], \"a/b\": null}", -0.3),
+        ]);
+
+        let fields = field_confidence(&result);
+        let paths: Vec<&str> = fields.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["/name", "/tags/0", "/tags/1", "/a~1b"]);
+
+        let name = &fields[0];
+        assert_eq!(name.raw, "\"Alice\"");
+        // The opening quote shares a token with the key separator
+        assert_eq!(name.token_count, 4);
+        assert!((name.probability - (-0.3f64).exp()).abs() < 1e-6);
+        assert!((name.min_token_probability - (-0.2f64).exp()).abs() < 1e-6);
+
+        assert_eq!(fields[1].raw, "1");
+        assert!((fields[1].probability - (-0.5f64).exp()).abs() < 1e-6);
+        assert_eq!(fields[3].raw, "null");
+    }
+
+    #[test]
+    fn test_field_confidence_truncated_and_scalar() {
+        let truncated = logprobs(&[("{\"a\": 1, \"b\": \"unfin", -0.1)]);
+        let fields = field_confidence(&truncated);
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].path, "/a");
+
+        let scalar = logprobs(&[("42", -0.25)]);
+        let fields = field_confidence(&scalar);
+        assert_eq!(fields[0].path, "");
+        assert_eq!(fields[0].raw, "42");
+
+        assert!(field_confidence(&logprobs(&[])).is_empty());
+    }
+
+    #[test]
+    fn test_field_confidence_number_truncated_at_eof() {
+        let truncated = logprobs(&[("{\"a\": true, \"n\": 12", -0.1)]);
+        let fields = field_confidence(&truncated);
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].path, "/a");
+
+        let truncated = logprobs(&[("[1, 2", -0.1)]);
+        let paths: Vec<String> = field_confidence(&truncated)
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        assert_eq!(paths, vec!["/0"]);
+
+        let closed = logprobs(&[("{\"n\": 12}", -0.1)]);
+        assert_eq!(field_confidence(&closed)[0].raw, "12");
+    }
+}
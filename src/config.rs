@@ -45,6 +45,16 @@ fn gemini_api_key() -> Result<String> {
     std::env::var("GEMINI_API_KEY").context("GEMINI_API_KEY environment variable not set")
 }
 
+/// How the client reports prompts and candidates blocked by safety filters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlockPolicy {
+    /// Return blocked responses unchanged (empty candidates or missing text).
+    #[default]
+    Passthrough,
+    /// Convert blocked responses into a `BlockedError` on both unary and streaming calls.
+    Error,
+}
+
 /// Configuration for the Gemini API client.
 #[derive(Clone)]
 pub struct GeminiConfig {
@@ -54,6 +64,8 @@ pub struct GeminiConfig {
     model: String,
     /// Base URL for the Gemini API
     base_url: String,
+    /// How blocked prompts and candidates are reported
+    block_policy: BlockPolicy,
 }
 
 impl std::fmt::Debug for GeminiConfig {
@@ -62,6 +74,7 @@ impl std::fmt::Debug for GeminiConfig {
             .field("api_key", &"[REDACTED]")
             .field("model", &self.model)
             .field("base_url", &self.base_url)
+            .field("block_policy", &self.block_policy)
             .finish()
     }
 }
@@ -88,6 +101,7 @@ impl GeminiConfig {
             api_key,
             model,
             base_url,
+            block_policy: BlockPolicy::default(),
         })
     }
 
@@ -102,9 +116,21 @@ impl GeminiConfig {
             api_key,
             model,
             base_url: "https://generativelanguage.googleapis.com/v1beta".to_string(),
+            block_policy: BlockPolicy::default(),
         }
     }
 
+    /// Sets how blocked prompts and candidates are reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The block policy to apply to every response
+    #[must_use]
+    pub fn with_block_policy(mut self, policy: BlockPolicy) -> Self {
+        self.block_policy = policy;
+        self
+    }
+
     /// Returns the API key.
     pub fn api_key(&self) -> &str {
         &self.api_key
@@ -119,6 +145,11 @@ impl GeminiConfig {
     pub fn base_url(&self) -> &str {
         &self.base_url
     }
+
+    /// Returns the block policy.
+    pub fn block_policy(&self) -> BlockPolicy {
+        self.block_policy
+    }
 }
 
 #[cfg(test)]
@@ -134,6 +165,14 @@ mod tests {
             config.base_url(),
             "https://generativelanguage.googleapis.com/v1beta"
         );
+        assert_eq!(config.block_policy(), BlockPolicy::Passthrough);
+    }
+
+    #[test]
+    fn test_gemini_config_with_block_policy() {
+        let config = GeminiConfig::new("test-key".to_string(), "gemini-2.5-flash".to_string())
+            .with_block_policy(BlockPolicy::Error);
+        assert_eq!(config.block_policy(), BlockPolicy::Error);
     }
 
     #[test]
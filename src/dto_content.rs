@@ -5,22 +5,27 @@
 //!
 //! ## Generic Text Content
 //!
-//! `Content` and `Part` are generic over the text content type, defaulting to `String`.
-//! When using `response_json_schema`, specify the desired type parameter and deserialization
-//! happens automatically:
+//! `Content` and `Part` are generic over the text payload type, defaulting to `String`.
+//! The payload is any [`TextPayload`]: `String` keeps text verbatim, `Json<T>` parses it
+//! into `T`, and `serde_json::Value` or `Box<RawValue>` keep untyped JSON. When using
+//! `response_json_schema`, the type parameter is `Json<T>` and deserialization happens
+//! automatically:
 //!
 //! ```rust,ignore
-//! let response: GenerateContentResponse<MySchema> = api.generate_content(request).await?;
-//! let my_data = response.first_text(); // Returns Option<&MySchema>
+//! let response: GenerateContentResponse<Json<MySchema>> = api.generate_content(request).await?;
+//! let my_data = response.first_text(); // Returns Option<&Json<MySchema>>, which derefs to MySchema
 //! ```
 
+use std::borrow::Cow;
+use std::ops::Range;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use serde::{Deserialize, Serialize};
 
 use crate::dto_request::MimeType;
 
-// Import for handling incomplete/streaming JSON in JsonString deserialization
-use deser_incomplete;
-
 /// Content object containing parts with role information
 ///
 /// Generic over the text content type `T`, which defaults to `String`.
@@ -32,49 +37,61 @@ use deser_incomplete;
 /// - `Model` - Content from the model (serializes with `role: "model"`)
 /// - `Unspecified` - Content without a role (role field omitted in serialization)
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(bound(
-    serialize = "T: Serialize + 'static",
-    deserialize = "T: serde::de::DeserializeOwned + 'static"
-))]
+#[serde(bound(serialize = "T: TextPayload", deserialize = "T: TextPayload"))]
 #[serde(tag = "role")]
 pub enum Content<T = String> {
     /// Content from the user
     #[serde(rename = "user")]
     User {
         /// Parts of the content
+        #[serde(default)]
         parts: Vec<Part<T>>,
     },
     /// Content from the model
     #[serde(rename = "model")]
     Model {
         /// Parts of the content
+        #[serde(default)]
         parts: Vec<Part<T>>,
     },
     /// Content without a specified role (role field omitted)
     #[serde(untagged)]
     Unspecified {
         /// Parts of the content
+        #[serde(default)]
         parts: Vec<Part<T>>,
     },
 }
 
-/// A wrapper type for JSON values stored as strings
+/// A wrapper type for text content that may hold JSON
 ///
-/// This type handles fields that need to be JSON-encoded and then serialized as strings.
-/// For example: `{"key": "{\"inner_key\": \"value\"}"}`
+/// The API always transfers text as a string. How that string maps to `T` is
+/// defined by [`TextPayload`]: `String` is kept verbatim, while [`Json<T>`],
+/// `serde_json::Value` and `Box<RawValue>` are JSON-encoded, e.g.
+/// `{"text": "{\"inner_key\": \"value\"}"}`.
 ///
-/// Special behavior for `JsonString<String>`:
-/// - Strings are serialized directly without additional JSON encoding
-/// - This prevents double-escaping of string values
-#[derive(Debug, Clone, PartialEq)]
+/// Deserialized values also keep the text exactly as received, see [`JsonString::raw`].
+#[derive(Debug, Clone)]
 pub struct JsonString<T> {
     inner: T,
+    raw: Option<String>,
+}
+
+impl<T: PartialEq> PartialEq for JsonString<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
 }
 
 impl<T> JsonString<T> {
     /// Create a new JSON string wrapper
     pub fn new(inner: T) -> Self {
-        Self { inner }
+        Self { inner, raw: None }
+    }
+
+    /// Get the text this value was parsed from, or `None` if it was built locally
+    pub fn raw(&self) -> Option<&str> {
+        self.raw.as_deref()
     }
 
     /// Get a reference to the inner value
@@ -88,66 +105,302 @@ impl<T> JsonString<T> {
     }
 }
 
-impl<T: Serialize + 'static> Serialize for JsonString<T> {
+impl<T: TextPayload> Serialize for JsonString<T> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        // Special case for String to avoid double-escaping
-        if std::any::TypeId::of::<T>() == std::any::TypeId::of::<String>() {
-            // SAFETY: We just checked that T is String
-            let s = unsafe { &*(&self.inner as *const T as *const String) };
-            return serializer.serialize_str(s);
-        }
-
-        // For all other types, JSON-encode then serialize as string
-        let json_string = serde_json::to_string(&self.inner).map_err(serde::ser::Error::custom)?;
-        serializer.serialize_str(&json_string)
+        let text = self.inner.to_text().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&text)
     }
 }
 
-impl<'de, T: serde::de::DeserializeOwned + 'static> Deserialize<'de> for JsonString<T> {
+impl<'de, T: TextPayload> Deserialize<'de> for JsonString<T> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
+        let inner = T::from_text(s.clone()).map_err(serde::de::Error::custom)?;
+        Ok(JsonString {
+            inner,
+            raw: Some(s),
+        })
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Text content type of a `Part`, `Content` or response
+///
+/// Implemented for:
+/// - `String`, sent and received verbatim
+/// - [`Json<T>`], for typed structured output; `T` is JSON-encoded
+/// - `serde_json::Value`, for structured output whose shape is only known at runtime
+/// - [`Enum<E>`], for `text/x.enum` classification output; `E` is a unit-variant enum
+/// - `Box<serde_json::value::RawValue>`, to keep structured output unparsed
+///
+/// This trait is sealed and cannot be implemented outside this crate.
+pub trait TextPayload: sealed::Sealed + Sized {
+    /// Whether a response schema must be configured to request this payload
+    const REQUIRES_SCHEMA: bool;
+
+    /// Returns the text sent to the API
+    fn to_text(&self) -> Result<Cow<'_, str>, serde_json::Error>;
+
+    /// Parses text received from the API
+    ///
+    /// JSON payloads other than `Box<RawValue>` tolerate truncated input, so partial
+    /// streamed output still yields the fields received so far.
+    fn from_text(text: String) -> Result<Self, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+impl sealed::Sealed for String {}
+
+impl TextPayload for String {
+    const REQUIRES_SCHEMA: bool = false;
+
+    fn to_text(&self) -> Result<Cow<'_, str>, serde_json::Error> {
+        Ok(Cow::Borrowed(self))
+    }
+
+    fn from_text(text: String) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(text)
+    }
+}
+
+impl<T: Serialize + serde::de::DeserializeOwned> sealed::Sealed for Json<T> {}
+
+impl<T: Serialize + serde::de::DeserializeOwned> TextPayload for Json<T> {
+    const REQUIRES_SCHEMA: bool = true;
+
+    fn to_text(&self) -> Result<Cow<'_, str>, serde_json::Error> {
+        serde_json::to_string(&self.0).map(Cow::Owned)
+    }
+
+    fn from_text(text: String) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        parse_incomplete_json(&text).map(Json)
+    }
+}
+
+impl<E: Serialize + serde::de::DeserializeOwned> sealed::Sealed for Enum<E> {}
 
-        // Special case for String to avoid double-escaping
-        if std::any::TypeId::of::<T>() == std::any::TypeId::of::<String>() {
-            // SAFETY: We just checked that T is String
-            let inner = unsafe { std::ptr::read(&s as *const String as *const T) };
-            std::mem::forget(s); // Prevent double-free
-            return Ok(JsonString { inner });
+impl<E: Serialize + serde::de::DeserializeOwned> TextPayload for Enum<E> {
+    const REQUIRES_SCHEMA: bool = true;
+
+    fn to_text(&self) -> Result<Cow<'_, str>, serde_json::Error> {
+        match serde_json::to_value(&self.0)? {
+            serde_json::Value::String(variant) => Ok(Cow::Owned(variant)),
+            other => Err(serde::ser::Error::custom(format!(
+                "enum response value must serialize to a variant name, got {other}"
+            ))),
         }
+    }
 
-        // For all other types, parse the JSON string using deser_incomplete
-        // to handle streaming/incomplete JSON gracefully
-        // tracing::debug!(
-        //     "🔍 JsonString<T>::deserialize: attempting to parse {} chars: {:?}",
-        //     s.len(),
-        //     if s.len() > 200 {
-        //         format!("{}...", &s[..200])
-        //     } else {
-        //         s.clone()
-        //     }
-        // );
-
-        let inner = deser_incomplete::from_json_str(&s).map_err(|e| {
-            tracing::error!(
-                "❌ JsonString<T>::deserialize failed: {} for input: {:?}",
-                e,
-                if s.len() > 200 {
-                    format!("{}...", &s[..200])
-                } else {
-                    s.clone()
-                }
-            );
-            serde::de::Error::custom(e)
-        })?;
+    fn from_text(text: String) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let variant = text.trim();
+        E::deserialize(serde::de::value::StrDeserializer::<serde::de::value::Error>::new(variant))
+            .map(Enum)
+            .map_err(|e| format!("unexpected enum response {variant:?}: {e}").into())
+    }
+}
+
+impl sealed::Sealed for serde_json::Value {}
+
+impl TextPayload for serde_json::Value {
+    const REQUIRES_SCHEMA: bool = false;
+
+    fn to_text(&self) -> Result<Cow<'_, str>, serde_json::Error> {
+        serde_json::to_string(self).map(Cow::Owned)
+    }
+
+    fn from_text(text: String) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        parse_incomplete_json(&text)
+    }
+}
+
+impl sealed::Sealed for Box<serde_json::value::RawValue> {}
+
+impl TextPayload for Box<serde_json::value::RawValue> {
+    const REQUIRES_SCHEMA: bool = false;
+
+    fn to_text(&self) -> Result<Cow<'_, str>, serde_json::Error> {
+        Ok(Cow::Borrowed(self.get()))
+    }
+
+    fn from_text(text: String) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(serde_json::value::RawValue::from_string(text)?)
+    }
+}
+
+/// Parses JSON that may be truncated, as happens with streamed output
+fn parse_incomplete_json<T: serde::de::DeserializeOwned>(
+    text: &str,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    deser_incomplete::from_json_str(text).map_err(|e| {
+        tracing::error!(
+            "❌ JsonString<T>::deserialize failed: {} for input: {:?}",
+            e,
+            if text.len() > 200 {
+                format!("{}...", &text[..text.floor_char_boundary(200)])
+            } else {
+                text.to_string()
+            }
+        );
+        e.to_string().into()
+    })
+}
+
+/// Structured output of type `T`, JSON-encoded in text parts
+///
+/// Dereferences to `T`, so fields of a typed response can be read directly:
+///
+/// ```rust,ignore
+/// let response: GenerateContentResponse<Json<Recipe>> = api.generate_content(request).await?;
+/// let recipe = response.first_text().unwrap();
+/// println!("{}", recipe.name);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    /// Unwraps the inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Json<T> {
+    fn from(value: T) -> Self {
+        Json(value)
+    }
+}
+
+impl<T> std::ops::Deref for Json<T> {
+    type Target = T;
 
-        tracing::debug!("✅ JsonString<T>::deserialize: successfully parsed");
-        Ok(JsonString { inner })
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for Json<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for Json<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Classification output of type `E`, sent as a bare variant name with `text/x.enum`
+///
+/// `E` is an enum of unit variants deriving `Serialize` and `Deserialize`; the variant
+/// names it deserializes from, including `#[serde(rename)]`s, are the values offered to
+/// the model. See [`GenerationConfigBuilder::enum_response`](crate::dto_request::GenerationConfigBuilder::enum_response).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Enum<E>(pub E);
+
+impl<E> Enum<E> {
+    /// Unwraps the inner value
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+impl<E: serde::de::DeserializeOwned> Enum<E> {
+    /// Returns the variant names `E` deserializes from, or `None` if `E` is not an
+    /// externally tagged enum of unit variants
+    pub fn variants() -> Option<&'static [&'static str]> {
+        match E::deserialize(VariantProbe) {
+            // A data-carrying variant cannot be parsed back from its bare name
+            Err(ProbeError::Variants(variants)) => variants
+                .iter()
+                .all(|variant| {
+                    E::deserialize(
+                        serde::de::value::StrDeserializer::<serde::de::value::Error>::new(variant),
+                    )
+                    .is_ok()
+                })
+                .then_some(variants),
+            _ => None,
+        }
+    }
+}
+
+impl<E> From<E> for Enum<E> {
+    fn from(value: E) -> Self {
+        Enum(value)
+    }
+}
+
+impl<E> std::ops::Deref for Enum<E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        &self.0
+    }
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for Enum<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Deserializer that only records the variant list serde passes to `deserialize_enum`
+struct VariantProbe;
+
+#[derive(Debug)]
+enum ProbeError {
+    Variants(&'static [&'static str]),
+    NotAnEnum,
+}
+
+impl std::fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "not an enum")
+    }
+}
+
+impl std::error::Error for ProbeError {}
+
+impl serde::de::Error for ProbeError {
+    fn custom<T: std::fmt::Display>(_msg: T) -> Self {
+        ProbeError::NotAnEnum
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for VariantProbe {
+    type Error = ProbeError;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, ProbeError> {
+        Err(ProbeError::NotAnEnum)
+    }
+
+    fn deserialize_enum<V: serde::de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, ProbeError> {
+        Err(ProbeError::Variants(variants))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
     }
 }
 
@@ -156,11 +409,14 @@ impl<'de, T: serde::de::DeserializeOwned + 'static> Deserialize<'de> for JsonStr
 /// Generic over the text content type `T`, which defaults to `String`.
 /// When using `response_json_schema`, specify the type parameter and JSON deserialization
 /// happens automatically during response parsing.
+///
+/// Thought summaries (parts with `thought: true`) are always kept as plain text and are
+/// not returned by `text()`, so they never have to match the response type `T`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(bound(
-    serialize = "T: Serialize + 'static",
-    deserialize = "T: serde::de::DeserializeOwned + 'static"
-))]
+#[serde(
+    bound(serialize = "T: TextPayload", deserialize = "T: TextPayload"),
+    try_from = "PartRepr"
+)]
 pub struct Part<T = String> {
     /// Inline text content
     ///
@@ -168,6 +424,18 @@ pub struct Part<T = String> {
     #[serde(skip_serializing_if = "Option::is_none")]
     text: Option<JsonString<T>>,
 
+    /// Text of a thought summary, serialized as `text` alongside `thought: true`
+    #[serde(rename = "text", skip_serializing_if = "Option::is_none")]
+    thought_text: Option<String>,
+
+    /// Whether this part is a thought summary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thought: Option<bool>,
+
+    /// Opaque signature of the model's thinking, which must be sent back unchanged
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thought_signature: Option<String>,
+
     /// Inline media bytes
     #[serde(skip_serializing_if = "Option::is_none")]
     inline_data: Option<Blob>,
@@ -197,6 +465,64 @@ pub struct Part<T = String> {
     video_metadata: Option<VideoMetadata>,
 }
 
+/// Wire representation of `Part` used during deserialization
+///
+/// Keeps `text` as a raw string until `thought` is known, so that thought summaries are
+/// not parsed as the response type.
+#[derive(Deserialize)]
+struct PartRepr {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    thought: Option<bool>,
+    #[serde(default, alias = "thoughtSignature")]
+    thought_signature: Option<String>,
+    #[serde(default, alias = "inlineData")]
+    inline_data: Option<Blob>,
+    #[serde(default, alias = "functionCall")]
+    function_call: Option<FunctionCall>,
+    #[serde(default, alias = "functionResponse")]
+    function_response: Option<FunctionResponse>,
+    #[serde(default, alias = "fileData")]
+    file_data: Option<FileData>,
+    #[serde(default, alias = "executableCode")]
+    executable_code: Option<ExecutableCode>,
+    #[serde(default, alias = "codeExecutionResult")]
+    code_execution_result: Option<CodeExecutionResult>,
+    #[serde(default, alias = "videoMetadata")]
+    video_metadata: Option<VideoMetadata>,
+}
+
+impl<T: TextPayload> TryFrom<PartRepr> for Part<T> {
+    type Error = serde::de::value::Error;
+
+    fn try_from(repr: PartRepr) -> Result<Self, Self::Error> {
+        let is_thought = repr.thought == Some(true);
+        let (text, thought_text) = match repr.text {
+            Some(text) if is_thought => (None, Some(text)),
+            Some(text) => {
+                let deserializer = serde::de::value::StringDeserializer::<Self::Error>::new(text);
+                (Some(JsonString::deserialize(deserializer)?), None)
+            }
+            None => (None, None),
+        };
+
+        Ok(Part {
+            text,
+            thought_text,
+            thought: repr.thought,
+            thought_signature: repr.thought_signature,
+            inline_data: repr.inline_data,
+            function_call: repr.function_call,
+            function_response: repr.function_response,
+            file_data: repr.file_data,
+            executable_code: repr.executable_code,
+            code_execution_result: repr.code_execution_result,
+            video_metadata: repr.video_metadata,
+        })
+    }
+}
+
 /// Builder for constructing `Part<T>` instances
 ///
 /// Use this builder when creating `Part` instances with multiple fields to avoid
@@ -204,6 +530,8 @@ pub struct Part<T = String> {
 #[derive(Debug)]
 pub struct PartBuilder<T = String> {
     text: Option<JsonString<T>>,
+    thought_text: Option<String>,
+    thought_signature: Option<String>,
     inline_data: Option<Blob>,
     function_call: Option<FunctionCall>,
     function_response: Option<FunctionResponse>,
@@ -217,6 +545,8 @@ impl<T> Default for PartBuilder<T> {
     fn default() -> Self {
         Self {
             text: None,
+            thought_text: None,
+            thought_signature: None,
             inline_data: None,
             function_call: None,
             function_response: None,
@@ -241,6 +571,20 @@ impl<T> PartBuilder<T> {
         self
     }
 
+    /// Sets the text of a thought summary, marking the part as a thought
+    #[must_use]
+    pub fn thought_text(mut self, text: impl Into<String>) -> Self {
+        self.thought_text = Some(text.into());
+        self
+    }
+
+    /// Sets the thought signature returned by the model
+    #[must_use]
+    pub fn thought_signature(mut self, signature: impl Into<String>) -> Self {
+        self.thought_signature = Some(signature.into());
+        self
+    }
+
     /// Sets the inline data (media bytes)
     #[must_use]
     pub fn inline_data(mut self, inline_data: Blob) -> Self {
@@ -294,6 +638,9 @@ impl<T> PartBuilder<T> {
     pub fn build(self) -> Part<T> {
         Part {
             text: self.text,
+            thought: self.thought_text.is_some().then_some(true),
+            thought_text: self.thought_text,
+            thought_signature: self.thought_signature,
             inline_data: self.inline_data,
             function_call: self.function_call,
             function_response: self.function_response,
@@ -309,10 +656,100 @@ impl<T> PartBuilder<T> {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Blob {
     /// The IANA standard MIME type of the source data
+    #[serde(alias = "mimeType")]
     pub mime_type: MimeType,
 
-    /// Raw bytes for media formats (base64-encoded)
-    pub data: String,
+    /// Raw bytes for media formats, base64-encoded on the wire
+    pub data: BlobData,
+}
+
+impl Blob {
+    /// Creates a blob from raw bytes
+    pub fn new(mime_type: MimeType, data: impl Into<bytes::Bytes>) -> Self {
+        Self {
+            mime_type,
+            data: BlobData::from_bytes(data),
+        }
+    }
+}
+
+/// Media bytes that are base64-encoded or decoded only when needed
+///
+/// Blobs built from raw bytes are encoded during serialization, and blobs received
+/// from the API keep their base64 text until the bytes are requested.
+#[derive(Clone)]
+pub struct BlobData(BlobRepr);
+
+#[derive(Clone)]
+enum BlobRepr {
+    Raw(bytes::Bytes),
+    Encoded(String),
+}
+
+impl BlobData {
+    /// Wraps raw bytes
+    pub fn from_bytes(data: impl Into<bytes::Bytes>) -> Self {
+        Self(BlobRepr::Raw(data.into()))
+    }
+
+    /// Wraps base64-encoded data without decoding it
+    pub fn from_base64(data: impl Into<String>) -> Self {
+        Self(BlobRepr::Encoded(data.into()))
+    }
+
+    /// Returns the base64-encoded data
+    pub fn to_base64(&self) -> std::borrow::Cow<'_, str> {
+        match &self.0 {
+            BlobRepr::Raw(data) => BASE64.encode(data).into(),
+            BlobRepr::Encoded(data) => data.as_str().into(),
+        }
+    }
+
+    /// Returns the decoded bytes
+    ///
+    /// # Errors
+    /// Returns an error if the data was received as invalid base64.
+    pub fn to_bytes(&self) -> Result<bytes::Bytes, base64::DecodeError> {
+        match &self.0 {
+            BlobRepr::Raw(data) => Ok(data.clone()),
+            BlobRepr::Encoded(data) => BASE64.decode(data).map(bytes::Bytes::from),
+        }
+    }
+
+    /// Returns the size of the data once base64-encoded
+    pub fn encoded_len(&self) -> usize {
+        match &self.0 {
+            BlobRepr::Raw(data) => data.len().div_ceil(3) * 4,
+            BlobRepr::Encoded(data) => data.len(),
+        }
+    }
+}
+
+impl std::fmt::Debug for BlobData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            BlobRepr::Raw(data) => write!(f, "BlobData({} bytes)", data.len()),
+            BlobRepr::Encoded(data) => write!(f, "BlobData({} base64 chars)", data.len()),
+        }
+    }
+}
+
+impl Serialize for BlobData {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_base64())
+    }
+}
+
+impl<'de> Deserialize<'de> for BlobData {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(BlobData::from_base64)
+    }
 }
 
 /// A predicted function call returned from the model
@@ -340,10 +777,11 @@ pub struct FunctionResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileData {
     /// The IANA standard MIME type of the source data
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "mimeType", skip_serializing_if = "Option::is_none")]
     pub mime_type: Option<MimeType>,
 
     /// URI
+    #[serde(alias = "fileUri")]
     pub file_uri: String,
 }
 
@@ -351,17 +789,64 @@ pub struct FileData {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutableCode {
     /// Programming language of the code
-    pub language: String,
+    pub language: Language,
 
     /// The code to be executed
     pub code: String,
 }
 
+/// Programming language of `ExecutableCode`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Language {
+    /// Default value, unused
+    LanguageUnspecified,
+    /// Python >= 3.10
+    Python,
+    /// A language not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Language::LanguageUnspecified => Ok(()),
+            Language::Python => f.write_str("python"),
+            Language::Unknown(language) => f.write_str(&language.to_lowercase()),
+        }
+    }
+}
+
+/// Outcome of executing `ExecutableCode`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Outcome {
+    /// Default value, unused
+    OutcomeUnspecified,
+    /// Execution completed successfully
+    OutcomeOk,
+    /// Execution finished with an error; `output` holds the error details
+    OutcomeFailed,
+    /// Execution ran too long and was cancelled
+    OutcomeDeadlineExceeded,
+    /// An outcome not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl Outcome {
+    /// Returns true if the code ran to completion without error
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Outcome::OutcomeOk)
+    }
+}
+
 /// Result of executing the ExecutableCode
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeExecutionResult {
     /// Outcome of the code execution
-    pub outcome: String,
+    pub outcome: Outcome,
 
     /// Contains stdout when code execution is successful, stderr or other description otherwise
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -369,21 +854,113 @@ pub struct CodeExecutionResult {
 }
 
 /// Metadata describing the input video content
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Offsets are serialized in the API's duration format, e.g. `"12.5s"`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct VideoMetadata {
-    /// The start offset of the video
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub start_offset: Option<String>,
-
-    /// The end offset of the video
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub end_offset: Option<String>,
+    /// Where the model starts watching the video
+    #[serde(
+        default,
+        alias = "startOffset",
+        with = "duration_format",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start_offset: Option<Duration>,
+
+    /// Where the model stops watching the video
+    #[serde(
+        default,
+        alias = "endOffset",
+        with = "duration_format",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub end_offset: Option<Duration>,
 
     /// The frame rate of the video (0.0, 24.0]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fps: Option<f32>,
 }
 
+impl VideoMetadata {
+    /// Creates metadata that limits the model to part of the video
+    pub fn clip(range: Range<Duration>) -> Self {
+        Self {
+            start_offset: Some(range.start),
+            end_offset: Some(range.end),
+            fps: None,
+        }
+    }
+
+    /// Creates metadata that samples the video at a custom frame rate
+    pub fn fps(fps: f32) -> Self {
+        Self {
+            fps: Some(fps),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the frame rate at which the video is sampled
+    #[must_use]
+    pub fn with_fps(mut self, fps: f32) -> Self {
+        self.fps = Some(fps);
+        self
+    }
+}
+
+/// (De)serializes optional durations in the protobuf JSON format, e.g. `"12.5s"`
+mod duration_format {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match duration {
+            Some(duration) => serializer.serialize_str(&format(*duration)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let Some(text) = Option::<String>::deserialize(deserializer)? else {
+            return Ok(None);
+        };
+        parse(&text)
+            .map(Some)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid duration: {text:?}")))
+    }
+
+    fn format(duration: Duration) -> String {
+        let nanos = duration.subsec_nanos();
+        if nanos == 0 {
+            return format!("{}s", duration.as_secs());
+        }
+        let fraction = format!("{nanos:09}");
+        format!("{}.{}s", duration.as_secs(), fraction.trim_end_matches('0'))
+    }
+
+    fn parse(text: &str) -> Option<Duration> {
+        let (seconds, fraction) = match text.strip_suffix('s')?.split_once('.') {
+            Some((seconds, fraction)) => (seconds, fraction),
+            None => (text.strip_suffix('s')?, ""),
+        };
+        if fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let nanos = if fraction.is_empty() {
+            0
+        } else {
+            format!("{fraction:0<9}").parse().ok()?
+        };
+        Some(Duration::new(seconds.parse().ok()?, nanos))
+    }
+}
+
 impl<T> Part<T> {
     /// Creates a new builder for constructing a `Part` instance
     pub fn builder() -> PartBuilder<T> {
@@ -395,14 +972,44 @@ impl<T> Part<T> {
         self.text.is_some()
     }
 
+    /// Returns true if this part is a thought summary
+    pub fn is_thought(&self) -> bool {
+        self.thought == Some(true)
+    }
+
+    /// Get the text of a thought summary
+    pub fn thought_text(&self) -> Option<&str> {
+        self.thought_text.as_deref()
+    }
+
+    /// Get the thought signature, if the model attached one to this part
+    pub fn thought_signature(&self) -> Option<&str> {
+        self.thought_signature.as_deref()
+    }
+
     /// Get a reference to the text content
+    ///
+    /// Returns `None` for thought summaries, see [`Part::thought_text`].
     pub fn text(&self) -> Option<&T> {
         self.text.as_ref().map(|field| field.inner())
     }
 
+    /// Get the text exactly as received from the API
+    ///
+    /// Unlike re-serializing [`Part::text`], this keeps the model's formatting and any
+    /// fields the response type ignores. Returns `None` for parts built locally.
+    pub fn raw_text(&self) -> Option<&str> {
+        self.text.as_ref().and_then(|field| field.raw())
+    }
+
     /// Get a mutable reference to the text content
+    ///
+    /// The received text is dropped, since it may no longer match.
     pub fn text_mut(&mut self) -> Option<&mut T> {
-        self.text.as_mut().map(|field| &mut field.inner)
+        self.text.as_mut().map(|field| {
+            field.raw = None;
+            &mut field.inner
+        })
     }
 
     /// Consume self and return the text content
@@ -444,6 +1051,17 @@ impl<T> Part<T> {
     pub fn video_metadata(&self) -> Option<&VideoMetadata> {
         self.video_metadata.as_ref()
     }
+
+    /// Limits a video part to a time range, keeping any configured frame rate
+    #[must_use]
+    pub fn clipped(mut self, range: Range<Duration>) -> Self {
+        let fps = self.video_metadata.take().and_then(|metadata| metadata.fps);
+        self.video_metadata = Some(VideoMetadata {
+            fps,
+            ..VideoMetadata::clip(range)
+        });
+        self
+    }
 }
 
 impl<T> Content<T> {
@@ -500,9 +1118,89 @@ impl<T> Content<T> {
         self.parts().first()
     }
 
-    /// Returns the first part's text, if any
+    /// Consumes the content and returns its parts
+    pub fn into_parts(self) -> Vec<Part<T>> {
+        match self {
+            Content::User { parts } => parts,
+            Content::Model { parts } => parts,
+            Content::Unspecified { parts } => parts,
+        }
+    }
+
+    /// Returns the text of the first part that is not a thought, if any
     pub fn first_text(&self) -> Option<&T> {
-        self.first_part().and_then(|p| p.text())
+        self.parts().iter().find_map(|p| p.text())
+    }
+
+    /// Returns the thought summaries in order
+    pub fn thoughts(&self) -> impl Iterator<Item = &str> {
+        self.parts().iter().filter_map(|p| p.thought_text())
+    }
+
+    /// Returns the parts that are not thought summaries
+    pub fn answer_parts(&self) -> impl Iterator<Item = &Part<T>> {
+        self.parts().iter().filter(|p| !p.is_thought())
+    }
+
+    /// Returns text, thoughts, code, execution results and media in part order
+    ///
+    /// Parts without displayable content, such as function calls, are skipped.
+    pub fn transcript(&self) -> Vec<TranscriptEntry<'_, T>> {
+        self.parts()
+            .iter()
+            .filter_map(|part| {
+                if let Some(thought) = part.thought_text() {
+                    Some(TranscriptEntry::Thought(thought))
+                } else if let Some(text) = part.text() {
+                    Some(TranscriptEntry::Text(text))
+                } else if let Some(code) = part.executable_code() {
+                    Some(TranscriptEntry::Code(code))
+                } else if let Some(result) = part.code_execution_result() {
+                    Some(TranscriptEntry::Result(result))
+                } else {
+                    part.inline_data().map(TranscriptEntry::Media)
+                }
+            })
+            .collect()
+    }
+}
+
+/// A single entry of a content transcript, in part order
+///
+/// Displays as Markdown: text as-is, code in a fenced block tagged with its
+/// language, and execution output in a plain fenced block.
+#[derive(Debug, Clone, Copy)]
+pub enum TranscriptEntry<'a, T = String> {
+    /// Answer text
+    Text(&'a T),
+    /// Thought summary
+    Thought(&'a str),
+    /// Code the model generated for execution
+    Code(&'a ExecutableCode),
+    /// Result of executing the preceding code
+    Result(&'a CodeExecutionResult),
+    /// Inline media, such as a plot produced by executed code
+    Media(&'a Blob),
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for TranscriptEntry<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranscriptEntry::Text(text) => write!(f, "{text}"),
+            TranscriptEntry::Thought(text) => write!(f, "> {text}"),
+            TranscriptEntry::Code(code) => {
+                write!(f, "```{}\n{}\n```", code.language, code.code.trim_end())
+            }
+            TranscriptEntry::Result(result) => {
+                let output = result.output.as_deref().unwrap_or_default().trim_end();
+                if result.outcome.is_ok() {
+                    write!(f, "```\n{output}\n```")
+                } else {
+                    write!(f, "{:?}:\n```\n{output}\n```", result.outcome)
+                }
+            }
+            TranscriptEntry::Media(blob) => write!(f, "[{}]", blob.mime_type),
+        }
     }
 }
 
@@ -530,10 +1228,10 @@ mod tests {
     #[test]
     fn test_part_text_mut() {
         let mut part = Part::builder()
-            .text(JsonString::new(TestSchema {
+            .text(JsonString::new(Json(TestSchema {
                 name: "Alice".to_string(),
                 age: 30,
-            }))
+            })))
             .build();
 
         if let Some(schema) = part.text_mut() {
@@ -566,7 +1264,7 @@ mod tests {
             ]
         }"#;
 
-        let content: Content<TestSchema> = serde_json::from_str(json).unwrap();
+        let content: Content<Json<TestSchema>> = serde_json::from_str(json).unwrap();
         assert!(content.is_model());
         assert_eq!(content.parts().len(), 2);
         assert_eq!(content.parts()[0].text().unwrap().name, "Alice");
@@ -607,16 +1305,16 @@ mod tests {
 
     #[test]
     fn test_json_string_serialize() {
-        #[derive(Debug, Serialize)]
+        #[derive(Debug, Serialize, Deserialize)]
         struct Inner {
             key: String,
             value: i32,
         }
 
-        let inner = Inner {
+        let inner = Json(Inner {
             key: "test".to_string(),
             value: 42,
-        };
+        });
 
         let wrapped = JsonString::new(inner);
         let serialized = serde_json::to_string(&wrapped).unwrap();
@@ -628,14 +1326,14 @@ mod tests {
 
     #[test]
     fn test_json_string_deserialize() {
-        #[derive(Debug, Deserialize, PartialEq)]
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
         struct Inner {
             key: String,
             value: i32,
         }
 
         let json = r#""{\"key\":\"test\",\"value\":42}""#;
-        let wrapped: JsonString<Inner> = serde_json::from_str(json).unwrap();
+        let wrapped: JsonString<Json<Inner>> = serde_json::from_str(json).unwrap();
 
         assert_eq!(wrapped.inner().key, "test");
         assert_eq!(wrapped.inner().value, 42);
@@ -648,25 +1346,25 @@ mod tests {
             age: 30,
         };
 
-        let wrapped = JsonString::new(original.clone());
+        let wrapped = JsonString::new(Json(original.clone()));
         let serialized = serde_json::to_string(&wrapped).unwrap();
-        let deserialized: JsonString<TestSchema> = serde_json::from_str(&serialized).unwrap();
+        let deserialized: JsonString<Json<TestSchema>> = serde_json::from_str(&serialized).unwrap();
 
-        assert_eq!(deserialized.inner(), &original);
+        assert_eq!(deserialized.inner().0, original);
     }
 
     #[test]
     fn test_json_string_in_struct() {
         #[derive(Debug, Serialize, Deserialize)]
         struct Outer {
-            field: JsonString<TestSchema>,
+            field: JsonString<Json<TestSchema>>,
         }
 
         let outer = Outer {
-            field: JsonString::new(TestSchema {
+            field: JsonString::new(Json(TestSchema {
                 name: "Bob".to_string(),
                 age: 25,
-            }),
+            })),
         };
 
         let serialized = serde_json::to_string(&outer).unwrap();
@@ -733,7 +1431,7 @@ mod tests {
     fn test_part_builder_multiple_fields() {
         let blob = Blob {
             mime_type: MimeType::ImagePng,
-            data: "base64data".to_string(),
+            data: BlobData::from_base64("base64data"),
         };
 
         let part = Part::builder()
@@ -743,7 +1441,7 @@ mod tests {
 
         assert!(part.has_text());
         assert!(part.inline_data().is_some());
-        assert_eq!(part.inline_data().unwrap().data, "base64data");
+        assert_eq!(part.inline_data().unwrap().data.to_base64(), "base64data");
     }
 
     #[test]
@@ -786,7 +1484,7 @@ mod tests {
     fn test_part_getters() {
         let blob = Blob {
             mime_type: MimeType::ImagePng,
-            data: "test_data".to_string(),
+            data: BlobData::from_base64("test_data"),
         };
 
         let function_call = FunctionCall {
@@ -800,7 +1498,7 @@ mod tests {
             .build();
 
         assert!(part.inline_data().is_some());
-        assert_eq!(part.inline_data().unwrap().data, "test_data");
+        assert_eq!(part.inline_data().unwrap().data.to_base64(), "test_data");
         assert!(part.function_call().is_some());
         assert_eq!(part.function_call().unwrap().name, "test_fn");
         assert!(part.function_response().is_none());
@@ -809,4 +1507,232 @@ mod tests {
         assert!(part.code_execution_result().is_none());
         assert!(part.video_metadata().is_none());
     }
+
+    #[test]
+    fn test_thought_part_deserialization_typed() {
+        let json = r#"[
+            {"text": "Let me think about the name", "thought": true},
+            {"text": "{\"name\": \"Alice\", \"age\": 30}", "thoughtSignature": "c2ln"}
+        ]"#;
+
+        let parts: Vec<Part<Json<TestSchema>>> = serde_json::from_str(json).unwrap();
+        assert!(parts[0].is_thought());
+        assert_eq!(parts[0].text(), None);
+        assert_eq!(parts[0].thought_text(), Some("Let me think about the name"));
+        assert!(!parts[1].is_thought());
+        assert_eq!(parts[1].text().unwrap().name, "Alice");
+        assert_eq!(parts[1].thought_signature(), Some("c2ln"));
+
+        let content = Content::model(parts);
+        assert_eq!(content.first_text().unwrap().age, 30);
+        assert_eq!(
+            content.thoughts().collect::<Vec<_>>(),
+            vec!["Let me think about the name"]
+        );
+        assert_eq!(content.answer_parts().count(), 1);
+    }
+
+    #[test]
+    fn test_thought_part_serialization() {
+        let part: Part<String> = Part::builder()
+            .thought_text("Considering options")
+            .thought_signature("c2ln")
+            .build();
+
+        let json = serde_json::to_value(&part).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "text": "Considering options",
+                "thought": true,
+                "thought_signature": "c2ln"
+            })
+        );
+
+        let roundtrip: Part<String> = serde_json::from_value(json).unwrap();
+        assert!(roundtrip.is_thought());
+        assert_eq!(roundtrip.thought_text(), Some("Considering options"));
+    }
+
+    #[test]
+    fn test_code_execution_transcript() {
+        let json = r#"{
+            "role": "model",
+            "parts": [
+                {"text": "Let me compute that."},
+                {"executableCode": {"language": "PYTHON", "code": "print(2 + 2)\n"}},
+                {"codeExecutionResult": {"outcome": "OUTCOME_OK", "output": "4\n"}},
+                {"executableCode": {"language": "PYTHON", "code": "1 / 0"}},
+                {"codeExecutionResult": {"outcome": "OUTCOME_FAILED", "output": "ZeroDivisionError"}},
+                {"text": "The answer is 4."}
+            ]
+        }"#;
+
+        let content: Content = serde_json::from_str(json).unwrap();
+        let transcript = content.transcript();
+        assert_eq!(transcript.len(), 6);
+
+        let TranscriptEntry::Code(code) = transcript[1] else {
+            panic!("expected code");
+        };
+        assert_eq!(code.language, Language::Python);
+        let TranscriptEntry::Result(result) = transcript[4] else {
+            panic!("expected result");
+        };
+        assert_eq!(result.outcome, Outcome::OutcomeFailed);
+        assert!(!result.outcome.is_ok());
+
+        let rendered: Vec<String> = transcript.iter().map(|e| e.to_string()).collect();
+        assert_eq!(rendered[1], "```python\nprint(2 + 2)\n```");
+        assert_eq!(rendered[2], "```\n4\n```");
+        assert_eq!(rendered[4], "OutcomeFailed:\n```\nZeroDivisionError\n```");
+
+        let outcome: Outcome = serde_json::from_str(r#""OUTCOME_DEADLINE_EXCEEDED""#).unwrap();
+        assert_eq!(outcome, Outcome::OutcomeDeadlineExceeded);
+    }
+
+    #[test]
+    fn test_blob_data_lazy_base64() {
+        let blob = Blob::new(MimeType::ImagePng, b"hello".to_vec());
+        assert_eq!(blob.data.encoded_len(), 8);
+
+        let json = serde_json::to_value(&blob).unwrap();
+        assert_eq!(json["data"], "aGVsbG8=");
+
+        let received: Blob =
+            serde_json::from_str(r#"{"mimeType": "image/png", "data": "aGVsbG8="}"#).unwrap();
+        assert_eq!(received.data.to_base64(), "aGVsbG8=");
+        assert_eq!(&received.data.to_bytes().unwrap()[..], b"hello");
+
+        assert!(BlobData::from_base64("not base64!").to_bytes().is_err());
+    }
+
+    #[test]
+    fn test_file_data_from_api_response() {
+        let part: Part = serde_json::from_str(
+            r#"{"fileData": {"fileUri": "gs://bucket/clip.mp4", "mimeType": "video/mp4"}}"#,
+        )
+        .unwrap();
+        let file_data = part.file_data().unwrap();
+        assert_eq!(file_data.file_uri, "gs://bucket/clip.mp4");
+        assert_eq!(file_data.mime_type, Some(MimeType::VideoMp4));
+    }
+
+    #[test]
+    fn test_video_metadata_duration_format() {
+        use std::time::Duration;
+
+        let metadata = VideoMetadata::clip(Duration::from_millis(12_500)..Duration::from_secs(90))
+            .with_fps(2.0);
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"start_offset": "12.5s", "end_offset": "90s", "fps": 2.0})
+        );
+
+        let parsed: VideoMetadata =
+            serde_json::from_str(r#"{"startOffset": "0.000000001s", "endOffset": "3.25s"}"#)
+                .unwrap();
+        assert_eq!(parsed.start_offset, Some(Duration::from_nanos(1)));
+        assert_eq!(parsed.end_offset, Some(Duration::from_millis(3250)));
+        assert!(serde_json::from_str::<VideoMetadata>(r#"{"start_offset": "12"}"#).is_err());
+
+        let part: Part = Part::builder()
+            .video_metadata(VideoMetadata::fps(0.5))
+            .build()
+            .clipped(Duration::from_secs(10)..Duration::from_secs(20));
+        let metadata = part.video_metadata().unwrap();
+        assert_eq!(metadata.start_offset, Some(Duration::from_secs(10)));
+        assert_eq!(metadata.fps, Some(0.5));
+    }
+
+    #[test]
+    fn test_string_payload_is_verbatim() {
+        let part: Part = serde_json::from_str(r#"{"text": "{\"name\": \"Alice\"}"}"#).unwrap();
+        assert_eq!(part.text().unwrap(), r#"{"name": "Alice"}"#);
+
+        let json = serde_json::to_value(&part).unwrap();
+        assert_eq!(json["text"], r#"{"name": "Alice"}"#);
+    }
+
+    #[test]
+    fn test_part_keeps_raw_text() {
+        let raw = r#"{"text": "{\"name\":  \"Alice\"}"}"#;
+        let mut part: Part<serde_json::Value> = serde_json::from_str(raw).unwrap();
+        assert_eq!(part.raw_text(), Some(r#"{"name":  "Alice"}"#));
+
+        part.text_mut().unwrap()["name"] = "Bob".into();
+        assert_eq!(part.raw_text(), None);
+        let built: Part<serde_json::Value> =
+            Part::builder().text(JsonString::new(1.into())).build();
+        assert_eq!(built.raw_text(), None);
+    }
+
+    #[test]
+    fn test_untyped_json_payloads() {
+        let raw = r#"{"text": "{\"name\": \"Alice\", \"age\": 30}"}"#;
+
+        let part: Part<serde_json::Value> = serde_json::from_str(raw).unwrap();
+        assert_eq!(part.text().unwrap()["age"], 30);
+        let json = serde_json::to_value(&part).unwrap();
+        let text: serde_json::Value = serde_json::from_str(json["text"].as_str().unwrap()).unwrap();
+        assert_eq!(text, serde_json::json!({"name": "Alice", "age": 30}));
+
+        let part: Part<Box<serde_json::value::RawValue>> = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            part.text().unwrap().get(),
+            r#"{"name": "Alice", "age": 30}"#
+        );
+        let json = serde_json::to_value(&part).unwrap();
+        assert_eq!(json["text"], r#"{"name": "Alice", "age": 30}"#);
+
+        let invalid = r#"{"text": "not json"}"#;
+        assert!(serde_json::from_str::<Part<Box<serde_json::value::RawValue>>>(invalid).is_err());
+    }
+
+    #[test]
+    fn test_json_wrapper_derefs_to_inner() {
+        let part: Part<Json<TestSchema>> =
+            serde_json::from_str(r#"{"text": "{\"name\": \"Bob\", \"age\": 7}"}"#).unwrap();
+        let value = part.into_text().unwrap();
+        assert_eq!(value.name, "Bob");
+        assert_eq!(
+            value.into_inner(),
+            TestSchema {
+                name: "Bob".to_string(),
+                age: 7
+            }
+        );
+    }
+
+    #[test]
+    fn test_enum_payload() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        enum Priority {
+            Low,
+            High,
+        }
+
+        assert_eq!(Enum::<Priority>::variants(), Some(&["LOW", "HIGH"][..]));
+        assert_eq!(Enum::<String>::variants(), None);
+
+        #[derive(Debug, Serialize, Deserialize)]
+        enum Shape {
+            Point,
+            Circle(f64),
+            Rect { width: f64, height: f64 },
+        }
+        assert_eq!(Enum::<Shape>::variants(), None);
+
+        let part: Part<Enum<Priority>> = serde_json::from_str(r#"{"text": "HIGH\n"}"#).unwrap();
+        assert_eq!(part.into_text().map(Enum::into_inner), Some(Priority::High));
+
+        let part: Part<Enum<Priority>> = PartBuilder::new()
+            .text(JsonString::new(Enum(Priority::Low)))
+            .build();
+        assert_eq!(serde_json::to_value(&part).unwrap()["text"], "LOW");
+
+        assert!(serde_json::from_str::<Part<Enum<Priority>>>(r#"{"text": "MEDIUM"}"#).is_err());
+    }
 }
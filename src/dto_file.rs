@@ -0,0 +1,111 @@
+//! File DTOs for the Gemini Files API
+//!
+//! Media that is too large to send inline is uploaded once and then referenced
+//! from requests by URI through a `FileData` part. Uploaded files expire after
+//! 48 hours.
+
+use serde::{Deserialize, Serialize};
+
+use super::dto_content::{FileData, Part};
+use super::dto_request::MimeType;
+use super::operation::OperationStatus;
+
+/// A file uploaded to the Files API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct File {
+    /// Resource name of the file, e.g. `files/abc-123`
+    pub name: String,
+
+    /// Human-readable name given at upload
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+
+    /// MIME type of the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<MimeType>,
+
+    /// Size of the file in bytes, as a decimal string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<String>,
+
+    /// Creation timestamp in RFC 3339 format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<String>,
+
+    /// Expiration timestamp in RFC 3339 format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_time: Option<String>,
+
+    /// URI used to reference the file in requests
+    #[serde(default)]
+    pub uri: String,
+
+    /// Processing state of the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<FileState>,
+
+    /// Error details if processing failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<OperationStatus>,
+}
+
+/// Processing state of an uploaded file
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FileState {
+    /// Default value, unused
+    StateUnspecified,
+    /// The file is being processed and cannot be used yet
+    Processing,
+    /// The file is ready to be used
+    Active,
+    /// Processing failed
+    Failed,
+    /// A state not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl File {
+    /// Returns true while the file is still being processed
+    pub fn is_processing(&self) -> bool {
+        self.state == Some(FileState::Processing)
+    }
+
+    /// Creates a part that references this file by URI
+    pub fn to_part<T>(&self) -> Part<T> {
+        Part::builder()
+            .file_data(FileData {
+                mime_type: self.mime_type.clone(),
+                file_uri: self.uri.clone(),
+            })
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_deserialization_and_part() {
+        let json = r#"{
+            "name": "files/abc-123",
+            "displayName": "clip",
+            "mimeType": "video/mp4",
+            "sizeBytes": "1048576",
+            "uri": "https://generativelanguage.googleapis.com/v1beta/files/abc-123",
+            "state": "PROCESSING"
+        }"#;
+
+        let file: File = serde_json::from_str(json).unwrap();
+        assert!(file.is_processing());
+        assert_eq!(file.size_bytes.as_deref(), Some("1048576"));
+
+        let part: Part = file.to_part();
+        let file_data = part.file_data().unwrap();
+        assert_eq!(file_data.file_uri, file.uri);
+        assert_eq!(file_data.mime_type, Some(MimeType::VideoMp4));
+    }
+}
@@ -34,7 +34,7 @@
 //!     name: String,
 //! }
 //!
-//! let config = GenerationConfig::<Person>::builder()
+//! let config = GenerationConfig::<String>::builder()
 //!     .response_schema::<Person>()
 //!     .build()
 //!     .unwrap();
@@ -46,16 +46,20 @@
 //!     level: u32,
 //! }
 //!
-//! let config = GenerationConfig::<Character>::builder()
+//! let config = GenerationConfig::<String>::builder()
 //!     .response_json_schema::<Character>()
 //!     .build()
 //!     .unwrap();
 //! ```
 
 use serde::{Deserialize, Serialize};
-use std::any::TypeId;
 use std::marker::PhantomData;
 
+#[cfg(any(feature = "openapi", feature = "json"))]
+use crate::dto_content::Json;
+use crate::dto_content::{Enum, TextPayload};
+use crate::schema::{SchemaDialect, SchemaIssue, UnsupportedSchemaError, normalize_schema};
+
 pub use crate::dto_content::{
     Blob, CodeExecutionResult, Content, ExecutableCode, FileData, FunctionCall, FunctionResponse,
     Part, VideoMetadata,
@@ -66,6 +70,8 @@ pub use crate::dto_content::{
 pub enum BuildError {
     /// A schema must be provided when using typed responses (non-String types)
     SchemaRequiredForTypedResponse,
+    /// The derived schema uses structures the API cannot express
+    UnsupportedSchema(UnsupportedSchemaError),
 }
 
 impl std::fmt::Display for BuildError {
@@ -78,6 +84,7 @@ impl std::fmt::Display for BuildError {
                      Use .response_schema() or .response_json_schema() to specify the expected structure."
                 )
             }
+            BuildError::UnsupportedSchema(error) => write!(f, "{}", error),
         }
     }
 }
@@ -101,7 +108,10 @@ pub enum ResponseMimeType {
 }
 
 /// MIME type for media content supported by Gemini API
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+///
+/// Parsing and deserialization ignore case and map known types to their variant,
+/// so `"Image/PNG"` becomes `MimeType::ImagePng` rather than `MimeType::Other`.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum MimeType {
     // Image formats
@@ -185,12 +195,171 @@ pub enum MimeType {
     ApplicationRtf,
     #[serde(rename = "text/rtf")]
     TextRtf,
+
+    /// A MIME type without a dedicated variant, e.g. `image/gif`
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl MimeType {
+    /// Returns the MIME type string, e.g. `image/png`
+    pub fn as_str(&self) -> &str {
+        match self {
+            MimeType::ImagePng => "image/png",
+            MimeType::ImageJpeg => "image/jpeg",
+            MimeType::ImageWebp => "image/webp",
+            MimeType::ImageHeic => "image/heic",
+            MimeType::ImageHeif => "image/heif",
+            MimeType::AudioWav => "audio/wav",
+            MimeType::AudioMp3 => "audio/mp3",
+            MimeType::AudioMpeg => "audio/mpeg",
+            MimeType::AudioAiff => "audio/aiff",
+            MimeType::AudioAac => "audio/aac",
+            MimeType::AudioOgg => "audio/ogg",
+            MimeType::AudioFlac => "audio/flac",
+            MimeType::VideoMp4 => "video/mp4",
+            MimeType::VideoMpeg => "video/mpeg",
+            MimeType::VideoMov => "video/mov",
+            MimeType::VideoAvi => "video/avi",
+            MimeType::VideoFlv => "video/x-flv",
+            MimeType::VideoMpg => "video/mpg",
+            MimeType::VideoWebm => "video/webm",
+            MimeType::VideoWmv => "video/wmv",
+            MimeType::Video3gpp => "video/3gpp",
+            MimeType::ApplicationPdf => "application/pdf",
+            MimeType::TextPlain => "text/plain",
+            MimeType::TextHtml => "text/html",
+            MimeType::TextCss => "text/css",
+            MimeType::TextJavascript => "text/javascript",
+            MimeType::ApplicationJavascript => "application/x-javascript",
+            MimeType::TextTypescript => "text/x-typescript",
+            MimeType::ApplicationTypescript => "application/x-typescript",
+            MimeType::TextCsv => "text/csv",
+            MimeType::TextMarkdown => "text/markdown",
+            MimeType::TextPython => "text/x-python",
+            MimeType::ApplicationPythonCode => "application/x-python-code",
+            MimeType::ApplicationJson => "application/json",
+            MimeType::TextXml => "text/xml",
+            MimeType::ApplicationRtf => "application/rtf",
+            MimeType::TextRtf => "text/rtf",
+            MimeType::Other(mime_type) => mime_type,
+        }
+    }
+}
+
+impl std::fmt::Display for MimeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error returned when a string is not a `type/subtype` MIME type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMimeTypeError {
+    /// The string that failed to parse
+    pub input: String,
+}
+
+impl std::fmt::Display for ParseMimeTypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid MIME type: {:?}", self.input)
+    }
+}
+
+impl std::error::Error for ParseMimeTypeError {}
+
+/// Every MIME type with a dedicated variant
+const KNOWN_MIME_TYPES: &[MimeType] = &[
+    MimeType::ImagePng,
+    MimeType::ImageJpeg,
+    MimeType::ImageWebp,
+    MimeType::ImageHeic,
+    MimeType::ImageHeif,
+    MimeType::AudioWav,
+    MimeType::AudioMp3,
+    MimeType::AudioMpeg,
+    MimeType::AudioAiff,
+    MimeType::AudioAac,
+    MimeType::AudioOgg,
+    MimeType::AudioFlac,
+    MimeType::VideoMp4,
+    MimeType::VideoMpeg,
+    MimeType::VideoMov,
+    MimeType::VideoAvi,
+    MimeType::VideoFlv,
+    MimeType::VideoMpg,
+    MimeType::VideoWebm,
+    MimeType::VideoWmv,
+    MimeType::Video3gpp,
+    MimeType::ApplicationPdf,
+    MimeType::TextPlain,
+    MimeType::TextHtml,
+    MimeType::TextCss,
+    MimeType::TextJavascript,
+    MimeType::ApplicationJavascript,
+    MimeType::TextTypescript,
+    MimeType::ApplicationTypescript,
+    MimeType::TextCsv,
+    MimeType::TextMarkdown,
+    MimeType::TextPython,
+    MimeType::ApplicationPythonCode,
+    MimeType::ApplicationJson,
+    MimeType::TextXml,
+    MimeType::ApplicationRtf,
+    MimeType::TextRtf,
+];
+
+impl std::str::FromStr for MimeType {
+    type Err = ParseMimeTypeError;
+
+    /// Parses a MIME type, ignoring case
+    ///
+    /// Known types map to their variant and drop parameters such as
+    /// `; charset=utf-8`. Other types become `MimeType::Other` with a lowercase
+    /// type and subtype, keeping their parameters.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (essence, parameters) = match s.split_once(';') {
+            Some((essence, parameters)) => (essence.trim(), Some(parameters)),
+            None => (s, None),
+        };
+        let is_valid = essence.split_once('/').is_some_and(|(kind, subtype)| {
+            !kind.is_empty() && !subtype.is_empty() && !subtype.contains('/')
+        });
+        if !is_valid || essence.contains(char::is_whitespace) {
+            return Err(ParseMimeTypeError {
+                input: s.to_string(),
+            });
+        }
+
+        let essence = essence.to_ascii_lowercase();
+        if let Some(known) = KNOWN_MIME_TYPES.iter().find(|m| m.as_str() == essence) {
+            return Ok(known.clone());
+        }
+
+        Ok(MimeType::Other(match parameters {
+            Some(parameters) => format!("{essence};{parameters}"),
+            None => essence,
+        }))
+    }
+}
+
+impl<'de> Deserialize<'de> for MimeType {
+    /// Deserializes like `FromStr`, keeping malformed strings verbatim as `MimeType::Other`
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        Ok(raw.parse().unwrap_or(MimeType::Other(raw)))
+    }
 }
 
 /// Request body for generateContent API call
 ///
 /// Generic over the expected response type `T`, which defaults to `String`.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct GenerateContentRequest<T = String> {
     /// The content of the current conversation with the model
     contents: Vec<Content>,
@@ -206,6 +375,14 @@ pub struct GenerateContentRequest<T = String> {
     /// Optional safety settings
     #[serde(skip_serializing_if = "Option::is_none")]
     safety_settings: Option<Vec<SafetySetting>>,
+
+    /// Optional tools the model may use
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<Tool>>,
+
+    /// Optional cached content to use as context, e.g. `cachedContents/abc123`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cached_content: Option<String>,
 }
 
 impl<T> GenerateContentRequest<T> {
@@ -233,6 +410,33 @@ impl<T> GenerateContentRequest<T> {
     pub fn safety_settings(&self) -> Option<&[SafetySetting]> {
         self.safety_settings.as_deref()
     }
+
+    /// Get a reference to the tools
+    pub fn tools(&self) -> Option<&[Tool]> {
+        self.tools.as_deref()
+    }
+
+    /// Get the name of the cached content
+    pub fn cached_content(&self) -> Option<&str> {
+        self.cached_content.as_deref()
+    }
+
+    /// Appends content to the conversation
+    pub(crate) fn push_content(&mut self, content: Content) {
+        self.contents.push(content);
+    }
+
+    /// Converts into a request whose response text is kept as a raw `String`
+    pub(crate) fn into_untyped(self) -> GenerateContentRequest<String> {
+        GenerateContentRequest {
+            contents: self.contents,
+            generation_config: self.generation_config.map(GenerationConfig::retyped),
+            system_instruction: self.system_instruction,
+            safety_settings: self.safety_settings,
+            tools: self.tools,
+            cached_content: self.cached_content,
+        }
+    }
 }
 
 /// Builder for constructing `GenerateContentRequest<T>` instances
@@ -244,6 +448,8 @@ pub struct GenerateContentRequestBuilder<T = String> {
     generation_config: Option<GenerationConfig<T>>,
     system_instruction: Option<Content>,
     safety_settings: Option<Vec<SafetySetting>>,
+    tools: Option<Vec<Tool>>,
+    cached_content: Option<String>,
 }
 
 impl<T> Default for GenerateContentRequestBuilder<T> {
@@ -253,6 +459,8 @@ impl<T> Default for GenerateContentRequestBuilder<T> {
             generation_config: None,
             system_instruction: None,
             safety_settings: None,
+            tools: None,
+            cached_content: None,
         }
     }
 }
@@ -307,6 +515,30 @@ impl<T> GenerateContentRequestBuilder<T> {
         self
     }
 
+    /// Sets the tools the model may use
+    #[must_use]
+    pub fn tools(mut self, tools: Vec<Tool>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// Adds a single tool
+    #[must_use]
+    pub fn add_tool(mut self, tool: Tool) -> Self {
+        self.tools.get_or_insert_with(Vec::new).push(tool);
+        self
+    }
+
+    /// Sets the cached content to use as context, e.g. `cachedContents/abc123`
+    ///
+    /// The API rejects requests that combine cached content with a system
+    /// instruction or tools; those must be part of the cache instead.
+    #[must_use]
+    pub fn cached_content(mut self, name: impl Into<String>) -> Self {
+        self.cached_content = Some(name.into());
+        self
+    }
+
     /// Constructs the `GenerateContentRequest<T>` instance from the builder
     pub fn build(self) -> GenerateContentRequest<T> {
         GenerateContentRequest {
@@ -314,6 +546,8 @@ impl<T> GenerateContentRequestBuilder<T> {
             generation_config: self.generation_config,
             system_instruction: self.system_instruction,
             safety_settings: self.safety_settings,
+            tools: self.tools,
+            cached_content: self.cached_content,
         }
     }
 }
@@ -340,9 +574,9 @@ pub struct GenerationConfig<T = String> {
     pub response_mime_type: Option<ResponseMimeType>,
 
     /// Output schema of the generated candidate text (OpenAPI schema subset)
+    /// Available without features so `enum_response` can set the variant list.
     /// Note: Mutually exclusive with response_json_schema. Only one should be set.
     /// Use the builder methods to ensure proper mutual exclusivity.
-    #[cfg(feature = "openapi")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_schema: Option<serde_json::Value>,
 
@@ -356,7 +590,7 @@ pub struct GenerationConfig<T = String> {
 
     /// The requested modalities of the response
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub response_modalities: Option<Vec<String>>,
+    pub response_modalities: Option<Vec<ResponseModality>>,
 
     /// Number of generated responses to return
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -404,19 +638,19 @@ pub struct GenerationConfig<T = String> {
 
     /// Speech generation config
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub speech_config: Option<serde_json::Value>,
+    pub speech_config: Option<SpeechConfig>,
 
     /// Config for thinking features
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub thinking_config: Option<serde_json::Value>,
+    pub thinking_config: Option<ThinkingConfig>,
 
     /// Config for image generation
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub image_config: Option<serde_json::Value>,
+    pub image_config: Option<ImageConfig>,
 
     /// Media resolution for input media
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub media_resolution: Option<String>,
+    pub media_resolution: Option<MediaResolution>,
 }
 
 impl<T> GenerationConfig<T> {
@@ -424,6 +658,94 @@ impl<T> GenerationConfig<T> {
     pub fn builder() -> GenerationConfigBuilder<T> {
         GenerationConfigBuilder::default()
     }
+
+    /// Returns the response schema that is set, with the dialect it is written in
+    pub fn schema(&self) -> Option<(&serde_json::Value, SchemaDialect)> {
+        #[cfg(feature = "json")]
+        if let Some(schema) = &self.response_json_schema {
+            return Some((schema, SchemaDialect::JsonSchema));
+        }
+        if let Some(schema) = &self.response_schema {
+            return Some((schema, SchemaDialect::OpenApi));
+        }
+        None
+    }
+
+    /// Fills every option not set in this config from `defaults`
+    ///
+    /// The response format is taken as a whole: the MIME type and schemas of `defaults`
+    /// only apply if this config sets none of them, so a per-message schema never mixes
+    /// with a default one.
+    pub fn with_defaults<U>(mut self, defaults: &GenerationConfig<U>) -> Self {
+        let has_format = self.response_mime_type.is_some() || self.schema().is_some();
+        if !has_format {
+            self.response_mime_type = defaults.response_mime_type.clone();
+            self.response_schema = defaults.response_schema.clone();
+            #[cfg(feature = "json")]
+            {
+                self.response_json_schema = defaults.response_json_schema.clone();
+            }
+        }
+
+        self.stop_sequences = self
+            .stop_sequences
+            .or_else(|| defaults.stop_sequences.clone());
+        self.response_modalities = self
+            .response_modalities
+            .or_else(|| defaults.response_modalities.clone());
+        self.candidate_count = self.candidate_count.or(defaults.candidate_count);
+        self.max_output_tokens = self.max_output_tokens.or(defaults.max_output_tokens);
+        self.temperature = self.temperature.or(defaults.temperature);
+        self.top_p = self.top_p.or(defaults.top_p);
+        self.top_k = self.top_k.or(defaults.top_k);
+        self.seed = self.seed.or(defaults.seed);
+        self.presence_penalty = self.presence_penalty.or(defaults.presence_penalty);
+        self.frequency_penalty = self.frequency_penalty.or(defaults.frequency_penalty);
+        self.response_logprobs = self.response_logprobs.or(defaults.response_logprobs);
+        self.logprobs = self.logprobs.or(defaults.logprobs);
+        self.enable_enhanced_civic_answers = self
+            .enable_enhanced_civic_answers
+            .or(defaults.enable_enhanced_civic_answers);
+        self.speech_config = self
+            .speech_config
+            .or_else(|| defaults.speech_config.clone());
+        self.thinking_config = self
+            .thinking_config
+            .or_else(|| defaults.thinking_config.clone());
+        self.image_config = self.image_config.or_else(|| defaults.image_config.clone());
+        self.media_resolution = self
+            .media_resolution
+            .or_else(|| defaults.media_resolution.clone());
+        self
+    }
+
+    /// Converts into a config for a different response type, keeping every option
+    pub(crate) fn retyped<U>(self) -> GenerationConfig<U> {
+        GenerationConfig {
+            _response_type: PhantomData,
+            stop_sequences: self.stop_sequences,
+            response_mime_type: self.response_mime_type,
+            response_schema: self.response_schema,
+            #[cfg(feature = "json")]
+            response_json_schema: self.response_json_schema,
+            response_modalities: self.response_modalities,
+            candidate_count: self.candidate_count,
+            max_output_tokens: self.max_output_tokens,
+            temperature: self.temperature,
+            top_p: self.top_p,
+            top_k: self.top_k,
+            seed: self.seed,
+            presence_penalty: self.presence_penalty,
+            frequency_penalty: self.frequency_penalty,
+            response_logprobs: self.response_logprobs,
+            logprobs: self.logprobs,
+            enable_enhanced_civic_answers: self.enable_enhanced_civic_answers,
+            speech_config: self.speech_config,
+            thinking_config: self.thinking_config,
+            image_config: self.image_config,
+            media_resolution: self.media_resolution,
+        }
+    }
 }
 
 impl<T> Default for GenerationConfig<T> {
@@ -432,7 +754,6 @@ impl<T> Default for GenerationConfig<T> {
             _response_type: PhantomData,
             stop_sequences: None,
             response_mime_type: None,
-            #[cfg(feature = "openapi")]
             response_schema: None,
             #[cfg(feature = "json")]
             response_json_schema: None,
@@ -464,11 +785,10 @@ pub struct GenerationConfigBuilder<T = String> {
     _response_type: PhantomData<T>,
     stop_sequences: Option<Vec<String>>,
     response_mime_type: Option<ResponseMimeType>,
-    #[cfg(feature = "openapi")]
     response_schema: Option<serde_json::Value>,
     #[cfg(feature = "json")]
     response_json_schema: Option<serde_json::Value>,
-    response_modalities: Option<Vec<String>>,
+    response_modalities: Option<Vec<ResponseModality>>,
     candidate_count: Option<i32>,
     max_output_tokens: Option<i32>,
     temperature: Option<f32>,
@@ -480,10 +800,11 @@ pub struct GenerationConfigBuilder<T = String> {
     response_logprobs: Option<bool>,
     logprobs: Option<i32>,
     enable_enhanced_civic_answers: Option<bool>,
-    speech_config: Option<serde_json::Value>,
-    thinking_config: Option<serde_json::Value>,
-    image_config: Option<serde_json::Value>,
-    media_resolution: Option<String>,
+    speech_config: Option<SpeechConfig>,
+    thinking_config: Option<ThinkingConfig>,
+    image_config: Option<ImageConfig>,
+    media_resolution: Option<MediaResolution>,
+    schema_error: Option<UnsupportedSchemaError>,
 }
 
 impl<T> Default for GenerationConfigBuilder<T> {
@@ -492,7 +813,6 @@ impl<T> Default for GenerationConfigBuilder<T> {
             _response_type: PhantomData,
             stop_sequences: None,
             response_mime_type: None,
-            #[cfg(feature = "openapi")]
             response_schema: None,
             #[cfg(feature = "json")]
             response_json_schema: None,
@@ -512,10 +832,28 @@ impl<T> Default for GenerationConfigBuilder<T> {
             thinking_config: None,
             image_config: None,
             media_resolution: None,
+            schema_error: None,
         }
     }
 }
 
+/// Normalizes a response schema, logging removed keywords against `source`
+fn normalize_response_schema(
+    schema: serde_json::Value,
+    dialect: SchemaDialect,
+    source: &str,
+) -> (Option<serde_json::Value>, Option<UnsupportedSchemaError>) {
+    match normalize_schema(&schema, dialect) {
+        Ok(normalized) => {
+            for warning in &normalized.warnings {
+                tracing::warn!("Schema for {}: {}", source, warning);
+            }
+            (Some(normalized.schema), None)
+        }
+        Err(error) => (None, Some(error)),
+    }
+}
+
 impl<T> GenerationConfigBuilder<T> {
     /// Creates a new builder with default values
     pub fn new() -> Self {
@@ -541,20 +879,44 @@ impl<T> GenerationConfigBuilder<T> {
     /// Automatically sets response_mime_type to ApplicationJson
     /// Note: This is mutually exclusive with response_json_schema.
     ///
-    /// Use turbofish to specify response type: `.response_schema::<MyType>()`
+    /// Use turbofish to specify response type: `.response_schema::<MyType>()`.
+    /// Responses are then typed as `Json<MyType>`, which dereferences to `MyType`.
     #[cfg(feature = "openapi")]
-    pub fn response_schema<R>(self) -> GenerationConfigBuilder<R>
+    pub fn response_schema<R>(self) -> GenerationConfigBuilder<Json<R>>
     where
         R: serde::de::DeserializeOwned + serde::Serialize + 'static + utoipa::ToSchema,
     {
-        let schema = R::schema();
-        let schema_value =
-            serde_json::to_value(schema).expect("Failed to serialize OpenAPI schema");
+        let mut schema_value =
+            serde_json::to_value(R::schema()).expect("Failed to serialize OpenAPI schema");
+
+        // Referenced types are not part of the root schema, so expose them where `$ref`s point
+        let mut components = Vec::new();
+        R::schemas(&mut components);
+        if !components.is_empty()
+            && let serde_json::Value::Object(root) = &mut schema_value
+        {
+            let schemas: serde_json::Map<_, _> = components
+                .into_iter()
+                .map(|(name, schema)| {
+                    let schema =
+                        serde_json::to_value(schema).expect("Failed to serialize OpenAPI schema");
+                    (name, schema)
+                })
+                .collect();
+            root.insert(
+                "components".to_string(),
+                serde_json::json!({ "schemas": schemas }),
+            );
+        }
+        let (schema_value, schema_error) = normalize_response_schema(
+            schema_value,
+            SchemaDialect::OpenApi,
+            std::any::type_name::<R>(),
+        );
 
         GenerationConfigBuilder {
             _response_type: PhantomData,
-            #[cfg(feature = "openapi")]
-            response_schema: Some(schema_value),
+            response_schema: schema_value,
             #[cfg(feature = "json")]
             response_json_schema: None,
             response_mime_type: Some(ResponseMimeType::ApplicationJson),
@@ -575,6 +937,7 @@ impl<T> GenerationConfigBuilder<T> {
             thinking_config: self.thinking_config,
             image_config: self.image_config,
             media_resolution: self.media_resolution,
+            schema_error,
         }
     }
 
@@ -583,20 +946,67 @@ impl<T> GenerationConfigBuilder<T> {
     /// Automatically sets response_mime_type to ApplicationJson
     /// Note: This is mutually exclusive with response_schema.
     ///
-    /// Use turbofish to specify response type: `.response_json_schema::<MyType>()`
+    /// Use turbofish to specify response type: `.response_json_schema::<MyType>()`.
+    /// Responses are then typed as `Json<MyType>`, which dereferences to `MyType`.
     #[cfg(feature = "json")]
-    pub fn response_json_schema<R>(self) -> GenerationConfigBuilder<R>
+    pub fn response_json_schema<R>(self) -> GenerationConfigBuilder<Json<R>>
     where
         R: serde::de::DeserializeOwned + serde::Serialize + 'static + schemars::JsonSchema,
     {
         let schema = schemars::schema_for!(R);
         let schema_value = serde_json::to_value(schema).expect("Failed to serialize JSON schema");
+        let (schema_value, schema_error) = normalize_response_schema(
+            schema_value,
+            SchemaDialect::JsonSchema,
+            std::any::type_name::<R>(),
+        );
+
+        GenerationConfigBuilder {
+            _response_type: PhantomData,
+            #[cfg(feature = "json")]
+            response_json_schema: schema_value,
+            response_schema: None,
+            response_mime_type: Some(ResponseMimeType::ApplicationJson),
+            stop_sequences: self.stop_sequences,
+            response_modalities: self.response_modalities,
+            candidate_count: self.candidate_count,
+            max_output_tokens: self.max_output_tokens,
+            temperature: self.temperature,
+            top_p: self.top_p,
+            top_k: self.top_k,
+            seed: self.seed,
+            presence_penalty: self.presence_penalty,
+            frequency_penalty: self.frequency_penalty,
+            response_logprobs: self.response_logprobs,
+            logprobs: self.logprobs,
+            enable_enhanced_civic_answers: self.enable_enhanced_civic_answers,
+            speech_config: self.speech_config,
+            thinking_config: self.thinking_config,
+            image_config: self.image_config,
+            media_resolution: self.media_resolution,
+            schema_error,
+        }
+    }
+
+    /// Sets a response JSON schema only known at runtime
+    /// Automatically sets response_mime_type to ApplicationJson
+    /// Note: This is mutually exclusive with response_schema.
+    ///
+    /// Accepts a [`Schema`](crate::schema::Schema) or a raw `serde_json::Value`. The schema is
+    /// normalized like derived ones, and `build()` fails if it cannot be expressed.
+    /// Responses are typed as `serde_json::Value`.
+    #[cfg(feature = "json")]
+    pub fn response_json_schema_value(
+        self,
+        schema: impl Into<serde_json::Value>,
+    ) -> GenerationConfigBuilder<serde_json::Value> {
+        let (schema_value, schema_error) =
+            normalize_response_schema(schema.into(), SchemaDialect::JsonSchema, "runtime schema");
 
         GenerationConfigBuilder {
             _response_type: PhantomData,
             #[cfg(feature = "json")]
-            response_json_schema: Some(schema_value),
-            #[cfg(feature = "openapi")]
+            response_json_schema: schema_value,
             response_schema: None,
             response_mime_type: Some(ResponseMimeType::ApplicationJson),
             stop_sequences: self.stop_sequences,
@@ -616,20 +1026,21 @@ impl<T> GenerationConfigBuilder<T> {
             thinking_config: self.thinking_config,
             image_config: self.image_config,
             media_resolution: self.media_resolution,
+            schema_error,
         }
     }
 
     /// Sets the response modalities
-    pub fn response_modalities(mut self, modalities: Vec<String>) -> Self {
+    pub fn response_modalities(mut self, modalities: Vec<ResponseModality>) -> Self {
         self.response_modalities = Some(modalities);
         self
     }
 
     /// Adds a single response modality
-    pub fn add_response_modality(mut self, modality: impl Into<String>) -> Self {
+    pub fn add_response_modality(mut self, modality: ResponseModality) -> Self {
         self.response_modalities
             .get_or_insert_with(Vec::new)
-            .push(modality.into());
+            .push(modality);
         self
     }
 
@@ -700,26 +1111,26 @@ impl<T> GenerationConfigBuilder<T> {
     }
 
     /// Sets the speech generation config
-    pub fn speech_config(mut self, config: serde_json::Value) -> Self {
+    pub fn speech_config(mut self, config: SpeechConfig) -> Self {
         self.speech_config = Some(config);
         self
     }
 
     /// Sets the thinking config
-    pub fn thinking_config(mut self, config: serde_json::Value) -> Self {
+    pub fn thinking_config(mut self, config: ThinkingConfig) -> Self {
         self.thinking_config = Some(config);
         self
     }
 
     /// Sets the image generation config
-    pub fn image_config(mut self, config: serde_json::Value) -> Self {
+    pub fn image_config(mut self, config: ImageConfig) -> Self {
         self.image_config = Some(config);
         self
     }
 
     /// Sets the media resolution
-    pub fn media_resolution(mut self, resolution: impl Into<String>) -> Self {
-        self.media_resolution = Some(resolution.into());
+    pub fn media_resolution(mut self, resolution: MediaResolution) -> Self {
+        self.media_resolution = Some(resolution);
         self
     }
 
@@ -729,10 +1140,70 @@ impl<T> GenerationConfigBuilder<T> {
         self
     }
 
-    /// Configures for enum response
-    pub fn enum_response(mut self) -> Self {
-        self.response_mime_type = Some(ResponseMimeType::TextEnum);
-        self
+    /// Configures a classification response choosing one variant of `E`
+    /// Automatically sets response_mime_type to TextEnum and the schema to the variant names.
+    ///
+    /// `E` must be an enum of unit variants deriving `Serialize` and `Deserialize`; the
+    /// returned text is parsed back into `E`. The schema is sent as `responseSchema`, so no
+    /// schema feature is needed. `build()` fails if `E` is not an enum.
+    ///
+    /// ```
+    /// # use gemini::dto_request::GenerationConfig;
+    /// #[derive(serde::Serialize, serde::Deserialize)]
+    /// #[serde(rename_all = "lowercase")]
+    /// enum Sentiment { Positive, Neutral, Negative }
+    ///
+    /// let config = GenerationConfig::<String>::builder().enum_response::<Sentiment>().build().unwrap();
+    /// ```
+    pub fn enum_response<E>(self) -> GenerationConfigBuilder<Enum<E>>
+    where
+        E: Serialize + serde::de::DeserializeOwned,
+    {
+        let (schema_value, schema_error) = match Enum::<E>::variants() {
+            Some(variants) => normalize_response_schema(
+                serde_json::json!({ "type": "string", "enum": variants }),
+                SchemaDialect::OpenApi,
+                std::any::type_name::<E>(),
+            ),
+            None => (
+                None,
+                Some(UnsupportedSchemaError {
+                    issues: vec![SchemaIssue {
+                        path: "#".to_string(),
+                        message: format!(
+                            "`{}` is not an enum of unit variants",
+                            std::any::type_name::<E>()
+                        ),
+                    }],
+                }),
+            ),
+        };
+
+        GenerationConfigBuilder {
+            _response_type: PhantomData,
+            response_schema: schema_value,
+            #[cfg(feature = "json")]
+            response_json_schema: None,
+            response_mime_type: Some(ResponseMimeType::TextEnum),
+            stop_sequences: self.stop_sequences,
+            response_modalities: self.response_modalities,
+            candidate_count: self.candidate_count,
+            max_output_tokens: self.max_output_tokens,
+            temperature: self.temperature,
+            top_p: self.top_p,
+            top_k: self.top_k,
+            seed: self.seed,
+            presence_penalty: self.presence_penalty,
+            frequency_penalty: self.frequency_penalty,
+            response_logprobs: self.response_logprobs,
+            logprobs: self.logprobs,
+            enable_enhanced_civic_answers: self.enable_enhanced_civic_answers,
+            speech_config: self.speech_config,
+            thinking_config: self.thinking_config,
+            image_config: self.image_config,
+            media_resolution: self.media_resolution,
+            schema_error,
+        }
     }
 
     /// Builds the GenerationConfig
@@ -746,9 +1217,12 @@ impl<T> GenerationConfigBuilder<T> {
     /// # Errors
     ///
     /// Returns `BuildError::SchemaRequiredForTypedResponse` if:
-    /// - `T` is not `String` (i.e., using typed responses)
+    /// - `T` is a typed response such as `Json<MyType>`
     /// - AND no schema is provided (neither `response_schema` nor `response_json_schema`)
     ///
+    /// Returns `BuildError::UnsupportedSchema` if the schema derived by `response_schema` or
+    /// `response_json_schema` uses structures the API cannot express.
+    ///
     /// # Examples
     ///
     /// ```
@@ -762,44 +1236,37 @@ impl<T> GenerationConfigBuilder<T> {
     ///
     /// ```should_panic
     /// # use gemini::dto_request::GenerationConfig;
+    /// # use gemini::Json;
     /// # use serde::{Deserialize, Serialize};
     /// # #[derive(Deserialize, Serialize)]
     /// # struct MyType { value: String }
     /// // Typed response without schema - will fail
-    /// let config = GenerationConfig::<MyType>::builder()
+    /// let config = GenerationConfig::<Json<MyType>>::builder()
     ///     .temperature(0.7)
     ///     .build()
     ///     .unwrap(); // This will panic!
     /// ```
     pub fn build(self) -> Result<GenerationConfig<T>, BuildError>
     where
-        T: 'static,
+        T: TextPayload,
     {
-        // Validate that typed responses have a schema configured
-        let is_string_type = TypeId::of::<T>() == TypeId::of::<String>();
+        if let Some(error) = self.schema_error {
+            return Err(BuildError::UnsupportedSchema(error));
+        }
 
-        if !is_string_type {
+        // Validate that typed responses have a schema configured
+        if T::REQUIRES_SCHEMA {
             // Check if at least one schema is set (considering feature flags)
             let has_schema = {
-                #[cfg(all(feature = "openapi", feature = "json"))]
+                #[cfg(feature = "json")]
                 {
                     self.response_schema.is_some() || self.response_json_schema.is_some()
                 }
 
-                #[cfg(all(feature = "openapi", not(feature = "json")))]
+                #[cfg(not(feature = "json"))]
                 {
                     self.response_schema.is_some()
                 }
-
-                #[cfg(all(not(feature = "openapi"), feature = "json"))]
-                {
-                    self.response_json_schema.is_some()
-                }
-
-                #[cfg(not(any(feature = "openapi", feature = "json")))]
-                {
-                    false
-                }
             };
 
             if !has_schema {
@@ -811,7 +1278,6 @@ impl<T> GenerationConfigBuilder<T> {
             _response_type: PhantomData,
             stop_sequences: self.stop_sequences,
             response_mime_type: self.response_mime_type,
-            #[cfg(feature = "openapi")]
             response_schema: self.response_schema,
             #[cfg(feature = "json")]
             response_json_schema: self.response_json_schema,
@@ -835,70 +1301,557 @@ impl<T> GenerationConfigBuilder<T> {
     }
 }
 
-/// Safety setting for content filtering
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SafetySetting {
-    /// Safety category
-    pub category: String,
+/// A tool the model may use to generate a response
+///
+/// Each tool enables one built-in capability; use the constructors rather than
+/// setting several fields on the same value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tool {
+    /// Grounds answers in Google Search results
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub google_search: Option<GoogleSearch>,
 
-    /// Harm block threshold
-    pub threshold: String,
+    /// Lets the model read URLs mentioned in the prompt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url_context: Option<UrlContext>,
+
+    /// Lets the model generate and run Python code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_execution: Option<CodeExecution>,
 }
 
-/// Safety rating for content
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SafetyRating {
-    /// Harm category
-    pub category: String,
+impl Tool {
+    /// Creates the Google Search grounding tool
+    pub fn google_search() -> Self {
+        Self {
+            google_search: Some(GoogleSearch::default()),
+            ..Self::default()
+        }
+    }
 
-    /// Probability of harm
-    pub probability: String,
+    /// Creates the URL context tool
+    pub fn url_context() -> Self {
+        Self {
+            url_context: Some(UrlContext::default()),
+            ..Self::default()
+        }
+    }
 
-    /// Whether content was blocked
-    #[serde(default)]
-    pub blocked: bool,
+    /// Creates the code execution tool
+    pub fn code_execution() -> Self {
+        Self {
+            code_execution: Some(CodeExecution::default()),
+            ..Self::default()
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Google Search grounding tool, configured without options
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GoogleSearch {}
 
-    #[test]
-    fn test_generation_config_builder_basic() {
-        let config: GenerationConfig = GenerationConfig::builder()
-            .temperature(0.7)
-            .max_output_tokens(1024)
-            .build()
-            .unwrap();
+/// URL context tool, configured without options
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UrlContext {}
 
-        assert_eq!(config.temperature, Some(0.7));
-        assert_eq!(config.max_output_tokens, Some(1024));
-        assert_eq!(config.response_mime_type, None);
+/// Code execution tool, configured without options
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CodeExecution {}
+
+/// Config for thinking features
+///
+/// Gemini 2.5 models are controlled with a token budget, Gemini 3 models with a
+/// thinking level. Setting both in one request is rejected by the API.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThinkingConfig {
+    /// Whether thought summaries are included in the response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_thoughts: Option<bool>,
+
+    /// Number of thinking tokens the model may use; 0 disables thinking, -1 lets the model decide
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<i32>,
+
+    /// Relative amount of thinking
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking_level: Option<ThinkingLevel>,
+}
+
+impl ThinkingConfig {
+    /// Creates an empty config that uses the model defaults
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    #[cfg(feature = "openapi")]
-    #[test]
-    fn test_generation_config_builder_json_response() {
-        #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
-        struct TestType {
-            name: String,
+    /// Creates a config with a fixed thinking token budget
+    pub fn budget(tokens: i32) -> Self {
+        Self {
+            thinking_budget: Some(tokens),
+            ..Self::default()
         }
+    }
 
-        let config: GenerationConfig<TestType> = GenerationConfig::<TestType>::builder()
-            .response_schema::<TestType>()
-            .temperature(0.5)
-            .build()
-            .unwrap();
+    /// Creates a config that lets the model choose its thinking budget
+    pub fn dynamic() -> Self {
+        Self::budget(-1)
+    }
 
-        assert_eq!(
-            config.response_mime_type,
-            Some(ResponseMimeType::ApplicationJson)
-        );
-        assert!(config.response_schema.is_some());
-        assert_eq!(config.temperature, Some(0.5));
+    /// Creates a config that turns thinking off, where the model supports it
+    pub fn disabled() -> Self {
+        Self::budget(0)
     }
 
-    #[test]
+    /// Creates a config with a thinking level
+    pub fn level(level: ThinkingLevel) -> Self {
+        Self {
+            thinking_level: Some(level),
+            ..Self::default()
+        }
+    }
+
+    /// Sets whether thought summaries are included in the response
+    #[must_use]
+    pub fn with_include_thoughts(mut self, include: bool) -> Self {
+        self.include_thoughts = Some(include);
+        self
+    }
+}
+
+/// Modality the model may respond with
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ResponseModality {
+    /// Text output
+    Text,
+    /// Generated images, returned as inline data parts
+    Image,
+    /// Generated audio, returned as inline data parts
+    Audio,
+    /// A modality not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Resolution at which input images and video frames are processed
+///
+/// Lower resolutions use fewer tokens per image or frame, which allows longer videos
+/// within the context window.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MediaResolution {
+    #[serde(rename = "MEDIA_RESOLUTION_UNSPECIFIED")]
+    Unspecified,
+    /// 64 tokens per image or frame
+    #[serde(rename = "MEDIA_RESOLUTION_LOW")]
+    Low,
+    /// 256 tokens per image or frame
+    #[serde(rename = "MEDIA_RESOLUTION_MEDIUM")]
+    Medium,
+    /// Zoomed reframing with 256 tokens per frame
+    #[serde(rename = "MEDIA_RESOLUTION_HIGH")]
+    High,
+    /// A resolution not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Config for speech generation
+///
+/// Set either a single voice or a multi-speaker config, not both.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechConfig {
+    /// Voice used for single-speaker output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice_config: Option<VoiceConfig>,
+
+    /// Voices used for multi-speaker output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multi_speaker_voice_config: Option<MultiSpeakerVoiceConfig>,
+
+    /// BCP-47 language code of the speech, e.g. `en-US`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<String>,
+}
+
+impl SpeechConfig {
+    /// Creates a single-speaker config using a prebuilt voice such as `Kore`
+    pub fn voice(voice_name: impl Into<String>) -> Self {
+        Self {
+            voice_config: Some(VoiceConfig::prebuilt(voice_name)),
+            ..Self::default()
+        }
+    }
+
+    /// Creates a multi-speaker config
+    ///
+    /// Speaker names must match the names used in the prompt.
+    pub fn multi_speaker(speakers: Vec<SpeakerVoiceConfig>) -> Self {
+        Self {
+            multi_speaker_voice_config: Some(MultiSpeakerVoiceConfig {
+                speaker_voice_configs: speakers,
+            }),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the language of the speech
+    #[must_use]
+    pub fn with_language_code(mut self, language_code: impl Into<String>) -> Self {
+        self.language_code = Some(language_code.into());
+        self
+    }
+}
+
+/// Voice used for speech generation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceConfig {
+    /// Prebuilt voice to use
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prebuilt_voice_config: Option<PrebuiltVoiceConfig>,
+}
+
+impl VoiceConfig {
+    /// Creates a config for a prebuilt voice
+    pub fn prebuilt(voice_name: impl Into<String>) -> Self {
+        Self {
+            prebuilt_voice_config: Some(PrebuiltVoiceConfig {
+                voice_name: voice_name.into(),
+            }),
+        }
+    }
+}
+
+/// A prebuilt voice, selected by name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrebuiltVoiceConfig {
+    /// Name of the voice, e.g. `Kore` or `Puck`
+    pub voice_name: String,
+}
+
+/// Voices for a conversation between several speakers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiSpeakerVoiceConfig {
+    /// Voice of each speaker
+    pub speaker_voice_configs: Vec<SpeakerVoiceConfig>,
+}
+
+/// Voice of one speaker in a multi-speaker config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeakerVoiceConfig {
+    /// Name of the speaker as used in the prompt
+    pub speaker: String,
+    /// Voice of the speaker
+    pub voice_config: VoiceConfig,
+}
+
+impl SpeakerVoiceConfig {
+    /// Assigns a prebuilt voice to a speaker
+    pub fn new(speaker: impl Into<String>, voice_name: impl Into<String>) -> Self {
+        Self {
+            speaker: speaker.into(),
+            voice_config: VoiceConfig::prebuilt(voice_name),
+        }
+    }
+}
+
+/// Config for image generation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageConfig {
+    /// Aspect ratio of generated images; the model default is square
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aspect_ratio: Option<AspectRatio>,
+
+    /// Resolution of generated images, for models that support several
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_size: Option<ImageSize>,
+}
+
+impl ImageConfig {
+    /// Creates an empty config that uses the model defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the aspect ratio of generated images
+    #[must_use]
+    pub fn with_aspect_ratio(mut self, aspect_ratio: AspectRatio) -> Self {
+        self.aspect_ratio = Some(aspect_ratio);
+        self
+    }
+
+    /// Sets the resolution of generated images
+    #[must_use]
+    pub fn with_image_size(mut self, image_size: ImageSize) -> Self {
+        self.image_size = Some(image_size);
+        self
+    }
+}
+
+/// Aspect ratio of generated images, as width to height
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AspectRatio {
+    #[serde(rename = "1:1")]
+    Ratio1x1,
+    #[serde(rename = "2:3")]
+    Ratio2x3,
+    #[serde(rename = "3:2")]
+    Ratio3x2,
+    #[serde(rename = "3:4")]
+    Ratio3x4,
+    #[serde(rename = "4:3")]
+    Ratio4x3,
+    #[serde(rename = "4:5")]
+    Ratio4x5,
+    #[serde(rename = "5:4")]
+    Ratio5x4,
+    #[serde(rename = "9:16")]
+    Ratio9x16,
+    #[serde(rename = "16:9")]
+    Ratio16x9,
+    #[serde(rename = "21:9")]
+    Ratio21x9,
+    /// A ratio not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Resolution of generated images
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ImageSize {
+    /// About 1024 pixels on the longer side
+    #[serde(rename = "1K")]
+    Size1K,
+    /// About 2048 pixels on the longer side
+    #[serde(rename = "2K")]
+    Size2K,
+    /// About 4096 pixels on the longer side
+    #[serde(rename = "4K")]
+    Size4K,
+    /// A size not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Relative amount of thinking for models that support thinking levels
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ThinkingLevel {
+    /// As little thinking as possible
+    Minimal,
+    /// Low thinking, minimizing latency
+    Low,
+    /// Medium thinking
+    Medium,
+    /// High thinking, maximizing reasoning depth
+    High,
+    /// A level not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Harm category used by safety settings and safety ratings
+///
+/// Unrecognized categories deserialize into `Unknown` so newer API values
+/// don't break response parsing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HarmCategory {
+    #[serde(rename = "HARM_CATEGORY_UNSPECIFIED")]
+    Unspecified,
+    #[serde(rename = "HARM_CATEGORY_DEROGATORY")]
+    Derogatory,
+    #[serde(rename = "HARM_CATEGORY_TOXICITY")]
+    Toxicity,
+    #[serde(rename = "HARM_CATEGORY_VIOLENCE")]
+    Violence,
+    #[serde(rename = "HARM_CATEGORY_SEXUAL")]
+    Sexual,
+    #[serde(rename = "HARM_CATEGORY_MEDICAL")]
+    Medical,
+    #[serde(rename = "HARM_CATEGORY_DANGEROUS")]
+    Dangerous,
+    #[serde(rename = "HARM_CATEGORY_HARASSMENT")]
+    Harassment,
+    #[serde(rename = "HARM_CATEGORY_HATE_SPEECH")]
+    HateSpeech,
+    #[serde(rename = "HARM_CATEGORY_SEXUALLY_EXPLICIT")]
+    SexuallyExplicit,
+    #[serde(rename = "HARM_CATEGORY_DANGEROUS_CONTENT")]
+    DangerousContent,
+    #[serde(rename = "HARM_CATEGORY_CIVIC_INTEGRITY")]
+    CivicIntegrity,
+    /// A category not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl HarmCategory {
+    /// Categories that can be configured through `SafetySetting` on Gemini models
+    pub const GEMINI: [HarmCategory; 5] = [
+        HarmCategory::Harassment,
+        HarmCategory::HateSpeech,
+        HarmCategory::SexuallyExplicit,
+        HarmCategory::DangerousContent,
+        HarmCategory::CivicIntegrity,
+    ];
+}
+
+/// Probability that content is harmful, as reported in a `SafetyRating`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HarmProbability {
+    #[serde(rename = "HARM_PROBABILITY_UNSPECIFIED")]
+    Unspecified,
+    #[serde(rename = "NEGLIGIBLE")]
+    Negligible,
+    #[serde(rename = "LOW")]
+    Low,
+    #[serde(rename = "MEDIUM")]
+    Medium,
+    #[serde(rename = "HIGH")]
+    High,
+    /// A probability not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Threshold at which content is blocked for a harm category
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HarmBlockThreshold {
+    #[serde(rename = "HARM_BLOCK_THRESHOLD_UNSPECIFIED")]
+    Unspecified,
+    /// Block content with low, medium or high probability of harm
+    #[serde(rename = "BLOCK_LOW_AND_ABOVE")]
+    BlockLowAndAbove,
+    /// Block content with medium or high probability of harm
+    #[serde(rename = "BLOCK_MEDIUM_AND_ABOVE")]
+    BlockMediumAndAbove,
+    /// Block only content with high probability of harm
+    #[serde(rename = "BLOCK_ONLY_HIGH")]
+    BlockOnlyHigh,
+    /// Never block, but still report safety ratings
+    #[serde(rename = "BLOCK_NONE")]
+    BlockNone,
+    /// Turn off the safety filter entirely
+    #[serde(rename = "OFF")]
+    Off,
+    /// A threshold not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Safety setting for content filtering
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SafetySetting {
+    /// Safety category
+    pub category: HarmCategory,
+
+    /// Harm block threshold
+    pub threshold: HarmBlockThreshold,
+}
+
+impl SafetySetting {
+    /// Creates a safety setting for the given category and threshold
+    pub fn new(category: HarmCategory, threshold: HarmBlockThreshold) -> Self {
+        Self {
+            category,
+            threshold,
+        }
+    }
+
+    /// Blocks content with low or higher probability of harm in `category`
+    pub fn block_low_and_above(category: HarmCategory) -> Self {
+        Self::new(category, HarmBlockThreshold::BlockLowAndAbove)
+    }
+
+    /// Blocks content with medium or higher probability of harm in `category`
+    pub fn block_medium_and_above(category: HarmCategory) -> Self {
+        Self::new(category, HarmBlockThreshold::BlockMediumAndAbove)
+    }
+
+    /// Blocks only content with high probability of harm in `category`
+    pub fn block_only_high(category: HarmCategory) -> Self {
+        Self::new(category, HarmBlockThreshold::BlockOnlyHigh)
+    }
+
+    /// Disables blocking for `category` while keeping safety ratings
+    pub fn block_none(category: HarmCategory) -> Self {
+        Self::new(category, HarmBlockThreshold::BlockNone)
+    }
+
+    /// Applies `threshold` to every category configurable on Gemini models
+    pub fn all(threshold: HarmBlockThreshold) -> Vec<Self> {
+        HarmCategory::GEMINI
+            .into_iter()
+            .map(|category| Self::new(category, threshold.clone()))
+            .collect()
+    }
+
+    /// Disables blocking for every category configurable on Gemini models
+    pub fn block_none_all() -> Vec<Self> {
+        Self::all(HarmBlockThreshold::BlockNone)
+    }
+}
+
+/// Safety rating for content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SafetyRating {
+    /// Harm category
+    pub category: HarmCategory,
+
+    /// Probability of harm
+    pub probability: HarmProbability,
+
+    /// Whether content was blocked
+    #[serde(default)]
+    pub blocked: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto_content::Json;
+
+    #[test]
+    fn test_generation_config_builder_basic() {
+        let config: GenerationConfig = GenerationConfig::builder()
+            .temperature(0.7)
+            .max_output_tokens(1024)
+            .build()
+            .unwrap();
+
+        assert_eq!(config.temperature, Some(0.7));
+        assert_eq!(config.max_output_tokens, Some(1024));
+        assert_eq!(config.response_mime_type, None);
+    }
+
+    #[cfg(feature = "openapi")]
+    #[test]
+    fn test_generation_config_builder_json_response() {
+        #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+        struct TestType {
+            name: String,
+        }
+
+        let config: GenerationConfig<Json<TestType>> = GenerationConfig::<TestType>::builder()
+            .response_schema::<TestType>()
+            .temperature(0.5)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            config.response_mime_type,
+            Some(ResponseMimeType::ApplicationJson)
+        );
+        assert!(config.response_schema.is_some());
+        assert_eq!(config.temperature, Some(0.5));
+    }
+
+    #[test]
     fn test_generation_config_builder_stop_sequences() {
         let config: GenerationConfig = GenerationConfig::builder()
             .add_stop_sequence("END")
@@ -946,14 +1899,29 @@ mod tests {
     #[test]
     fn test_generation_config_builder_modalities() {
         let config: GenerationConfig = GenerationConfig::builder()
-            .add_response_modality("TEXT")
-            .add_response_modality("IMAGE")
+            .add_response_modality(ResponseModality::Text)
+            .add_response_modality(ResponseModality::Image)
+            .image_config(
+                ImageConfig::new()
+                    .with_aspect_ratio(AspectRatio::Ratio16x9)
+                    .with_image_size(ImageSize::Size2K),
+            )
             .build()
             .unwrap();
 
         assert_eq!(
             config.response_modalities,
-            Some(vec!["TEXT".to_string(), "IMAGE".to_string()])
+            Some(vec![ResponseModality::Text, ResponseModality::Image])
+        );
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(
+            json["responseModalities"],
+            serde_json::json!(["TEXT", "IMAGE"])
+        );
+        assert_eq!(
+            json["imageConfig"],
+            serde_json::json!({"aspectRatio": "16:9", "imageSize": "2K"})
         );
     }
 
@@ -974,7 +1942,7 @@ mod tests {
             result: String,
         }
 
-        let config: GenerationConfig<TestResult> = GenerationConfig::<TestResult>::builder()
+        let config: GenerationConfig<Json<TestResult>> = GenerationConfig::<TestResult>::builder()
             .temperature(0.7)
             .max_output_tokens(1024)
             .response_schema::<TestResult>()
@@ -1006,11 +1974,71 @@ mod tests {
         assert!(json.contains("\"application/json\""));
 
         // Test TextEnum serialization
-        let config: GenerationConfig = GenerationConfig::builder().enum_response().build().unwrap();
+        let config: GenerationConfig = GenerationConfig::<String> {
+            response_mime_type: Some(ResponseMimeType::TextEnum),
+            ..Default::default()
+        };
         let json = serde_json::to_string(&config).unwrap();
         assert!(json.contains("\"text/x.enum\""));
     }
 
+    #[test]
+    fn test_enum_response_schema() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Sentiment {
+            Positive,
+            Neutral,
+            #[serde(rename = "bad")]
+            Negative,
+        }
+
+        let config = GenerationConfig::<String>::builder()
+            .temperature(0.0)
+            .enum_response::<Sentiment>()
+            .build()
+            .unwrap();
+        assert_eq!(config.response_mime_type, Some(ResponseMimeType::TextEnum));
+        assert_eq!(config.temperature, Some(0.0));
+
+        let (schema, dialect) = config.schema().unwrap();
+        assert_eq!(dialect, SchemaDialect::OpenApi);
+        assert_eq!(schema["format"], "enum");
+        assert_eq!(
+            schema["enum"],
+            serde_json::json!(["positive", "neutral", "bad"])
+        );
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(json.get("responseSchema").is_some());
+    }
+
+    #[test]
+    fn test_enum_response_rejects_non_enums() {
+        #[derive(Debug, Serialize, Deserialize)]
+        struct Label {
+            name: String,
+        }
+
+        let error = GenerationConfig::<String>::builder()
+            .enum_response::<Label>()
+            .build()
+            .unwrap_err();
+        assert!(matches!(error, BuildError::UnsupportedSchema(_)));
+        assert!(error.to_string().contains("not an enum of unit variants"));
+
+        #[derive(Debug, Serialize, Deserialize)]
+        enum Answer {
+            Yes,
+            Other(String),
+        }
+
+        let error = GenerationConfig::<String>::builder()
+            .enum_response::<Answer>()
+            .build()
+            .unwrap_err();
+        assert!(matches!(error, BuildError::UnsupportedSchema(_)));
+    }
+
     #[cfg(all(feature = "openapi", feature = "json"))]
     #[test]
     fn test_schema_auto_sets_mime_type() {
@@ -1020,7 +2048,7 @@ mod tests {
             result: String,
         }
 
-        let config: GenerationConfig<TestTypeOpenApi> =
+        let config: GenerationConfig<Json<TestTypeOpenApi>> =
             GenerationConfig::<TestTypeOpenApi>::builder()
                 .response_schema::<TestTypeOpenApi>()
                 .build()
@@ -1036,10 +2064,11 @@ mod tests {
             result: String,
         }
 
-        let config2: GenerationConfig<TestTypeJson> = GenerationConfig::<TestTypeJson>::builder()
-            .response_json_schema::<TestTypeJson>()
-            .build()
-            .unwrap();
+        let config2: GenerationConfig<Json<TestTypeJson>> =
+            GenerationConfig::<TestTypeJson>::builder()
+                .response_json_schema::<TestTypeJson>()
+                .build()
+                .unwrap();
 
         assert_eq!(
             config2.response_mime_type,
@@ -1061,7 +2090,7 @@ mod tests {
             age: u32,
         }
 
-        let config: GenerationConfig<TestType2> = GenerationConfig::<TestType1>::builder()
+        let config: GenerationConfig<Json<TestType2>> = GenerationConfig::<TestType1>::builder()
             .response_schema::<TestType1>()
             .response_json_schema::<TestType2>()
             .build()
@@ -1080,7 +2109,7 @@ mod tests {
             value: String,
         }
 
-        let config2: GenerationConfig<TestType3> = GenerationConfig::<TestType2>::builder()
+        let config2: GenerationConfig<Json<TestType3>> = GenerationConfig::<TestType2>::builder()
             .response_json_schema::<TestType2>()
             .response_schema::<TestType3>()
             .build()
@@ -1112,7 +2141,7 @@ mod tests {
         }
 
         // Use turbofish to specify response type - schema is auto-derived
-        let config: GenerationConfig<Character> = GenerationConfig::<Character>::builder()
+        let config: GenerationConfig<Json<Character>> = GenerationConfig::<String>::builder()
             .response_json_schema::<Character>()
             .temperature(0.7)
             .build()
@@ -1122,14 +2151,47 @@ mod tests {
         assert_eq!(config.temperature, Some(0.7));
 
         // Use typed config in a request
-        let _request: GenerateContentRequest<Character> = GenerateContentRequest {
+        let _request: GenerateContentRequest<Json<Character>> = GenerateContentRequest {
             contents: vec![],
             generation_config: Some(config),
             system_instruction: None,
             safety_settings: None,
+            tools: None,
+            cached_content: None,
         };
     }
 
+    #[test]
+    fn test_generation_config_with_defaults() {
+        let defaults = GenerationConfig::<String>::builder()
+            .temperature(0.2)
+            .max_output_tokens(128)
+            .text_response()
+            .build()
+            .unwrap();
+
+        let config = GenerationConfig::<String>::builder()
+            .temperature(0.9)
+            .build()
+            .unwrap()
+            .with_defaults(&defaults);
+        assert_eq!(config.temperature, Some(0.9));
+        assert_eq!(config.max_output_tokens, Some(128));
+        assert_eq!(config.response_mime_type, Some(ResponseMimeType::TextPlain));
+
+        // A config choosing its own response format keeps it whole
+        let config = GenerationConfig::<String> {
+            response_mime_type: Some(ResponseMimeType::ApplicationJson),
+            ..Default::default()
+        }
+        .with_defaults(&defaults);
+        assert_eq!(
+            config.response_mime_type,
+            Some(ResponseMimeType::ApplicationJson)
+        );
+        assert_eq!(config.temperature, Some(0.2));
+    }
+
     #[test]
     fn test_build_validates_string_type_allows_no_schema() {
         // String type should not require a schema
@@ -1151,7 +2213,7 @@ mod tests {
         }
 
         // Typed response without schema should fail
-        let result = GenerationConfig::<TestStruct>::builder()
+        let result = GenerationConfig::<Json<TestStruct>>::builder()
             .temperature(0.7)
             .build();
 
@@ -1162,7 +2224,7 @@ mod tests {
         );
 
         // Typed response WITH schema should succeed (auto-derived)
-        let result = GenerationConfig::<TestStruct>::builder()
+        let result = GenerationConfig::<Json<TestStruct>>::builder()
             .response_schema::<TestStruct>()
             .temperature(0.7)
             .build();
@@ -1181,7 +2243,7 @@ mod tests {
         }
 
         // Typed response without schema should fail
-        let result = GenerationConfig::<TestStruct>::builder()
+        let result = GenerationConfig::<Json<TestStruct>>::builder()
             .temperature(0.7)
             .build();
 
@@ -1192,7 +2254,7 @@ mod tests {
         );
 
         // Typed response WITH json_schema should succeed (auto-derived)
-        let result = GenerationConfig::<TestStruct>::builder()
+        let result = GenerationConfig::<Json<TestStruct>>::builder()
             .response_json_schema::<TestStruct>()
             .temperature(0.7)
             .build();
@@ -1211,7 +2273,7 @@ mod tests {
         }
 
         // Without schema features, typed responses should always fail
-        let result = GenerationConfig::<TestStruct>::builder()
+        let result = GenerationConfig::<Json<TestStruct>>::builder()
             .temperature(0.7)
             .build();
 
@@ -1233,7 +2295,7 @@ mod tests {
             age: u32,
         }
 
-        let config: GenerationConfig<TestPerson> = GenerationConfig::<TestPerson>::builder()
+        let config: GenerationConfig<Json<TestPerson>> = GenerationConfig::<TestPerson>::builder()
             .response_json_schema::<TestPerson>()
             .build()
             .unwrap();
@@ -1262,7 +2324,7 @@ mod tests {
             age: u32,
         }
 
-        let config: GenerationConfig<TestPerson> = GenerationConfig::<TestPerson>::builder()
+        let config: GenerationConfig<Json<TestPerson>> = GenerationConfig::<TestPerson>::builder()
             .response_schema::<TestPerson>()
             .build()
             .unwrap();
@@ -1280,6 +2342,142 @@ mod tests {
         assert_eq!(schema["properties"]["age"]["type"], "integer");
     }
 
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_derived_json_schema_is_normalized() {
+        use serde::{Deserialize, Serialize};
+
+        /// Priority of a task
+        #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+        enum Priority {
+            /// Can wait
+            Low,
+            /// Needs attention today
+            High,
+        }
+
+        #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+        struct Task {
+            title: String,
+            priority: Priority,
+            estimate: Option<u32>,
+            subtasks: Vec<Task>,
+        }
+
+        let config = GenerationConfig::<String>::builder()
+            .response_json_schema::<Task>()
+            .build()
+            .unwrap();
+        let schema = config.response_json_schema.unwrap();
+
+        assert!(schema.get("$schema").is_none());
+        assert_eq!(
+            schema["propertyOrdering"],
+            serde_json::json!(["title", "priority", "estimate", "subtasks"])
+        );
+        assert_eq!(
+            schema["properties"]["priority"],
+            serde_json::json!({
+                "description": "Priority of a task",
+                "type": "string",
+                "enum": ["Low", "High"]
+            })
+        );
+        assert_eq!(
+            schema["properties"]["estimate"],
+            serde_json::json!({"type": ["integer", "null"], "minimum": 0})
+        );
+        assert_eq!(schema["properties"]["subtasks"]["items"]["$ref"], "#");
+    }
+
+    #[cfg(feature = "openapi")]
+    #[test]
+    fn test_derived_openapi_schema_is_normalized() {
+        use serde::{Deserialize, Serialize};
+        use std::collections::HashMap;
+
+        #[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+        struct Address {
+            city: String,
+        }
+
+        #[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+        struct Contact {
+            name: String,
+            address: Option<Address>,
+        }
+
+        let config = GenerationConfig::<String>::builder()
+            .response_schema::<Contact>()
+            .build()
+            .unwrap();
+        let schema = config.response_schema.unwrap();
+
+        assert_eq!(
+            schema["propertyOrdering"],
+            serde_json::json!(["name", "address"])
+        );
+        assert_eq!(schema["properties"]["address"]["type"], "object");
+        assert_eq!(schema["properties"]["address"]["nullable"], true);
+        assert_eq!(
+            schema["properties"]["address"]["properties"]["city"],
+            serde_json::json!({"type": "string"})
+        );
+        assert!(schema.get("components").is_none());
+
+        #[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+        struct Scores {
+            by_player: HashMap<String, u32>,
+        }
+
+        let error = GenerationConfig::<String>::builder()
+            .response_schema::<Scores>()
+            .build()
+            .unwrap_err();
+        let BuildError::UnsupportedSchema(error) = error else {
+            panic!("expected an unsupported schema error");
+        };
+        assert_eq!(
+            error.issues[0].path,
+            "#/properties/by_player/additionalProperties"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_runtime_json_schema() {
+        use crate::schema::{Schema, StringFormat};
+
+        let schema = Schema::object()
+            .property("name", Schema::string())
+            .property("born", Schema::string().with_format(StringFormat::Date));
+        let config: GenerationConfig<serde_json::Value> = GenerationConfig::<String>::builder()
+            .temperature(0.2)
+            .response_json_schema_value(schema)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            config.response_mime_type,
+            Some(ResponseMimeType::ApplicationJson)
+        );
+        assert_eq!(config.temperature, Some(0.2));
+        assert_eq!(
+            config.response_json_schema.unwrap()["propertyOrdering"],
+            serde_json::json!(["name", "born"])
+        );
+
+        // Raw schemas from elsewhere are validated too
+        let error = GenerationConfig::<String>::builder()
+            .response_json_schema_value(serde_json::json!({
+                "type": "object",
+                "properties": {"id": {"type": ["string", "integer"]}}
+            }))
+            .build()
+            .unwrap_err();
+        assert!(matches!(error, BuildError::UnsupportedSchema(_)));
+    }
+
     #[test]
     fn test_build_error_display() {
         let error = BuildError::SchemaRequiredForTypedResponse;
@@ -1351,10 +2549,7 @@ mod tests {
     fn test_generate_content_request_builder_with_safety_settings() {
         use crate::dto_content::{Content, JsonString, Part};
 
-        let setting = SafetySetting {
-            category: "HARM_CATEGORY_DANGEROUS".to_string(),
-            threshold: "BLOCK_MEDIUM_AND_ABOVE".to_string(),
-        };
+        let setting = SafetySetting::block_medium_and_above(HarmCategory::DangerousContent);
 
         let request: GenerateContentRequest<String> = GenerateContentRequest::builder()
             .add_content(Content::unspecified(vec![
@@ -1438,4 +2633,196 @@ mod tests {
         assert!(request.system_instruction().is_none());
         assert!(request.safety_settings().is_none());
     }
+
+    #[test]
+    fn test_safety_setting_serialization() {
+        let setting = SafetySetting::block_only_high(HarmCategory::HateSpeech);
+        let json = serde_json::to_value(&setting).unwrap();
+
+        assert_eq!(json["category"], "HARM_CATEGORY_HATE_SPEECH");
+        assert_eq!(json["threshold"], "BLOCK_ONLY_HIGH");
+    }
+
+    #[test]
+    fn test_safety_setting_block_none_all() {
+        let settings = SafetySetting::block_none_all();
+
+        assert_eq!(settings.len(), HarmCategory::GEMINI.len());
+        assert!(
+            settings
+                .iter()
+                .all(|s| s.threshold == HarmBlockThreshold::BlockNone)
+        );
+        assert!(
+            settings
+                .iter()
+                .any(|s| s.category == HarmCategory::DangerousContent)
+        );
+    }
+
+    #[test]
+    fn test_safety_rating_deserialization() {
+        let json = r#"{
+            "category": "HARM_CATEGORY_HARASSMENT",
+            "probability": "NEGLIGIBLE"
+        }"#;
+
+        let rating: SafetyRating = serde_json::from_str(json).unwrap();
+        assert_eq!(rating.category, HarmCategory::Harassment);
+        assert_eq!(rating.probability, HarmProbability::Negligible);
+        assert!(!rating.blocked);
+    }
+
+    #[test]
+    fn test_harm_enums_unknown_fallback() {
+        let category: HarmCategory =
+            serde_json::from_str(r#""HARM_CATEGORY_SOMETHING_NEW""#).unwrap();
+        assert_eq!(
+            category,
+            HarmCategory::Unknown("HARM_CATEGORY_SOMETHING_NEW".to_string())
+        );
+
+        // Unknown values serialize back verbatim
+        let json = serde_json::to_string(&category).unwrap();
+        assert_eq!(json, r#""HARM_CATEGORY_SOMETHING_NEW""#);
+
+        let threshold: HarmBlockThreshold = serde_json::from_str(r#""BLOCK_MOST""#).unwrap();
+        assert_eq!(
+            threshold,
+            HarmBlockThreshold::Unknown("BLOCK_MOST".to_string())
+        );
+    }
+
+    #[test]
+    fn test_media_resolution_serialization() {
+        let config: GenerationConfig = GenerationConfig::builder()
+            .media_resolution(MediaResolution::Low)
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["mediaResolution"], "MEDIA_RESOLUTION_LOW");
+
+        let resolution: MediaResolution =
+            serde_json::from_str("\"MEDIA_RESOLUTION_ULTRA\"").unwrap();
+        assert_eq!(
+            resolution,
+            MediaResolution::Unknown("MEDIA_RESOLUTION_ULTRA".to_string())
+        );
+    }
+
+    #[test]
+    fn test_speech_config_serialization() {
+        let single = SpeechConfig::voice("Kore").with_language_code("en-US");
+        assert_eq!(
+            serde_json::to_value(&single).unwrap(),
+            serde_json::json!({
+                "voiceConfig": {"prebuiltVoiceConfig": {"voiceName": "Kore"}},
+                "languageCode": "en-US"
+            })
+        );
+
+        let multi = SpeechConfig::multi_speaker(vec![
+            SpeakerVoiceConfig::new("Joe", "Kore"),
+            SpeakerVoiceConfig::new("Jane", "Puck"),
+        ]);
+        let json = serde_json::to_value(&multi).unwrap();
+        assert_eq!(
+            json["multiSpeakerVoiceConfig"]["speakerVoiceConfigs"][1],
+            serde_json::json!({
+                "speaker": "Jane",
+                "voiceConfig": {"prebuiltVoiceConfig": {"voiceName": "Puck"}}
+            })
+        );
+        assert!(json.get("voiceConfig").is_none());
+    }
+
+    #[test]
+    fn test_mime_type_parsing_and_fallback() {
+        assert_eq!(
+            "Text/CSV; charset=utf-8".parse::<MimeType>().unwrap(),
+            MimeType::TextCsv
+        );
+        assert_eq!(
+            "image/gif".parse::<MimeType>().unwrap(),
+            MimeType::Other("image/gif".to_string())
+        );
+        assert!("png".parse::<MimeType>().is_err());
+        assert!("image/".parse::<MimeType>().is_err());
+
+        assert_eq!(MimeType::VideoFlv.to_string(), "video/x-flv");
+
+        let mime_type: MimeType = serde_json::from_str("\"audio/webm\"").unwrap();
+        assert_eq!(mime_type, MimeType::Other("audio/webm".to_string()));
+        assert_eq!(mime_type.to_string(), "audio/webm");
+        assert_eq!(serde_json::to_string(&mime_type).unwrap(), "\"audio/webm\"");
+    }
+
+    #[test]
+    fn test_mime_type_normalizes_known_types() {
+        assert_eq!("IMAGE/PNG".parse::<MimeType>().unwrap(), MimeType::ImagePng);
+        for json in [r#""image/png""#, r#""Image/PNG""#, r#"" image/png; q=1""#] {
+            let mime_type: MimeType = serde_json::from_str(json).unwrap();
+            assert_eq!(mime_type, MimeType::ImagePng);
+        }
+
+        let pcm: MimeType = serde_json::from_str(r#""audio/L16;codec=pcm;rate=24000""#).unwrap();
+        assert_eq!(
+            pcm,
+            MimeType::Other("audio/l16;codec=pcm;rate=24000".to_string())
+        );
+        assert_eq!(pcm.parameter("rate"), Some("24000"));
+
+        let malformed: MimeType = serde_json::from_str(r#""not a mime type""#).unwrap();
+        assert_eq!(malformed, MimeType::Other("not a mime type".to_string()));
+    }
+
+    #[test]
+    fn test_thinking_config_serialization() {
+        let config: GenerationConfig = GenerationConfig::builder()
+            .thinking_config(ThinkingConfig::budget(1024).with_include_thoughts(true))
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(
+            json["thinkingConfig"],
+            serde_json::json!({"includeThoughts": true, "thinkingBudget": 1024})
+        );
+
+        let json = serde_json::to_value(ThinkingConfig::level(ThinkingLevel::High)).unwrap();
+        assert_eq!(json, serde_json::json!({"thinkingLevel": "HIGH"}));
+
+        assert_eq!(ThinkingConfig::dynamic().thinking_budget, Some(-1));
+        assert_eq!(ThinkingConfig::disabled().thinking_budget, Some(0));
+
+        let level: ThinkingLevel = serde_json::from_str(r#""EXTREME""#).unwrap();
+        assert_eq!(level, ThinkingLevel::Unknown("EXTREME".to_string()));
+    }
+
+    #[test]
+    fn test_request_tools_serialization() {
+        use crate::dto_content::{Content, JsonString, Part};
+
+        let request: GenerateContentRequest = GenerateContentRequest::builder()
+            .add_content(Content::user(vec![
+                Part::builder()
+                    .text(JsonString::new("Who won Euro 2024?".to_string()))
+                    .build(),
+            ]))
+            .add_tool(Tool::google_search())
+            .add_tool(Tool::url_context())
+            .add_tool(Tool::code_execution())
+            .build();
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            json["tools"],
+            serde_json::json!([{"google_search": {}}, {"url_context": {}}, {"code_execution": {}}])
+        );
+        assert_eq!(request.tools().unwrap().len(), 3);
+
+        let without_tools: GenerateContentRequest = GenerateContentRequest::builder().build();
+        let json = serde_json::to_value(&without_tools).unwrap();
+        assert!(json.get("tools").is_none());
+    }
 }
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 
-use super::dto_content::Content;
+use crate::confidence::{FieldConfidence, field_confidence};
+
+use super::dto_content::{Content, TextPayload, TranscriptEntry};
 use super::dto_request::SafetyRating;
 
 /// Response from generateContent API call
@@ -9,21 +11,19 @@ use super::dto_request::SafetyRating;
 /// When using `response_json_schema`, specify the type parameter and JSON deserialization
 /// happens automatically during response parsing.
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(bound(
-    serialize = "T: Serialize + 'static",
-    deserialize = "T: serde::de::DeserializeOwned + 'static"
-))]
+#[serde(rename_all = "camelCase")]
+#[serde(bound(serialize = "T: TextPayload", deserialize = "T: TextPayload"))]
 pub struct GenerateContentResponse<T = String> {
     /// Candidate responses from the model
     #[serde(default)]
     pub candidates: Vec<Candidate<T>>,
 
     /// Prompt feedback related to content filters
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "prompt_feedback", skip_serializing_if = "Option::is_none")]
     pub prompt_feedback: Option<PromptFeedback>,
 
     /// Token usage metadata
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "usage_metadata", skip_serializing_if = "Option::is_none")]
     pub usage_metadata: Option<UsageMetadata>,
 }
 
@@ -32,52 +32,734 @@ pub struct GenerateContentResponse<T = String> {
 /// Generic over the text content type `T`, which defaults to `String`.
 /// When using `response_json_schema`, the text content is automatically deserialized into `T`.
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(bound(
-    serialize = "T: Serialize + 'static",
-    deserialize = "T: serde::de::DeserializeOwned + 'static"
-))]
+#[serde(rename_all = "camelCase")]
+#[serde(bound(serialize = "T: TextPayload", deserialize = "T: TextPayload"))]
 pub struct Candidate<T = String> {
     /// Generated content
+    ///
+    /// Defaults to empty model content, since blocked candidates may omit it.
+    #[serde(default = "empty_model_content")]
     pub content: Content<T>,
 
     /// Reason why generation stopped
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub finish_reason: Option<String>,
+    #[serde(alias = "finish_reason", skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<FinishReason>,
 
     /// Safety ratings for the candidate
-    #[serde(default)]
+    #[serde(default, alias = "safety_ratings")]
     pub safety_ratings: Vec<SafetyRating>,
+
+    /// Sources used to ground the answer, when a grounding tool was enabled
+    #[serde(alias = "grounding_metadata", skip_serializing_if = "Option::is_none")]
+    pub grounding_metadata: Option<GroundingMetadata>,
+
+    /// URLs retrieved by the URL context tool
+    #[serde(
+        alias = "url_context_metadata",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub url_context_metadata: Option<UrlContextMetadata>,
+
+    /// Sources the model recited from
+    #[serde(alias = "citation_metadata", skip_serializing_if = "Option::is_none")]
+    pub citation_metadata: Option<CitationMetadata>,
+
+    /// Average log probability of the candidate's tokens
+    #[serde(alias = "avg_logprobs", skip_serializing_if = "Option::is_none")]
+    pub avg_logprobs: Option<f64>,
+
+    /// Token log probabilities, returned when `response_logprobs` is set
+    #[serde(alias = "logprobs_result", skip_serializing_if = "Option::is_none")]
+    pub logprobs_result: Option<LogprobsResult>,
+}
+
+/// Log probabilities of the generated tokens
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogprobsResult {
+    /// The most likely tokens at each decoding step, up to `logprobs` per step
+    #[serde(default, alias = "topCandidates")]
+    pub top_candidates: Vec<TopCandidates>,
+
+    /// The token chosen at each decoding step
+    #[serde(default, alias = "chosenCandidates")]
+    pub chosen_candidates: Vec<LogprobsCandidate>,
+}
+
+/// The most likely tokens of a single decoding step, in descending probability
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TopCandidates {
+    /// Candidate tokens
+    #[serde(default)]
+    pub candidates: Vec<LogprobsCandidate>,
+}
+
+/// A token and its log probability
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogprobsCandidate {
+    /// The token text
+    #[serde(default)]
+    pub token: String,
+
+    /// The token id
+    #[serde(alias = "tokenId", skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<i32>,
+
+    /// Log probability of the token
+    #[serde(default, alias = "logProbability")]
+    pub log_probability: f32,
+}
+
+/// Sources the model recited from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CitationMetadata {
+    /// Citations to sources for parts of the answer
+    #[serde(default, alias = "citationSources")]
+    pub citation_sources: Vec<CitationSource>,
+}
+
+/// A citation to a source for part of the answer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CitationSource {
+    /// Start of the cited segment in the answer, in bytes
+    #[serde(alias = "startIndex", skip_serializing_if = "Option::is_none")]
+    pub start_index: Option<i32>,
+
+    /// End of the cited segment in the answer, in bytes, exclusive
+    #[serde(alias = "endIndex", skip_serializing_if = "Option::is_none")]
+    pub end_index: Option<i32>,
+
+    /// URI of the source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+
+    /// License of the source, for GitHub projects
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+}
+
+impl<T> Default for Candidate<T> {
+    /// Returns a candidate with empty model content and no metadata
+    fn default() -> Self {
+        Self {
+            content: empty_model_content(),
+            finish_reason: None,
+            safety_ratings: Vec::new(),
+            grounding_metadata: None,
+            url_context_metadata: None,
+            citation_metadata: None,
+            avg_logprobs: None,
+            logprobs_result: None,
+        }
+    }
+}
+
+impl<T> Candidate<T> {
+    /// Returns the confidence of every leaf value of a structured answer
+    ///
+    /// Requires `response_logprobs` to be enabled; returns an empty list otherwise.
+    /// See [`crate::confidence::field_confidence`].
+    pub fn field_confidence(&self) -> Vec<FieldConfidence> {
+        self.logprobs_result
+            .as_ref()
+            .map(field_confidence)
+            .unwrap_or_default()
+    }
+}
+
+/// Grounding sources and how they support the generated answer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroundingMetadata {
+    /// Google Search suggestions to display alongside grounded answers
+    #[serde(alias = "searchEntryPoint", skip_serializing_if = "Option::is_none")]
+    pub search_entry_point: Option<SearchEntryPoint>,
+
+    /// Sources the answer is grounded in, referenced by index from `grounding_supports`
+    #[serde(
+        default,
+        alias = "groundingChunks",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub grounding_chunks: Vec<GroundingChunk>,
+
+    /// Segments of the answer and the chunks that support them
+    #[serde(
+        default,
+        alias = "groundingSupports",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub grounding_supports: Vec<GroundingSupport>,
+
+    /// Search queries issued by the model
+    #[serde(
+        default,
+        alias = "webSearchQueries",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub web_search_queries: Vec<String>,
+}
+
+/// Google Search entry point for displaying search suggestions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchEntryPoint {
+    /// HTML and CSS snippet that renders the suggestions
+    #[serde(alias = "renderedContent", skip_serializing_if = "Option::is_none")]
+    pub rendered_content: Option<String>,
+
+    /// Base64-encoded JSON of search terms and URLs
+    #[serde(alias = "sdkBlob", skip_serializing_if = "Option::is_none")]
+    pub sdk_blob: Option<String>,
+}
+
+/// A single grounding source
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroundingChunk {
+    /// Web page the answer is grounded in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web: Option<WebSource>,
+}
+
+/// A web page used as a grounding source
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WebSource {
+    /// URI of the page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+
+    /// Title of the page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// A segment of the answer supported by one or more grounding chunks
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroundingSupport {
+    /// The supported segment of the answer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segment: Option<Segment>,
+
+    /// Indices into `GroundingMetadata::grounding_chunks`
+    #[serde(
+        default,
+        alias = "groundingChunkIndices",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub grounding_chunk_indices: Vec<i32>,
+
+    /// Confidence of each referenced chunk, in the same order
+    #[serde(
+        default,
+        alias = "confidenceScores",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub confidence_scores: Vec<f32>,
+}
+
+/// A span of text within a part
+///
+/// Offsets are byte offsets into the UTF-8 text of the part.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    /// Index of the part the segment belongs to
+    #[serde(default, alias = "partIndex")]
+    pub part_index: i32,
+
+    /// Start offset in bytes, inclusive
+    #[serde(default, alias = "startIndex")]
+    pub start_index: i32,
+
+    /// End offset in bytes, exclusive
+    #[serde(default, alias = "endIndex")]
+    pub end_index: i32,
+
+    /// Text of the segment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// URLs retrieved by the URL context tool
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UrlContextMetadata {
+    /// Retrieval result of each URL
+    #[serde(default, alias = "urlMetadata")]
+    pub url_metadata: Vec<UrlMetadata>,
+}
+
+/// Retrieval result of a single URL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UrlMetadata {
+    /// The URL that was retrieved
+    #[serde(alias = "retrievedUrl")]
+    pub retrieved_url: String,
+
+    /// Whether retrieval succeeded
+    #[serde(alias = "urlRetrievalStatus", skip_serializing_if = "Option::is_none")]
+    pub url_retrieval_status: Option<UrlRetrievalStatus>,
+}
+
+/// Status of a URL retrieval
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum UrlRetrievalStatus {
+    /// Default value, unused
+    UrlRetrievalStatusUnspecified,
+    /// The URL was retrieved
+    UrlRetrievalStatusSuccess,
+    /// The URL could not be retrieved
+    UrlRetrievalStatusError,
+    /// The URL is behind a paywall
+    UrlRetrievalStatusPaywall,
+    /// The URL content is unsafe
+    UrlRetrievalStatusUnsafe,
+    /// A status not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl GroundingMetadata {
+    /// Inserts citation markers such as `[1]` or `[1][3]` into the text of part `part_index`
+    ///
+    /// A marker is placed at the end of every supported segment of that part and
+    /// numbers the referenced `grounding_chunks` starting at 1. Segments whose offsets
+    /// fall outside the text are ignored.
+    pub fn render_citations(&self, part_index: i32, text: &str) -> String {
+        let mut insertions: Vec<(usize, String)> = self
+            .grounding_supports
+            .iter()
+            .filter_map(|support| {
+                let segment = support.segment.as_ref()?;
+                if segment.part_index != part_index || support.grounding_chunk_indices.is_empty() {
+                    return None;
+                }
+                let mut end = usize::try_from(segment.end_index).ok()?;
+                if end > text.len() {
+                    return None;
+                }
+                while !text.is_char_boundary(end) {
+                    end += 1;
+                }
+                let marker: String = support
+                    .grounding_chunk_indices
+                    .iter()
+                    .map(|index| format!("[{}]", index + 1))
+                    .collect();
+                Some((end, marker))
+            })
+            .collect();
+
+        // Stable sort keeps markers for the same offset in support order
+        insertions.sort_by_key(|(offset, _)| *offset);
+
+        let mut rendered = String::with_capacity(text.len());
+        let mut last = 0;
+        for (offset, marker) in insertions {
+            rendered.push_str(&text[last..offset]);
+            rendered.push_str(&marker);
+            last = offset;
+        }
+        rendered.push_str(&text[last..]);
+        rendered
+    }
+}
+
+/// Reason why the model stopped generating tokens
+///
+/// Unrecognized reasons deserialize into `Unknown` so newer API values
+/// don't break response parsing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FinishReason {
+    /// Default value, unused
+    FinishReasonUnspecified,
+    /// Natural stop point of the model or a provided stop sequence
+    Stop,
+    /// The maximum number of tokens specified in the request was reached
+    MaxTokens,
+    /// The response was flagged for safety reasons
+    Safety,
+    /// The response was flagged for recitation reasons
+    Recitation,
+    /// The response was flagged for using an unsupported language
+    Language,
+    /// Unknown reason
+    Other,
+    /// The response contained forbidden terms
+    Blocklist,
+    /// The response was flagged for prohibited content
+    ProhibitedContent,
+    /// The response was flagged for sensitive personally identifiable information
+    Spii,
+    /// The function call generated by the model is invalid
+    MalformedFunctionCall,
+    /// The generated image was flagged for safety reasons
+    ImageSafety,
+    /// The model generated a tool call but no tools were enabled
+    UnexpectedToolCall,
+    /// The model called too many tools consecutively
+    TooManyToolCalls,
+    /// A reason not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl FinishReason {
+    /// Returns true if generation stopped because the candidate was blocked
+    ///
+    /// Covers safety, recitation, blocklist, prohibited content, SPII and image safety stops.
+    pub fn is_blocked(&self) -> bool {
+        matches!(
+            self,
+            FinishReason::Safety
+                | FinishReason::Recitation
+                | FinishReason::Blocklist
+                | FinishReason::ProhibitedContent
+                | FinishReason::Spii
+                | FinishReason::ImageSafety
+        )
+    }
+}
+
+/// Reason why a prompt was blocked
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BlockReason {
+    /// Default value, unused
+    BlockReasonUnspecified,
+    /// The prompt was blocked for safety reasons
+    Safety,
+    /// The prompt was blocked for unknown reasons
+    Other,
+    /// The prompt contained forbidden terms
+    Blocklist,
+    /// The prompt was blocked for prohibited content
+    ProhibitedContent,
+    /// The prompt was blocked for unsafe image generation content
+    ImageSafety,
+    /// A reason not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Returns model content without parts, used when a candidate carries no content
+fn empty_model_content<T>() -> Content<T> {
+    Content::model(Vec::new())
 }
 
 /// Feedback about the prompt
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PromptFeedback {
     /// Reason the prompt was blocked
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub block_reason: Option<String>,
+    #[serde(alias = "block_reason", skip_serializing_if = "Option::is_none")]
+    pub block_reason: Option<BlockReason>,
 
     /// Safety ratings for the prompt
-    #[serde(default)]
+    #[serde(default, alias = "safety_ratings")]
     pub safety_ratings: Vec<SafetyRating>,
 }
 
 /// Token usage metadata
-#[derive(Debug, Serialize, Deserialize)]
+///
+/// Counts from separate requests can be combined with `+`, `+=` or `Iterator::sum`,
+/// which adds the totals and merges the per-modality breakdowns.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct UsageMetadata {
-    /// Number of tokens in the prompt
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub prompt_token_count: Option<i32>,
+    /// Number of tokens in the prompt, including cached content
+    #[serde(alias = "prompt_token_count", skip_serializing_if = "Option::is_none")]
+    pub prompt_token_count: Option<u32>,
+
+    /// Number of tokens in the cached part of the prompt
+    #[serde(
+        alias = "cached_content_token_count",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub cached_content_token_count: Option<u32>,
 
     /// Number of tokens in the response candidates
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub candidates_token_count: Option<i32>,
+    #[serde(
+        alias = "candidates_token_count",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub candidates_token_count: Option<u32>,
+
+    /// Number of tokens in tool-use prompts
+    #[serde(
+        alias = "tool_use_prompt_token_count",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tool_use_prompt_token_count: Option<u32>,
+
+    /// Number of tokens spent on thinking
+    #[serde(
+        alias = "thoughts_token_count",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub thoughts_token_count: Option<u32>,
 
     /// Total token count
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub total_token_count: Option<i32>,
+    #[serde(alias = "total_token_count", skip_serializing_if = "Option::is_none")]
+    pub total_token_count: Option<u32>,
+
+    /// Per-modality breakdown of the prompt tokens
+    #[serde(
+        default,
+        alias = "prompt_tokens_details",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub prompt_tokens_details: Vec<ModalityTokenCount>,
+
+    /// Per-modality breakdown of the cached prompt tokens
+    #[serde(
+        default,
+        alias = "cache_tokens_details",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub cache_tokens_details: Vec<ModalityTokenCount>,
+
+    /// Per-modality breakdown of the candidate tokens
+    #[serde(
+        default,
+        alias = "candidates_tokens_details",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub candidates_tokens_details: Vec<ModalityTokenCount>,
+
+    /// Per-modality breakdown of the tool-use prompt tokens
+    #[serde(
+        default,
+        alias = "tool_use_prompt_tokens_details",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub tool_use_prompt_tokens_details: Vec<ModalityTokenCount>,
+}
+
+/// Content modality of a token count
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Modality {
+    /// Default value, unused
+    ModalityUnspecified,
+    /// Plain text
+    Text,
+    /// Image
+    Image,
+    /// Video
+    Video,
+    /// Audio
+    Audio,
+    /// Document, e.g. PDF
+    Document,
+    /// A modality not known to this version of the client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Number of tokens for a single modality
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModalityTokenCount {
+    /// The modality these tokens belong to
+    pub modality: Modality,
+
+    /// Number of tokens
+    #[serde(default, alias = "token_count")]
+    pub token_count: u32,
+}
+
+/// Adds two optional counts, keeping `None` only if both are `None`
+///
+/// Saturates at `u32::MAX` rather than overflowing.
+fn add_counts(a: Option<u32>, b: Option<u32>) -> Option<u32> {
+    match (a, b) {
+        (None, None) => None,
+        (a, b) => Some(a.unwrap_or(0).saturating_add(b.unwrap_or(0))),
+    }
+}
+
+/// Merges per-modality counts, summing entries with the same modality
+fn merge_details(
+    mut into: Vec<ModalityTokenCount>,
+    from: Vec<ModalityTokenCount>,
+) -> Vec<ModalityTokenCount> {
+    for detail in from {
+        match into.iter_mut().find(|d| d.modality == detail.modality) {
+            Some(existing) => {
+                existing.token_count = existing.token_count.saturating_add(detail.token_count)
+            }
+            None => into.push(detail),
+        }
+    }
+    into
+}
+
+impl UsageMetadata {
+    /// Returns the token count for `modality` in the prompt breakdown, if reported
+    pub fn prompt_tokens_for(&self, modality: &Modality) -> Option<u32> {
+        self.prompt_tokens_details
+            .iter()
+            .find(|d| &d.modality == modality)
+            .map(|d| d.token_count)
+    }
+
+    /// Returns the token count for `modality` in the candidates breakdown, if reported
+    pub fn candidates_tokens_for(&self, modality: &Modality) -> Option<u32> {
+        self.candidates_tokens_details
+            .iter()
+            .find(|d| &d.modality == modality)
+            .map(|d| d.token_count)
+    }
+}
+
+impl std::ops::Add for UsageMetadata {
+    type Output = UsageMetadata;
+
+    fn add(self, other: UsageMetadata) -> UsageMetadata {
+        UsageMetadata {
+            prompt_token_count: add_counts(self.prompt_token_count, other.prompt_token_count),
+            cached_content_token_count: add_counts(
+                self.cached_content_token_count,
+                other.cached_content_token_count,
+            ),
+            candidates_token_count: add_counts(
+                self.candidates_token_count,
+                other.candidates_token_count,
+            ),
+            tool_use_prompt_token_count: add_counts(
+                self.tool_use_prompt_token_count,
+                other.tool_use_prompt_token_count,
+            ),
+            thoughts_token_count: add_counts(self.thoughts_token_count, other.thoughts_token_count),
+            total_token_count: add_counts(self.total_token_count, other.total_token_count),
+            prompt_tokens_details: merge_details(
+                self.prompt_tokens_details,
+                other.prompt_tokens_details,
+            ),
+            cache_tokens_details: merge_details(
+                self.cache_tokens_details,
+                other.cache_tokens_details,
+            ),
+            candidates_tokens_details: merge_details(
+                self.candidates_tokens_details,
+                other.candidates_tokens_details,
+            ),
+            tool_use_prompt_tokens_details: merge_details(
+                self.tool_use_prompt_tokens_details,
+                other.tool_use_prompt_tokens_details,
+            ),
+        }
+    }
+}
+
+impl std::ops::AddAssign for UsageMetadata {
+    fn add_assign(&mut self, other: UsageMetadata) {
+        *self = std::mem::take(self) + other;
+    }
+}
+
+impl std::iter::Sum for UsageMetadata {
+    fn sum<I: Iterator<Item = UsageMetadata>>(iter: I) -> UsageMetadata {
+        iter.fold(UsageMetadata::default(), |acc, usage| acc + usage)
+    }
+}
+
+/// Error describing a blocked prompt or candidate
+///
+/// Produced by [`GenerateContentResponse::blocked`] and returned by the client when
+/// its `BlockPolicy` is `BlockPolicy::Error`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockedError {
+    /// The prompt was rejected before any candidate was generated
+    PromptBlocked {
+        /// Reason the prompt was blocked
+        reason: BlockReason,
+        /// Safety ratings for the prompt
+        ratings: Vec<SafetyRating>,
+        /// Token usage reported with the blocked response
+        usage: Option<Box<UsageMetadata>>,
+    },
+    /// The candidate stopped early because its content was blocked
+    CandidateBlocked {
+        /// Reason generation stopped
+        reason: FinishReason,
+        /// Safety ratings for the candidate
+        ratings: Vec<SafetyRating>,
+        /// Token usage reported with the blocked response
+        usage: Option<Box<UsageMetadata>>,
+    },
+}
+
+impl BlockedError {
+    /// Returns the token usage reported with the blocked response, if any
+    ///
+    /// Blocked requests may still be billed, for example for the prompt tokens.
+    pub fn usage_metadata(&self) -> Option<&UsageMetadata> {
+        match self {
+            BlockedError::PromptBlocked { usage, .. }
+            | BlockedError::CandidateBlocked { usage, .. } => usage.as_deref(),
+        }
+    }
+}
+
+impl std::fmt::Display for BlockedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockedError::PromptBlocked { reason, .. } => {
+                write!(f, "Prompt was blocked: {:?}", reason)
+            }
+            BlockedError::CandidateBlocked { reason, .. } => {
+                write!(f, "Candidate was blocked: {:?}", reason)
+            }
+        }
+    }
 }
 
+impl std::error::Error for BlockedError {}
+
 impl<T> GenerateContentResponse<T> {
+    /// Returns why the response was blocked, if it was
+    ///
+    /// A response is blocked when `prompt_feedback` carries a `block_reason`, or when
+    /// the first candidate stopped with a blocking `FinishReason` (see
+    /// [`FinishReason::is_blocked`]).
+    pub fn blocked(&self) -> Option<BlockedError> {
+        if let Some(feedback) = &self.prompt_feedback
+            && let Some(reason) = &feedback.block_reason
+        {
+            return Some(BlockedError::PromptBlocked {
+                reason: reason.clone(),
+                ratings: feedback.safety_ratings.clone(),
+                usage: self.usage_metadata.clone().map(Box::new),
+            });
+        }
+
+        let candidate = self.first_candidate()?;
+        match &candidate.finish_reason {
+            Some(reason) if reason.is_blocked() => Some(BlockedError::CandidateBlocked {
+                reason: reason.clone(),
+                ratings: candidate.safety_ratings.clone(),
+                usage: self.usage_metadata.clone().map(Box::new),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the prompt or the first candidate was blocked
+    pub fn is_blocked(&self) -> bool {
+        self.blocked().is_some()
+    }
+
+    /// Converts a blocked response into a `BlockedError`
+    ///
+    /// # Errors
+    ///
+    /// Returns the `BlockedError` from [`blocked`](Self::blocked) if the response was blocked.
+    pub fn check_blocked(self) -> Result<Self, BlockedError> {
+        match self.blocked() {
+            Some(blocked) => Err(blocked),
+            None => Ok(self),
+        }
+    }
+
     /// Returns the first candidate, if any
     pub fn first_candidate(&self) -> Option<&Candidate<T>> {
         self.candidates.first()
@@ -92,12 +774,68 @@ impl<T> GenerateContentResponse<T> {
     pub fn first_text(&self) -> Option<&T> {
         self.first_content().and_then(|c| c.first_text())
     }
+
+    /// Returns the first candidate's thought summaries joined together, if any
+    ///
+    /// Thought summaries are only returned when `ThinkingConfig::include_thoughts` is set.
+    pub fn thought_summary(&self) -> Option<String> {
+        let thoughts: Vec<&str> = self.first_content()?.thoughts().collect();
+        (!thoughts.is_empty()).then(|| thoughts.concat())
+    }
+
+    /// Returns the first candidate's transcript of text, code and execution results
+    ///
+    /// See [`Content::transcript`]. Returns an empty list if there are no candidates.
+    pub fn transcript(&self) -> Vec<TranscriptEntry<'_, T>> {
+        self.first_content()
+            .map(Content::transcript)
+            .unwrap_or_default()
+    }
+
+    /// Returns the grounding metadata of the first candidate, if any
+    pub fn grounding_metadata(&self) -> Option<&GroundingMetadata> {
+        self.first_candidate()?.grounding_metadata.as_ref()
+    }
+
+    /// Returns the token usage of this response, or zero usage if none was reported
+    pub fn usage(&self) -> UsageMetadata {
+        self.usage_metadata.clone().unwrap_or_default()
+    }
+}
+
+impl GenerateContentResponse<String> {
+    /// Returns the first candidate's answer text with inline citation markers
+    ///
+    /// Thought summaries are left out. Markers number the sources in
+    /// `GroundingMetadata::grounding_chunks` starting at 1. Without grounding metadata
+    /// the plain answer text is returned.
+    pub fn text_with_citations(&self) -> Option<String> {
+        let candidate = self.first_candidate()?;
+        let mut rendered = String::new();
+        let mut has_text = false;
+
+        for (index, part) in candidate.content.parts().iter().enumerate() {
+            let Some(text) = part.text() else {
+                continue;
+            };
+            has_text = true;
+            match &candidate.grounding_metadata {
+                Some(metadata) => {
+                    let index = i32::try_from(index).unwrap_or(i32::MAX);
+                    rendered.push_str(&metadata.render_citations(index, text));
+                }
+                None => rendered.push_str(text),
+            }
+        }
+
+        has_text.then_some(rendered)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::dto_content::{JsonString, Part};
+    use crate::dto_content::{Json, JsonString, Part};
 
     #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
     struct TestSchema {
@@ -119,7 +857,8 @@ mod tests {
             }]
         }"#;
 
-        let response: GenerateContentResponse<TestSchema> = serde_json::from_str(json).unwrap();
+        let response: GenerateContentResponse<Json<TestSchema>> =
+            serde_json::from_str(json).unwrap();
         assert_eq!(response.candidates.len(), 1);
         assert_eq!(response.first_text().unwrap().name, "Alice");
         assert_eq!(response.first_text().unwrap().age, 30);
@@ -134,8 +873,7 @@ mod tests {
                         .text(JsonString::new("Hello".to_string()))
                         .build(),
                 ]),
-                finish_reason: None,
-                safety_ratings: vec![],
+                ..Default::default()
             }],
             prompt_feedback: None,
             usage_metadata: None,
@@ -176,7 +914,7 @@ mod tests {
         }"#;
 
         // Deserialize directly into typed response
-        let typed_response: GenerateContentResponse<TestSchema> =
+        let typed_response: GenerateContentResponse<Json<TestSchema>> =
             serde_json::from_str(json).unwrap();
 
         // Verify structure is preserved
@@ -185,7 +923,7 @@ mod tests {
 
         // Verify first candidate
         let first = &typed_response.candidates[0];
-        assert_eq!(first.finish_reason, Some("STOP".to_string()));
+        assert_eq!(first.finish_reason, Some(FinishReason::Stop));
         let first_schema = first.content.parts()[0].text().unwrap();
         assert_eq!(first_schema.name, "Alice");
         assert_eq!(first_schema.age, 30);
@@ -221,7 +959,7 @@ mod tests {
             }
         }"#;
 
-        let typed_response: GenerateContentResponse<TestSchema> =
+        let typed_response: GenerateContentResponse<Json<TestSchema>> =
             serde_json::from_str(json).unwrap();
 
         // All metadata should be preserved
@@ -236,7 +974,7 @@ mod tests {
         );
         assert_eq!(
             typed_response.candidates[0].finish_reason,
-            Some("MAX_TOKENS".to_string())
+            Some(FinishReason::MaxTokens)
         );
     }
 
@@ -260,4 +998,438 @@ mod tests {
             Some(&"Plain text response".to_string())
         );
     }
+
+    #[test]
+    fn test_finish_reason_deserialization() {
+        let reasons: Vec<FinishReason> =
+            serde_json::from_str(r#"["STOP", "SAFETY", "MALFORMED_FUNCTION_CALL", "SPII"]"#)
+                .unwrap();
+
+        assert_eq!(
+            reasons,
+            vec![
+                FinishReason::Stop,
+                FinishReason::Safety,
+                FinishReason::MalformedFunctionCall,
+                FinishReason::Spii,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_finish_reason_unknown_fallback() {
+        let reason: FinishReason = serde_json::from_str(r#""BRAND_NEW_REASON""#).unwrap();
+        assert_eq!(
+            reason,
+            FinishReason::Unknown("BRAND_NEW_REASON".to_string())
+        );
+        assert_eq!(
+            serde_json::to_string(&reason).unwrap(),
+            r#""BRAND_NEW_REASON""#
+        );
+    }
+
+    #[test]
+    fn test_prompt_feedback_block_reason() {
+        let json = r#"{
+            "block_reason": "PROHIBITED_CONTENT",
+            "safety_ratings": [
+                {"category": "HARM_CATEGORY_DANGEROUS_CONTENT", "probability": "HIGH", "blocked": true}
+            ]
+        }"#;
+
+        let feedback: PromptFeedback = serde_json::from_str(json).unwrap();
+        assert_eq!(feedback.block_reason, Some(BlockReason::ProhibitedContent));
+        assert_eq!(feedback.safety_ratings.len(), 1);
+        assert!(feedback.safety_ratings[0].blocked);
+    }
+
+    #[test]
+    fn test_blocked_prompt_detection() {
+        let json = r#"{
+            "prompt_feedback": {
+                "block_reason": "SAFETY",
+                "safety_ratings": [
+                    {"category": "HARM_CATEGORY_HARASSMENT", "probability": "HIGH", "blocked": true}
+                ]
+            }
+        }"#;
+
+        let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
+        assert!(response.candidates.is_empty());
+        assert!(response.is_blocked());
+
+        match response.check_blocked().unwrap_err() {
+            BlockedError::PromptBlocked {
+                reason, ratings, ..
+            } => {
+                assert_eq!(reason, BlockReason::Safety);
+                assert_eq!(ratings.len(), 1);
+            }
+            other => panic!("Expected PromptBlocked, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_blocked_candidate_detection() {
+        // Blocked candidates may omit their content entirely
+        let json = r#"{
+            "candidates": [{
+                "finish_reason": "RECITATION",
+                "safety_ratings": []
+            }]
+        }"#;
+
+        let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.first_text(), None);
+        assert_eq!(
+            response.blocked(),
+            Some(BlockedError::CandidateBlocked {
+                reason: FinishReason::Recitation,
+                ratings: vec![],
+                usage: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_blocked_detection_from_api_response() {
+        // Field names exactly as returned by the REST API
+        let json = r#"{
+            "promptFeedback": {
+                "blockReason": "SAFETY",
+                "safetyRatings": [
+                    {"category": "HARM_CATEGORY_HARASSMENT", "probability": "HIGH", "blocked": true}
+                ]
+            },
+            "usageMetadata": {"promptTokenCount": 8, "totalTokenCount": 8}
+        }"#;
+
+        let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
+        assert!(response.is_blocked());
+        let error = response.check_blocked().unwrap_err();
+        assert!(matches!(
+            error,
+            BlockedError::PromptBlocked {
+                reason: BlockReason::Safety,
+                ..
+            }
+        ));
+        assert_eq!(
+            error
+                .usage_metadata()
+                .and_then(|usage| usage.prompt_token_count),
+            Some(8)
+        );
+
+        let json = r#"{
+            "candidates": [{
+                "finishReason": "SAFETY",
+                "safetyRatings": [
+                    {"category": "HARM_CATEGORY_DANGEROUS_CONTENT", "probability": "MEDIUM", "blocked": true}
+                ],
+                "index": 0
+            }],
+            "modelVersion": "gemini-2.5-flash"
+        }"#;
+
+        let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
+        let candidate = &response.candidates[0];
+        assert_eq!(candidate.finish_reason, Some(FinishReason::Safety));
+        assert_eq!(candidate.safety_ratings.len(), 1);
+        assert!(matches!(
+            response.blocked(),
+            Some(BlockedError::CandidateBlocked {
+                reason: FinishReason::Safety,
+                ..
+            })
+        ));
+
+        let serialized = serde_json::to_value(&response).unwrap();
+        assert!(serialized["candidates"][0].get("finishReason").is_some());
+    }
+
+    #[test]
+    fn test_unblocked_response_passes_check() {
+        let json = r#"{
+            "candidates": [{
+                "content": {"role": "model", "parts": [{"text": "Hi"}]},
+                "finish_reason": "MAX_TOKENS"
+            }]
+        }"#;
+
+        let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
+        assert!(!response.is_blocked());
+        assert!(response.check_blocked().is_ok());
+    }
+
+    #[test]
+    fn test_usage_metadata_full_camel_case() {
+        let json = r#"{
+            "candidates": [],
+            "usageMetadata": {
+                "promptTokenCount": 120,
+                "cachedContentTokenCount": 100,
+                "candidatesTokenCount": 30,
+                "toolUsePromptTokenCount": 5,
+                "thoughtsTokenCount": 40,
+                "totalTokenCount": 195,
+                "promptTokensDetails": [
+                    {"modality": "TEXT", "tokenCount": 20},
+                    {"modality": "IMAGE", "tokenCount": 100}
+                ],
+                "cacheTokensDetails": [{"modality": "IMAGE", "tokenCount": 100}],
+                "candidatesTokensDetails": [{"modality": "TEXT", "tokenCount": 30}]
+            }
+        }"#;
+
+        let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
+        let usage = response.usage();
+
+        assert_eq!(usage.prompt_token_count, Some(120));
+        assert_eq!(usage.cached_content_token_count, Some(100));
+        assert_eq!(usage.tool_use_prompt_token_count, Some(5));
+        assert_eq!(usage.thoughts_token_count, Some(40));
+        assert_eq!(usage.prompt_tokens_for(&Modality::Image), Some(100));
+        assert_eq!(usage.candidates_tokens_for(&Modality::Text), Some(30));
+        assert_eq!(usage.cache_tokens_details.len(), 1);
+        assert!(usage.tool_use_prompt_tokens_details.is_empty());
+    }
+
+    #[test]
+    fn test_usage_metadata_addition() {
+        let first = UsageMetadata {
+            prompt_token_count: Some(10),
+            candidates_token_count: Some(5),
+            total_token_count: Some(15),
+            prompt_tokens_details: vec![ModalityTokenCount {
+                modality: Modality::Text,
+                token_count: 10,
+            }],
+            ..Default::default()
+        };
+        let second = UsageMetadata {
+            prompt_token_count: Some(20),
+            thoughts_token_count: Some(8),
+            total_token_count: Some(28),
+            prompt_tokens_details: vec![
+                ModalityTokenCount {
+                    modality: Modality::Text,
+                    token_count: 12,
+                },
+                ModalityTokenCount {
+                    modality: Modality::Audio,
+                    token_count: 8,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let total = first.clone() + second.clone();
+        assert_eq!(total.prompt_token_count, Some(30));
+        assert_eq!(total.candidates_token_count, Some(5));
+        assert_eq!(total.thoughts_token_count, Some(8));
+        assert_eq!(total.cached_content_token_count, None);
+        assert_eq!(total.total_token_count, Some(43));
+        assert_eq!(total.prompt_tokens_for(&Modality::Text), Some(22));
+        assert_eq!(total.prompt_tokens_for(&Modality::Audio), Some(8));
+
+        let summed: UsageMetadata = vec![first.clone(), second].into_iter().sum();
+        assert_eq!(summed, total);
+
+        let mut accumulated = UsageMetadata::default();
+        accumulated += first;
+        assert_eq!(accumulated.total_token_count, Some(15));
+    }
+
+    #[test]
+    fn test_usage_metadata_add_saturates() {
+        let large = UsageMetadata {
+            total_token_count: Some(u32::MAX - 1),
+            prompt_tokens_details: vec![ModalityTokenCount {
+                modality: Modality::Text,
+                token_count: u32::MAX,
+            }],
+            ..Default::default()
+        };
+
+        let total = large.clone() + large;
+        assert_eq!(total.total_token_count, Some(u32::MAX));
+        assert_eq!(total.prompt_tokens_for(&Modality::Text), Some(u32::MAX));
+    }
+
+    #[test]
+    fn test_thought_summary_separated_from_answer() {
+        let json = r#"{
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"text": "First I consider ", "thought": true},
+                        {"text": "the question.", "thought": true},
+                        {"text": "The answer is 42."}
+                    ]
+                }
+            }]
+        }"#;
+
+        let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.first_text().unwrap(), "The answer is 42.");
+        assert_eq!(
+            response.thought_summary().unwrap(),
+            "First I consider the question."
+        );
+
+        let without_thoughts: GenerateContentResponse = serde_json::from_str(
+            r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "Hi"}]}}]}"#,
+        )
+        .unwrap();
+        assert!(without_thoughts.thought_summary().is_none());
+    }
+
+    #[test]
+    fn test_grounding_metadata_deserialization() {
+        let json = r#"{
+            "candidates": [{
+                "content": {"role": "model", "parts": [{"text": "Spain won Euro 2024."}]},
+                "groundingMetadata": {
+                    "webSearchQueries": ["euro 2024 winner"],
+                    "searchEntryPoint": {"renderedContent": "<div></div>"},
+                    "groundingChunks": [
+                        {"web": {"uri": "https://example.com/a", "title": "a.com"}},
+                        {"web": {"uri": "https://example.com/b", "title": "b.com"}}
+                    ],
+                    "groundingSupports": [{
+                        "segment": {"endIndex": 20, "text": "Spain won Euro 2024."},
+                        "groundingChunkIndices": [0, 1],
+                        "confidenceScores": [0.9, 0.7]
+                    }]
+                },
+                "urlContextMetadata": {
+                    "urlMetadata": [{
+                        "retrievedUrl": "https://example.com/a",
+                        "urlRetrievalStatus": "URL_RETRIEVAL_STATUS_SUCCESS"
+                    }]
+                }
+            }]
+        }"#;
+
+        let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
+        let metadata = response.grounding_metadata().unwrap();
+        assert_eq!(metadata.web_search_queries, vec!["euro 2024 winner"]);
+        assert_eq!(
+            metadata
+                .search_entry_point
+                .as_ref()
+                .unwrap()
+                .rendered_content,
+            Some("<div></div>".to_string())
+        );
+        assert_eq!(
+            metadata.grounding_chunks[1].web.as_ref().unwrap().title,
+            Some("b.com".to_string())
+        );
+        let segment = metadata.grounding_supports[0].segment.as_ref().unwrap();
+        assert_eq!((segment.part_index, segment.start_index), (0, 0));
+
+        let url_metadata = &response.candidates[0]
+            .url_context_metadata
+            .as_ref()
+            .unwrap()
+            .url_metadata[0];
+        assert_eq!(
+            url_metadata.url_retrieval_status,
+            Some(UrlRetrievalStatus::UrlRetrievalStatusSuccess)
+        );
+
+        assert_eq!(
+            response.text_with_citations().unwrap(),
+            "Spain won Euro 2024.[1][2]"
+        );
+    }
+
+    #[test]
+    fn test_render_citations() {
+        let support = |start: i32, end: i32, chunks: Vec<i32>| GroundingSupport {
+            segment: Some(Segment {
+                part_index: 0,
+                start_index: start,
+                end_index: end,
+                text: None,
+            }),
+            grounding_chunk_indices: chunks,
+            confidence_scores: vec![],
+        };
+        let metadata = GroundingMetadata {
+            grounding_supports: vec![
+                support(14, 25, vec![2]),
+                support(0, 13, vec![0]),
+                // Out of range and unsupported segments are ignored
+                support(0, 100, vec![1]),
+                support(0, 5, vec![]),
+            ],
+            ..Default::default()
+        };
+
+        let text = "Café opened. It is busy.";
+        assert_eq!(
+            metadata.render_citations(0, text),
+            "Café opened.[1] It is busy.[3]"
+        );
+        assert_eq!(metadata.render_citations(1, text), text);
+
+        // Offsets inside a multi-byte character move to the next boundary
+        let metadata = GroundingMetadata {
+            grounding_supports: vec![support(0, 4, vec![0])],
+            ..Default::default()
+        };
+        assert_eq!(
+            metadata.render_citations(0, text),
+            "Café[1] opened. It is busy."
+        );
+    }
+
+    #[test]
+    fn test_logprobs_and_citations_deserialization() {
+        let json = r#"{
+            "candidates": [{
+                "content": {"role": "model", "parts": [{"text": "{\"n\": 7}"}]},
+                "avgLogprobs": -0.05,
+                "logprobsResult": {
+                    "topCandidates": [
+                        {"candidates": [{"token": "7", "logProbability": -0.05}, {"token": "8", "logProbability": -3.1}]}
+                    ],
+                    "chosenCandidates": [
+                        {"token": "{\"n\": ", "tokenId": 1, "logProbability": 0.0},
+                        {"token": "7", "tokenId": 2, "logProbability": -0.05},
+                        {"token": "}", "tokenId": 3, "logProbability": 0.0}
+                    ]
+                },
+                "citationMetadata": {
+                    "citationSources": [{"startIndex": 0, "endIndex": 8, "uri": "https://github.com/a/b", "license": "mit"}]
+                }
+            }]
+        }"#;
+
+        let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
+        let candidate = response.first_candidate().unwrap();
+        assert_eq!(candidate.avg_logprobs, Some(-0.05));
+
+        let logprobs = candidate.logprobs_result.as_ref().unwrap();
+        assert_eq!(logprobs.top_candidates[0].candidates[1].token, "8");
+        assert_eq!(logprobs.chosen_candidates[1].token_id, Some(2));
+
+        let source = &candidate
+            .citation_metadata
+            .as_ref()
+            .unwrap()
+            .citation_sources[0];
+        assert_eq!(source.end_index, Some(8));
+        assert_eq!(source.license.as_deref(), Some("mit"));
+
+        let confidence = candidate.field_confidence();
+        assert_eq!(confidence.len(), 1);
+        assert_eq!(confidence[0].path, "/n");
+        assert!((confidence[0].probability - (-0.05f64).exp()).abs() < 1e-6);
+    }
 }
@@ -0,0 +1,555 @@
+//! Strategies choosing which part of a chat's history is sent with each turn
+//!
+//! A chat keeps its full history, but long conversations eventually exceed the
+//! model's context window. A [`HistoryStrategy`] selects the contents sent for
+//! the next turn:
+//!
+//! - [`FullHistory`] sends everything, the default
+//! - [`SlidingWindow`] keeps the most recent turns
+//! - [`TokenBudget`] drops the oldest turns until the prompt fits a token budget,
+//!   counted with `countTokens` or the local [`TokenEstimator`]
+//! - [`RollingSummary`] asks the model to compress older turns into a summary
+//!
+//! Strategies only cut history where a turn starts (see [`turn_starts`]), so a
+//! function call is never separated from its response.
+//!
+//! ```rust,ignore
+//! use gemini::{GeminiChat, TokenBudget, TokenEstimator};
+//!
+//! let mut chat = GeminiChat::builder(client)
+//!     .history_strategy(TokenBudget::new(TokenEstimator, 100_000))
+//!     .build();
+//! ```
+
+use std::error::Error;
+
+use crate::api::{GeminiApi, GeminiTokensApi};
+use crate::dto_content::{Content, JsonString, Part};
+use crate::dto_request::GenerateContentRequest;
+
+/// Selects the history sent with each turn of a chat
+///
+/// Strategies are `Clone` so a forked chat gets its own copy, state included, and
+/// `Sync` so a chat can be shared across tasks.
+#[async_trait::async_trait]
+pub trait HistoryStrategy: Send + Sync + HistoryStrategyClone {
+    /// Returns the contents to send for the next turn
+    ///
+    /// `history` is the full conversation ending with the new user message, which
+    /// must be part of the result.
+    async fn select(&mut self, history: Vec<Content>) -> Result<Vec<Content>, Box<dyn Error>>;
+
+    /// Forgets any state derived from earlier history, called when a chat's history is
+    /// cleared or the chat continues from another branch
+    fn reset(&mut self) {}
+}
+
+/// Clones a boxed [`HistoryStrategy`], implemented for every `Clone` strategy
+pub trait HistoryStrategyClone {
+    /// Returns a boxed copy of the strategy
+    fn clone_box(&self) -> Box<dyn HistoryStrategy>;
+}
+
+impl<S> HistoryStrategyClone for S
+where
+    S: HistoryStrategy + Clone + 'static,
+{
+    fn clone_box(&self) -> Box<dyn HistoryStrategy> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn HistoryStrategy> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Returns the indices at which a turn starts
+///
+/// A turn starts at every user message that is not a function response, so cutting
+/// history at one of these indices never separates a function call from its response.
+pub fn turn_starts(history: &[Content]) -> Vec<usize> {
+    history
+        .iter()
+        .enumerate()
+        .filter(|(_, content)| {
+            content.is_user()
+                && !content
+                    .parts()
+                    .iter()
+                    .any(|part| part.function_response().is_some())
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Sends the full history with every turn
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FullHistory;
+
+#[async_trait::async_trait]
+impl HistoryStrategy for FullHistory {
+    async fn select(&mut self, history: Vec<Content>) -> Result<Vec<Content>, Box<dyn Error>> {
+        Ok(history)
+    }
+}
+
+/// Sends only the most recent turns
+#[derive(Debug, Clone, Copy)]
+pub struct SlidingWindow {
+    max_turns: usize,
+}
+
+impl SlidingWindow {
+    /// Keeps the last `max_turns` turns, including the new message
+    pub fn new(max_turns: usize) -> Self {
+        Self {
+            max_turns: max_turns.max(1),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HistoryStrategy for SlidingWindow {
+    async fn select(&mut self, mut history: Vec<Content>) -> Result<Vec<Content>, Box<dyn Error>> {
+        let starts = turn_starts(&history);
+        if starts.len() > self.max_turns {
+            history.drain(..starts[starts.len() - self.max_turns]);
+        }
+        Ok(history)
+    }
+}
+
+/// Counts the tokens of a prompt
+#[async_trait::async_trait]
+pub trait TokenCounter: Send + Sync {
+    /// Returns the number of tokens `contents` take up as a prompt
+    async fn count(&self, contents: &[Content]) -> Result<i32, Box<dyn Error>>;
+}
+
+/// Counts exactly with the `countTokens` endpoint, at the cost of a request per count
+#[async_trait::async_trait]
+impl<A> TokenCounter for A
+where
+    A: GeminiTokensApi + Send + Sync,
+{
+    async fn count(&self, contents: &[Content]) -> Result<i32, Box<dyn Error>> {
+        self.count_tokens(contents).await
+    }
+}
+
+/// Estimates token counts locally, without any request
+///
+/// Counts a token per four characters of text and JSON, and a fixed cost per media
+/// part and content. Good enough for budgeting, not for billing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenEstimator;
+
+impl TokenEstimator {
+    /// Characters of text per token
+    const CHARS_PER_TOKEN: usize = 4;
+    /// Tokens of an image at default resolution, also used for other media
+    const MEDIA_TOKENS: usize = 258;
+    /// Tokens of the role and framing of each content
+    const CONTENT_TOKENS: usize = 4;
+
+    /// Returns the estimated number of tokens of `contents`
+    pub fn estimate(contents: &[Content]) -> i32 {
+        let text_tokens = |len: usize| len.div_ceil(Self::CHARS_PER_TOKEN);
+        let tokens: usize = contents
+            .iter()
+            .map(|content| {
+                let parts: usize = content
+                    .parts()
+                    .iter()
+                    .map(|part| {
+                        if let Some(text) = part.text() {
+                            text_tokens(text.chars().count())
+                        } else if let Some(thought) = part.thought_text() {
+                            text_tokens(thought.chars().count())
+                        } else if let Some(call) = part.function_call() {
+                            let args = call.args.as_ref().map_or(0, |args| args.to_string().len());
+                            text_tokens(call.name.len() + args)
+                        } else if let Some(response) = part.function_response() {
+                            text_tokens(response.name.len() + response.response.to_string().len())
+                        } else if part.inline_data().is_some() || part.file_data().is_some() {
+                            Self::MEDIA_TOKENS
+                        } else {
+                            0
+                        }
+                    })
+                    .sum();
+                Self::CONTENT_TOKENS + parts
+            })
+            .sum();
+        i32::try_from(tokens).unwrap_or(i32::MAX)
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenCounter for TokenEstimator {
+    async fn count(&self, contents: &[Content]) -> Result<i32, Box<dyn Error>> {
+        Ok(Self::estimate(contents))
+    }
+}
+
+/// Drops the oldest turns until the prompt fits a token budget
+///
+/// Only the contents are counted; leave room for the system instruction, tools and
+/// the response. The new message is always sent, even if it exceeds the budget alone.
+/// With a counter making requests, finding the cut takes a logarithmic number of counts.
+#[derive(Debug, Clone)]
+pub struct TokenBudget<C> {
+    counter: C,
+    max_tokens: i32,
+}
+
+impl<C: TokenCounter> TokenBudget<C> {
+    /// Limits prompts to `max_tokens` as counted by `counter`
+    pub fn new(counter: C, max_tokens: i32) -> Self {
+        Self {
+            counter,
+            max_tokens,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: TokenCounter + Clone + 'static> HistoryStrategy for TokenBudget<C> {
+    async fn select(&mut self, mut history: Vec<Content>) -> Result<Vec<Content>, Box<dyn Error>> {
+        if self.counter.count(&history).await? <= self.max_tokens {
+            return Ok(history);
+        }
+
+        let starts = turn_starts(&history);
+        if starts.is_empty() {
+            return Ok(history);
+        }
+
+        // Find the earliest turn start whose suffix fits, keeping at least the last turn
+        let (mut low, mut high) = (0, starts.len() - 1);
+        while low < high {
+            let mid = (low + high) / 2;
+            if self.counter.count(&history[starts[mid]..]).await? <= self.max_tokens {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        history.drain(..starts[low]);
+        Ok(history)
+    }
+}
+
+/// Compresses older turns into a summary written by the model
+///
+/// Once more than `max_turns` turns follow the current summary, every turn but the
+/// last `keep_turns` is summarized, together with the previous summary. The summary is
+/// sent at the start of the first kept user message; the chat's own history is
+/// never modified.
+///
+/// The summary is requested through its own API client, which may use a smaller
+/// model than the chat.
+#[derive(Clone)]
+pub struct RollingSummary<A> {
+    api: A,
+    max_turns: usize,
+    keep_turns: usize,
+    prompt: String,
+    summary: Option<String>,
+    summarized: usize,
+}
+
+impl<A> RollingSummary<A>
+where
+    A: GeminiApi + Send + Sync,
+{
+    /// Prompt asking for the summary, followed by the transcript
+    const DEFAULT_PROMPT: &'static str = "Summarize the conversation below so it can be \
+        continued without it. Keep names, facts, decisions, preferences and open questions. \
+        Reply with the summary only.";
+
+    /// Summarizes older turns once more than `max_turns` are unsummarized, keeping the
+    /// last `keep_turns` verbatim
+    pub fn new(api: A, max_turns: usize, keep_turns: usize) -> Self {
+        let max_turns = max_turns.max(1);
+        Self {
+            api,
+            max_turns,
+            keep_turns: keep_turns.clamp(1, max_turns),
+            prompt: Self::DEFAULT_PROMPT.to_string(),
+            summary: None,
+            summarized: 0,
+        }
+    }
+
+    /// Replaces the instruction sent with the transcript to summarize
+    #[must_use]
+    pub fn with_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// Returns the current summary of older turns, if any were summarized
+    pub fn summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+
+    /// Asks the model to summarize `contents` together with the previous summary
+    async fn summarize(&self, contents: &[Content]) -> Result<String, Box<dyn Error>> {
+        let mut transcript = String::new();
+        if let Some(summary) = &self.summary {
+            transcript.push_str(&format!(
+                "Summary of the conversation before:\n{summary}\n\n"
+            ));
+        }
+        for content in contents {
+            let role = if content.is_user() { "User" } else { "Model" };
+            for part in content.parts() {
+                if let Some(text) = part.text() {
+                    transcript.push_str(&format!("{role}: {text}\n"));
+                } else if let Some(call) = part.function_call() {
+                    transcript.push_str(&format!("{role} called function {}\n", call.name));
+                } else if let Some(response) = part.function_response() {
+                    transcript.push_str(&format!(
+                        "Function {} returned: {}\n",
+                        response.name, response.response
+                    ));
+                }
+            }
+        }
+
+        let request = GenerateContentRequest::<String>::builder()
+            .add_content(Content::user(vec![
+                Part::builder()
+                    .text(JsonString::new(format!("{}\n\n{transcript}", self.prompt)))
+                    .build(),
+            ]))
+            .build();
+        let response = self.api.generate_content(request).await?;
+        let summary = response
+            .first_text()
+            .ok_or("Summary response contained no text")?;
+        Ok(summary.trim().to_string())
+    }
+}
+
+#[async_trait::async_trait]
+impl<A> HistoryStrategy for RollingSummary<A>
+where
+    A: GeminiApi + Send + Sync + Clone + 'static,
+{
+    async fn select(&mut self, mut history: Vec<Content>) -> Result<Vec<Content>, Box<dyn Error>> {
+        if history.len() < self.summarized {
+            // The history was replaced by a shorter one
+            self.reset();
+        }
+
+        let starts: Vec<usize> = turn_starts(&history)
+            .into_iter()
+            .filter(|&start| start >= self.summarized)
+            .collect();
+        if starts.len() > self.max_turns {
+            let cut = starts[starts.len() - self.keep_turns];
+            let summary = self.summarize(&history[self.summarized..cut]).await?;
+            self.summary = Some(summary);
+            self.summarized = cut;
+        }
+
+        history.drain(..self.summarized);
+        if let Some(summary) = &self.summary
+            && let Some(first) = history.first_mut()
+        {
+            first.parts_mut().insert(
+                0,
+                Part::builder()
+                    .text(JsonString::new(format!(
+                        "Summary of our earlier conversation:\n{summary}"
+                    )))
+                    .build(),
+            );
+        }
+        Ok(history)
+    }
+
+    fn reset(&mut self) {
+        self.summary = None;
+        self.summarized = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto_content::{FunctionCall, FunctionResponse};
+    use crate::dto_response::GenerateContentResponse;
+    use std::sync::{Arc, Mutex};
+
+    fn text(role_is_user: bool, text: &str) -> Content {
+        let parts = vec![
+            Part::builder()
+                .text(JsonString::new(text.to_string()))
+                .build(),
+        ];
+        if role_is_user {
+            Content::user(parts)
+        } else {
+            Content::model(parts)
+        }
+    }
+
+    fn conversation(turns: usize) -> Vec<Content> {
+        (0..turns)
+            .flat_map(|i| [text(true, &format!("q{i}")), text(false, &format!("a{i}"))])
+            .collect()
+    }
+
+    fn texts(contents: &[Content]) -> Vec<String> {
+        contents
+            .iter()
+            .flat_map(|content| content.parts())
+            .filter_map(|part| part.text().cloned())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_sliding_window_keeps_function_calls_with_responses() {
+        let mut history = conversation(2);
+        history.push(text(true, "weather?"));
+        history.push(Content::model(vec![
+            Part::builder()
+                .function_call(FunctionCall {
+                    name: "weather".to_string(),
+                    args: None,
+                })
+                .build(),
+        ]));
+        history.push(Content::user(vec![
+            Part::builder()
+                .function_response(FunctionResponse {
+                    name: "weather".to_string(),
+                    response: serde_json::json!({"sky": "clear"}),
+                })
+                .build(),
+        ]));
+        history.push(text(false, "Clear skies"));
+        history.push(text(true, "thanks"));
+
+        assert_eq!(turn_starts(&history), vec![0, 2, 4, 8]);
+
+        let selected = SlidingWindow::new(2).select(history).await.unwrap();
+        assert_eq!(selected.len(), 5);
+        assert_eq!(texts(&selected)[0], "weather?");
+        assert!(selected[1].parts()[0].function_call().is_some());
+
+        let selected = SlidingWindow::new(10)
+            .select(conversation(3))
+            .await
+            .unwrap();
+        assert_eq!(selected.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_token_budget_drops_oldest_turns() {
+        let mut history = conversation(4);
+        history.push(text(true, "last"));
+        // Every content of the conversation is estimated at 5 tokens
+        assert_eq!(TokenEstimator::estimate(&history), 45);
+
+        let mut budget = TokenBudget::new(TokenEstimator, 20);
+        let selected = budget.select(history.clone()).await.unwrap();
+        assert_eq!(texts(&selected), vec!["q3", "a3", "last"]);
+
+        let mut budget = TokenBudget::new(TokenEstimator, 1);
+        let selected = budget.select(history.clone()).await.unwrap();
+        assert_eq!(texts(&selected), vec!["last"]);
+
+        let mut budget = TokenBudget::new(TokenEstimator, 100);
+        assert_eq!(budget.select(history).await.unwrap().len(), 9);
+    }
+
+    #[tokio::test]
+    async fn test_rolling_summary_summarizes_older_turns_once() {
+        #[derive(Clone)]
+        struct MockApi {
+            prompts: Arc<Mutex<Vec<String>>>,
+        }
+        #[async_trait::async_trait]
+        impl GeminiApi for MockApi {
+            async fn generate_content<T>(
+                &self,
+                request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn Error>>
+            where
+                T: crate::dto_content::TextPayload + Send + 'static,
+            {
+                let prompt = texts(request.contents()).concat();
+                let mut prompts = self.prompts.lock().unwrap();
+                prompts.push(prompt);
+                let json = serde_json::json!({
+                    "candidates": [{"content": {"role": "model", "parts": [
+                        {"text": format!("summary {}", prompts.len())}
+                    ]}}]
+                });
+                Ok(serde_json::from_value(json)?)
+            }
+        }
+
+        let prompts = Arc::new(Mutex::new(Vec::new()));
+        let mut strategy = RollingSummary::new(
+            MockApi {
+                prompts: prompts.clone(),
+            },
+            4,
+            2,
+        );
+
+        // Within the limit nothing is summarized
+        let mut history = conversation(3);
+        history.push(text(true, "q3"));
+        let selected = strategy.select(history.clone()).await.unwrap();
+        assert_eq!(selected.len(), 7);
+        assert!(prompts.lock().unwrap().is_empty());
+
+        // Five turns: the first three are summarized, the last two kept
+        history.push(text(false, "a3"));
+        history.push(text(true, "q4"));
+        let selected = strategy.select(history.clone()).await.unwrap();
+        assert_eq!(
+            texts(&selected),
+            vec![
+                "Summary of our earlier conversation:\nsummary 1",
+                "q3",
+                "a3",
+                "q4"
+            ]
+        );
+        assert!(prompts.lock().unwrap()[0].contains("User: q2\nModel: a2\n"));
+        assert_eq!(strategy.summary(), Some("summary 1"));
+
+        // The summary is reused until more than four turns follow it again
+        for i in 4..6 {
+            history.push(text(false, &format!("a{i}")));
+            history.push(text(true, &format!("q{}", i + 1)));
+            let selected = strategy.select(history.clone()).await.unwrap();
+            assert_eq!(texts(&selected)[1], "q3");
+        }
+        assert_eq!(prompts.lock().unwrap().len(), 1);
+
+        history.push(text(false, "a6"));
+        history.push(text(true, "q7"));
+        let selected = strategy.select(history.clone()).await.unwrap();
+        assert_eq!(texts(&selected)[1..], ["q6", "a6", "q7"]);
+        let prompts = prompts.lock().unwrap();
+        assert_eq!(prompts.len(), 2);
+        assert!(prompts[1].contains("summary 1"));
+        assert!(prompts[1].contains("User: q5\nModel: a5\n"));
+        assert!(!prompts[1].contains("q2"));
+        drop(prompts);
+
+        strategy.reset();
+        assert_eq!(strategy.summary(), None);
+    }
+}
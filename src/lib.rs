@@ -9,7 +9,18 @@
 //! - Generic text parsing for structured JSON responses
 //! - Configurable via environment variables or explicit configuration
 //! - Streaming and non-streaming content generation
-//! - Chat wrappers for managing multi-turn conversations with automatic history
+//! - Chat wrappers for managing multi-turn conversations with automatic history and
+//!   session-wide defaults
+//! - History strategies keeping long chats within the context window
+//! - Branching chat history with regeneration, edits and forks
+//! - Saving chat sessions to JSON files or SQLite via `ChatStore`
+//! - Polling of long-running operations with backoff, deadlines and cancellation
+//! - Cost estimation from token usage and spending limits via `Budget`
+//! - Media parts from files and bytes, uploading large payloads via the Files API
+//! - Image and speech generation with typed configs and output helpers
+//! - Derived schemas normalized to the subset the Gemini API supports
+//! - Validation and repair of structured responses against their schema
+//! - Typed enum responses for classification via `enum_response::<E>()`
 //!
 //! ## Example Usage
 //!
@@ -46,7 +57,7 @@
 //! }
 //!
 //! // Create typed request with auto-derived schema using builder pattern
-//! let request: GenerateContentRequest<Character> = GenerateContentRequest::builder()
+//! let request: GenerateContentRequest<Json<Character>> = GenerateContentRequest::builder()
 //!     .add_content(Content::user(vec![
 //!         Part::builder()
 //!             .text(JsonString::new("Create a character".to_string()))
@@ -59,8 +70,8 @@
 //!     )
 //!     .build();
 //!
-//! // Response is automatically typed as Character!
-//! let response: GenerateContentResponse<Character> = api.generate_content(request)?;
+//! // Response is automatically typed as Json<Character>, which derefs to Character!
+//! let response: GenerateContentResponse<Json<Character>> = api.generate_content(request)?;
 //!
 //! // Access the typed data directly
 //! if let Some(character) = response.first_text() {
@@ -128,26 +139,87 @@
 
 // Public module exports
 pub mod api;
+pub mod audio;
+pub mod budget;
 pub mod chat;
+pub mod chat_tree;
 pub mod client;
+pub mod confidence;
 pub mod config;
 pub mod dto_content;
+pub mod dto_file;
 pub mod dto_request;
 pub mod dto_response;
+pub mod history;
+pub mod media;
+pub mod operation;
+pub mod pricing;
+pub mod schema;
+pub mod store;
 pub mod stream_ext;
+pub mod transcription;
+pub mod validation;
 
 // Re-export commonly used types
-pub use api::{BoxResponseStream, GeminiApi, GeminiStreamingApi};
-pub use chat::{BufferedChatStream, GeminiChat, GeminiStreamChat};
+pub use api::{
+    BoxResponseStream, FILE_PROCESSING_TIMEOUT, GeminiApi, GeminiFilesApi, GeminiOperationsApi,
+    GeminiStreamingApi, GeminiTokensApi, StreamTextMode,
+};
+pub use audio::PcmAudio;
+pub use budget::{Budget, BudgetExceededError};
+pub use chat::{
+    BufferedChatStream, GeminiChat, GeminiChatBuilder, GeminiStreamChat, GeminiStreamChatBuilder,
+    INCOMPLETE_TURN_MARKER, PartialTurnPolicy,
+};
+pub use chat_tree::{BranchError, ChatNode, ChatTree};
 pub use client::GeminiV1Beta;
-pub use config::GeminiConfig;
+pub use confidence::FieldConfidence;
+pub use config::{BlockPolicy, GeminiConfig};
 pub use dto_content::{
-    Blob, CodeExecutionResult, Content, ExecutableCode, FileData, FunctionCall, FunctionResponse,
-    JsonString, Part, PartBuilder, VideoMetadata,
+    Blob, BlobData, CodeExecutionResult, Content, Enum, ExecutableCode, FileData, FunctionCall,
+    FunctionResponse, Json, JsonString, Language, Outcome, Part, PartBuilder, TextPayload,
+    TranscriptEntry, VideoMetadata,
 };
+pub use dto_file::{File, FileState};
 pub use dto_request::{
-    GenerateContentRequest, GenerateContentRequestBuilder, GenerationConfig,
-    GenerationConfigBuilder, MimeType, ResponseMimeType, SafetyRating, SafetySetting,
+    AspectRatio, CodeExecution, GenerateContentRequest, GenerateContentRequestBuilder,
+    GenerationConfig, GenerationConfigBuilder, GoogleSearch, HarmBlockThreshold, HarmCategory,
+    HarmProbability, ImageConfig, ImageSize, MediaResolution, MimeType, MultiSpeakerVoiceConfig,
+    ParseMimeTypeError, PrebuiltVoiceConfig, ResponseMimeType, ResponseModality, SafetyRating,
+    SafetySetting, SpeakerVoiceConfig, SpeechConfig, ThinkingConfig, ThinkingLevel, Tool,
+    UrlContext, VoiceConfig,
+};
+pub use dto_response::{
+    BlockReason, BlockedError, Candidate, CitationMetadata, CitationSource, FinishReason,
+    GenerateContentResponse, GroundingChunk, GroundingMetadata, GroundingSupport,
+    LogprobsCandidate, LogprobsResult, Modality, ModalityTokenCount, PromptFeedback,
+    SearchEntryPoint, Segment, TopCandidates, UrlContextMetadata, UrlMetadata, UrlRetrievalStatus,
+    UsageMetadata, WebSource,
+};
+pub use history::{
+    FullHistory, HistoryStrategy, HistoryStrategyClone, RollingSummary, SlidingWindow, TokenBudget,
+    TokenCounter, TokenEstimator, turn_starts,
+};
+pub use media::{
+    GeneratedImage, MAX_INLINE_BYTES, MediaCategory, UnsupportedMediaError, accepted_media,
+};
+pub use operation::{
+    CancellationToken, Operation, OperationError, OperationPoller, OperationStatus,
+};
+pub use pricing::{Cost, ModelPricing, PricingTable, TokenRates};
+pub use schema::{
+    NormalizedSchema, Schema, SchemaDialect, SchemaIssue, StringFormat, UnsupportedSchemaError,
+    normalize_schema,
+};
+#[cfg(feature = "sqlite")]
+pub use store::SqliteStore;
+pub use store::{
+    ChatSession, ChatStore, InvalidSessionIdError, JsonFileStore, SESSION_FORMAT_VERSION,
+    SessionFormatError, SessionInfo,
+};
+pub use stream_ext::{BoxError, BoxStream, IntoInnerStreamExt, ThinkingEvent, ThinkingStreamExt};
+pub use transcription::{TimedSegment, Transcription};
+pub use validation::{
+    LocalFix, RepairDiagnostics, RepairStrategy, ResponseValidationError, ValidatedResponse,
+    repair_json, validate_json,
 };
-pub use dto_response::{Candidate, GenerateContentResponse, PromptFeedback, UsageMetadata};
-pub use stream_ext::{BoxError, BoxStream, IntoInnerStreamExt};
@@ -0,0 +1,661 @@
+//! Building media `Part`s from files, bytes and readers.
+//!
+//! MIME types are detected from magic bytes first and from the file extension
+//! second. Small payloads are sent inline as base64 `Blob`s; payloads above
+//! [`MAX_INLINE_BYTES`] must go through the Files API, which
+//! [`crate::api::GeminiFilesApi::part_from_bytes`] does automatically.
+//! [`Part::from_path`] and [`Part::from_reader`] have no client to upload with and
+//! reject such payloads instead.
+//!
+//! Not every model accepts every kind of media. [`accepted_media`] lists the
+//! categories each known model takes as input, and
+//! [`GenerateContentRequest::check_media_support`] validates a request against it
+//! before it is sent.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::dto_content::{Blob, FileData, Part};
+use super::dto_request::{GenerateContentRequest, MimeType};
+use super::dto_response::GenerateContentResponse;
+
+/// Largest payload sent inline, in raw bytes
+///
+/// Requests are limited to 20 MB in total; base64 encoding grows data by a third,
+/// which leaves room for the prompt and other parts.
+pub const MAX_INLINE_BYTES: usize = 14 * 1024 * 1024;
+
+impl MimeType {
+    /// Detects a MIME type from the leading bytes of a file
+    pub fn sniff(data: &[u8]) -> Option<MimeType> {
+        let riff_kind = (data.len() >= 12 && &data[..4] == b"RIFF").then(|| &data[8..12]);
+        let form_kind = (data.len() >= 12 && &data[..4] == b"FORM").then(|| &data[8..12]);
+        let ftyp_brand = (data.len() >= 12 && &data[4..8] == b"ftyp").then(|| &data[8..12]);
+
+        let mime_type = match data {
+            [0x89, b'P', b'N', b'G', ..] => MimeType::ImagePng,
+            [0xFF, 0xD8, 0xFF, ..] => MimeType::ImageJpeg,
+            _ if riff_kind == Some(b"WEBP") => MimeType::ImageWebp,
+            _ if riff_kind == Some(b"WAVE") => MimeType::AudioWav,
+            _ if riff_kind == Some(b"AVI ") => MimeType::VideoAvi,
+            _ if form_kind == Some(b"AIFF") || form_kind == Some(b"AIFC") => MimeType::AudioAiff,
+            _ if ftyp_brand.is_some() => match ftyp_brand? {
+                b"heic" | b"heix" | b"hevc" | b"hevx" => MimeType::ImageHeic,
+                b"mif1" | b"msf1" => MimeType::ImageHeif,
+                b"qt  " => MimeType::VideoMov,
+                [b'3', b'g', ..] => MimeType::Video3gpp,
+                b"M4A " => MimeType::AudioAac,
+                _ => MimeType::VideoMp4,
+            },
+            [b'%', b'P', b'D', b'F', b'-', ..] => MimeType::ApplicationPdf,
+            [b'f', b'L', b'a', b'C', ..] => MimeType::AudioFlac,
+            [b'O', b'g', b'g', b'S', ..] => MimeType::AudioOgg,
+            [b'I', b'D', b'3', ..] => MimeType::AudioMp3,
+            [0xFF, 0xF1 | 0xF9, ..] => MimeType::AudioAac,
+            [0xFF, 0xFB | 0xF3 | 0xF2, ..] => MimeType::AudioMp3,
+            [0x1A, 0x45, 0xDF, 0xA3, ..] => MimeType::VideoWebm,
+            [b'F', b'L', b'V', ..] => MimeType::VideoFlv,
+            [0x00, 0x00, 0x01, 0xBA | 0xB3, ..] => MimeType::VideoMpeg,
+            [0x30, 0x26, 0xB2, 0x75, ..] => MimeType::VideoWmv,
+            [b'{', b'\\', b'r', b't', b'f', ..] => MimeType::ApplicationRtf,
+            [b'G', b'I', b'F', b'8', ..] => MimeType::Other("image/gif".to_string()),
+            _ => return None,
+        };
+        Some(mime_type)
+    }
+
+    /// Maps a file extension, without the dot, to a MIME type
+    pub fn from_extension(extension: &str) -> Option<MimeType> {
+        let mime_type = match extension.to_ascii_lowercase().as_str() {
+            "png" => MimeType::ImagePng,
+            "jpg" | "jpeg" => MimeType::ImageJpeg,
+            "webp" => MimeType::ImageWebp,
+            "heic" => MimeType::ImageHeic,
+            "heif" => MimeType::ImageHeif,
+            "wav" => MimeType::AudioWav,
+            "mp3" => MimeType::AudioMp3,
+            "aif" | "aiff" => MimeType::AudioAiff,
+            "aac" | "m4a" => MimeType::AudioAac,
+            "ogg" | "oga" => MimeType::AudioOgg,
+            "flac" => MimeType::AudioFlac,
+            "mp4" | "m4v" => MimeType::VideoMp4,
+            "mpeg" => MimeType::VideoMpeg,
+            "mpg" => MimeType::VideoMpg,
+            "mov" => MimeType::VideoMov,
+            "avi" => MimeType::VideoAvi,
+            "flv" => MimeType::VideoFlv,
+            "webm" => MimeType::VideoWebm,
+            "wmv" => MimeType::VideoWmv,
+            "3gp" | "3gpp" => MimeType::Video3gpp,
+            "pdf" => MimeType::ApplicationPdf,
+            "txt" => MimeType::TextPlain,
+            "html" | "htm" => MimeType::TextHtml,
+            "css" => MimeType::TextCss,
+            "js" | "mjs" => MimeType::TextJavascript,
+            "ts" => MimeType::TextTypescript,
+            "csv" => MimeType::TextCsv,
+            "md" | "markdown" => MimeType::TextMarkdown,
+            "py" => MimeType::TextPython,
+            "json" => MimeType::ApplicationJson,
+            "xml" => MimeType::TextXml,
+            "rtf" => MimeType::ApplicationRtf,
+            "gif" => MimeType::Other("image/gif".to_string()),
+            _ => return None,
+        };
+        Some(mime_type)
+    }
+
+    /// Detects a MIME type from magic bytes, falling back to the path's extension
+    pub fn detect(data: &[u8], path: Option<&Path>) -> Option<MimeType> {
+        MimeType::sniff(data).or_else(|| {
+            path.and_then(Path::extension)
+                .and_then(|extension| extension.to_str())
+                .and_then(MimeType::from_extension)
+        })
+    }
+
+    /// Returns the type without parameters, e.g. `audio/L16` for `audio/L16;rate=24000`
+    pub fn essence(&self) -> &str {
+        self.as_str().split(';').next().unwrap_or_default().trim()
+    }
+
+    /// Returns the value of a MIME parameter, matching its name case-insensitively
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.as_str().split(';').skip(1).find_map(|parameter| {
+            let (key, value) = parameter.split_once('=')?;
+            key.trim()
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim().trim_matches('"'))
+        })
+    }
+
+    /// Returns the usual file extension, without the dot
+    pub fn extension(&self) -> Option<&'static str> {
+        let extension = match self {
+            MimeType::ImagePng => "png",
+            MimeType::ImageJpeg => "jpg",
+            MimeType::ImageWebp => "webp",
+            MimeType::ImageHeic => "heic",
+            MimeType::ImageHeif => "heif",
+            MimeType::AudioWav => "wav",
+            MimeType::AudioMp3 | MimeType::AudioMpeg => "mp3",
+            MimeType::AudioAiff => "aiff",
+            MimeType::AudioAac => "aac",
+            MimeType::AudioOgg => "ogg",
+            MimeType::AudioFlac => "flac",
+            MimeType::VideoMp4 => "mp4",
+            MimeType::VideoMpeg => "mpeg",
+            MimeType::VideoMov => "mov",
+            MimeType::VideoAvi => "avi",
+            MimeType::VideoFlv => "flv",
+            MimeType::VideoMpg => "mpg",
+            MimeType::VideoWebm => "webm",
+            MimeType::VideoWmv => "wmv",
+            MimeType::Video3gpp => "3gp",
+            MimeType::ApplicationPdf => "pdf",
+            MimeType::TextPlain => "txt",
+            MimeType::TextHtml => "html",
+            MimeType::TextCss => "css",
+            MimeType::TextJavascript | MimeType::ApplicationJavascript => "js",
+            MimeType::TextTypescript | MimeType::ApplicationTypescript => "ts",
+            MimeType::TextCsv => "csv",
+            MimeType::TextMarkdown => "md",
+            MimeType::TextPython | MimeType::ApplicationPythonCode => "py",
+            MimeType::ApplicationJson => "json",
+            MimeType::TextXml => "xml",
+            MimeType::ApplicationRtf | MimeType::TextRtf => "rtf",
+            MimeType::Other(mime_type) if mime_type == "image/gif" => "gif",
+            MimeType::Other(_) => return None,
+        };
+        Some(extension)
+    }
+
+    /// Returns the broad category of the media, or `None` for unrecognized types
+    ///
+    /// Text and application types, such as PDFs, source code and office formats,
+    /// count as documents.
+    pub fn category(&self) -> Option<MediaCategory> {
+        let (kind, _) = self.as_str().split_once('/')?;
+        match kind {
+            "image" => Some(MediaCategory::Image),
+            "audio" => Some(MediaCategory::Audio),
+            "video" => Some(MediaCategory::Video),
+            "text" | "application" => Some(MediaCategory::Document),
+            _ => None,
+        }
+    }
+
+    /// Returns true for image types
+    pub fn is_image(&self) -> bool {
+        self.category() == Some(MediaCategory::Image)
+    }
+
+    /// Returns true for audio types
+    pub fn is_audio(&self) -> bool {
+        self.category() == Some(MediaCategory::Audio)
+    }
+
+    /// Returns true for video types
+    pub fn is_video(&self) -> bool {
+        self.category() == Some(MediaCategory::Video)
+    }
+
+    /// Returns true for document types, including plain text and source code
+    pub fn is_document(&self) -> bool {
+        self.category() == Some(MediaCategory::Document)
+    }
+}
+
+/// Broad category of media accepted as model input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaCategory {
+    /// Still images
+    Image,
+    /// Audio recordings
+    Audio,
+    /// Video, with or without an audio track
+    Video,
+    /// PDFs, plain text, source code and similar documents
+    Document,
+}
+
+const ALL_MEDIA: &[MediaCategory] = &[
+    MediaCategory::Image,
+    MediaCategory::Audio,
+    MediaCategory::Video,
+    MediaCategory::Document,
+];
+
+/// Media categories accepted as input by known models
+const MODEL_MEDIA: &[(&str, &[MediaCategory])] = &[
+    ("gemini-3-pro", ALL_MEDIA),
+    ("gemini-2.5-pro", ALL_MEDIA),
+    ("gemini-2.5-flash", ALL_MEDIA),
+    ("gemini-2.5-flash-lite", ALL_MEDIA),
+    ("gemini-2.5-flash-image", &[MediaCategory::Image]),
+    ("gemini-2.5-flash-preview-tts", &[]),
+    ("gemini-2.5-pro-preview-tts", &[]),
+    (
+        "gemini-2.5-flash-native-audio",
+        &[
+            MediaCategory::Image,
+            MediaCategory::Audio,
+            MediaCategory::Video,
+        ],
+    ),
+    ("gemini-2.0-flash", ALL_MEDIA),
+    ("gemini-2.0-flash-lite", ALL_MEDIA),
+    (
+        "gemini-2.0-flash-preview-image-generation",
+        &[MediaCategory::Image],
+    ),
+    ("gemini-1.5-pro", ALL_MEDIA),
+    ("gemini-1.5-flash", ALL_MEDIA),
+    ("gemini-embedding-001", &[]),
+];
+
+/// Returns the media categories a model accepts as input
+///
+/// Accepts names with a `models/` prefix. Versioned names such as
+/// `gemini-2.5-flash-preview-05-20` fall back to the longest known name they
+/// extend. Returns `None` for unknown models.
+pub fn accepted_media(model: &str) -> Option<&'static [MediaCategory]> {
+    let model = model.strip_prefix("models/").unwrap_or(model);
+
+    MODEL_MEDIA
+        .iter()
+        .filter(|(name, _)| {
+            model
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('-'))
+        })
+        .max_by_key(|(name, _)| name.len())
+        .map(|(_, categories)| *categories)
+}
+
+/// Error returned when a request contains media a model does not accept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedMediaError {
+    /// The model the request was checked against
+    pub model: String,
+    /// The first MIME type the model does not accept
+    pub mime_type: MimeType,
+}
+
+impl std::fmt::Display for UnsupportedMediaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Model {} does not accept {} input",
+            self.model, self.mime_type
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedMediaError {}
+
+impl<T> GenerateContentRequest<T> {
+    /// Checks that `model` accepts every inline and file media part in the request
+    ///
+    /// Requests for models missing from [`accepted_media`] always pass, as do
+    /// file parts without a MIME type.
+    ///
+    /// # Errors
+    /// Returns the first media type the model does not accept.
+    pub fn check_media_support(&self, model: &str) -> Result<(), UnsupportedMediaError> {
+        let Some(accepted) = accepted_media(model) else {
+            return Ok(());
+        };
+
+        let mime_types = self
+            .contents()
+            .iter()
+            .chain(self.system_instruction())
+            .flat_map(|content| content.parts())
+            .filter_map(|part| {
+                part.inline_data()
+                    .map(|blob| &blob.mime_type)
+                    .or_else(|| part.file_data()?.mime_type.as_ref())
+            });
+
+        for mime_type in mime_types {
+            let is_accepted = mime_type
+                .category()
+                .is_some_and(|category| accepted.contains(&category));
+            if !is_accepted {
+                return Err(UnsupportedMediaError {
+                    model: model.to_string(),
+                    mime_type: mime_type.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An image generated by the model
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedImage {
+    /// MIME type of the image, usually `image/png`
+    pub mime_type: MimeType,
+    /// Decoded image bytes
+    pub data: bytes::Bytes,
+}
+
+impl GeneratedImage {
+    /// Writes the image to a file
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, &self.data)
+    }
+}
+
+impl<T> GenerateContentResponse<T> {
+    /// Returns the images in the first candidate, in order
+    ///
+    /// Images are requested with `ResponseModality::Image`. Images attached to
+    /// thought parts are left out.
+    ///
+    /// # Errors
+    /// Returns an error if an image is not valid base64.
+    pub fn images(&self) -> Result<Vec<GeneratedImage>, base64::DecodeError> {
+        let Some(content) = self.first_content() else {
+            return Ok(Vec::new());
+        };
+
+        content
+            .parts()
+            .iter()
+            .filter(|part| !part.is_thought())
+            .filter_map(Part::inline_data)
+            .filter(|blob| blob.mime_type.is_image())
+            .map(|blob| {
+                Ok(GeneratedImage {
+                    mime_type: blob.mime_type.clone(),
+                    data: blob.data.to_bytes()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Writes the images in the first candidate to `dir` and returns their paths
+    ///
+    /// Files are named `image-1.png`, `image-2.png` and so on, with the extension
+    /// taken from the MIME type. The directory is created if needed and existing
+    /// files are overwritten.
+    ///
+    /// # Errors
+    /// Returns an error if an image cannot be decoded or written.
+    pub fn save_images(&self, dir: impl AsRef<Path>) -> std::io::Result<Vec<PathBuf>> {
+        let images = self
+            .images()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        if images.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        images
+            .iter()
+            .enumerate()
+            .map(|(index, image)| {
+                let extension = image.mime_type.extension().unwrap_or("bin");
+                let path = dir.join(format!("image-{}.{extension}", index + 1));
+                image.save(&path)?;
+                Ok(path)
+            })
+            .collect()
+    }
+}
+
+/// Error returned when the MIME type of media cannot be determined
+fn unknown_mime_type(path: Option<&Path>) -> std::io::Error {
+    let message = match path {
+        Some(path) => format!("Cannot determine MIME type of {}", path.display()),
+        None => "Cannot determine MIME type of data".to_string(),
+    };
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+/// Error returned when media is too large to be sent inline
+fn too_large_for_inline(path: Option<&Path>) -> std::io::Error {
+    let source = match path {
+        Some(path) => path.display().to_string(),
+        None => "Data".to_string(),
+    };
+    let message = format!(
+        "{source} exceeds the inline limit of {MAX_INLINE_BYTES} bytes; \
+         upload it with GeminiFilesApi::part_from_path instead"
+    );
+    std::io::Error::new(std::io::ErrorKind::FileTooLarge, message)
+}
+
+impl<T> Part<T> {
+    /// Creates an inline media part from raw bytes
+    ///
+    /// The bytes are base64-encoded only when the request is serialized.
+    pub fn from_bytes(data: impl Into<bytes::Bytes>, mime_type: MimeType) -> Self {
+        Part::builder()
+            .inline_data(Blob::new(mime_type, data))
+            .build()
+    }
+
+    /// Creates a part that references media by URI
+    ///
+    /// Accepts Files API URIs and public YouTube URLs, for which `mime_type` may be
+    /// left out. Combine with [`Part::clipped`] to limit a video to a time range.
+    pub fn from_uri(uri: impl Into<String>, mime_type: Option<MimeType>) -> Self {
+        Part::builder()
+            .file_data(FileData {
+                mime_type,
+                file_uri: uri.into(),
+            })
+            .build()
+    }
+
+    /// Creates an inline media part from a file, detecting its MIME type
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, its type is not recognized, or
+    /// it is larger than [`MAX_INLINE_BYTES`].
+    pub fn from_path(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        if std::fs::metadata(path)?.len() > MAX_INLINE_BYTES as u64 {
+            return Err(too_large_for_inline(Some(path)));
+        }
+        let data = std::fs::read(path)?;
+        let mime_type =
+            MimeType::detect(&data, Some(path)).ok_or_else(|| unknown_mime_type(Some(path)))?;
+        Ok(Part::from_bytes(data, mime_type))
+    }
+
+    /// Creates an inline media part from a reader
+    ///
+    /// When `mime_type` is `None`, it is detected from the data.
+    ///
+    /// # Errors
+    /// Returns an error if reading fails, the type is not given and not recognized,
+    /// or the reader yields more than [`MAX_INLINE_BYTES`].
+    pub fn from_reader(reader: impl Read, mime_type: Option<MimeType>) -> std::io::Result<Self> {
+        let mut data = Vec::new();
+        reader
+            .take(MAX_INLINE_BYTES as u64 + 1)
+            .read_to_end(&mut data)?;
+        if data.len() > MAX_INLINE_BYTES {
+            return Err(too_large_for_inline(None));
+        }
+        let mime_type = match mime_type {
+            Some(mime_type) => mime_type,
+            None => MimeType::sniff(&data).ok_or_else(|| unknown_mime_type(None))?,
+        };
+        Ok(Part::from_bytes(data, mime_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_magic_bytes() {
+        let cases: Vec<(&[u8], MimeType)> = vec![
+            (b"\x89PNG\r\n\x1a\n", MimeType::ImagePng),
+            (b"\xFF\xD8\xFF\xE0", MimeType::ImageJpeg),
+            (b"RIFF\0\0\0\0WEBPVP8 ", MimeType::ImageWebp),
+            (b"RIFF\0\0\0\0WAVEfmt ", MimeType::AudioWav),
+            (b"\0\0\0\x18ftypheic", MimeType::ImageHeic),
+            (b"\0\0\0\x18ftypisom", MimeType::VideoMp4),
+            (b"\0\0\0\x14ftypqt  ", MimeType::VideoMov),
+            (b"%PDF-1.7", MimeType::ApplicationPdf),
+            (b"fLaC\0\0", MimeType::AudioFlac),
+            (b"ID3\x04", MimeType::AudioMp3),
+            (b"\x1A\x45\xDF\xA3", MimeType::VideoWebm),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(MimeType::sniff(data), Some(expected));
+        }
+        assert_eq!(
+            MimeType::sniff(b"GIF89a"),
+            Some(MimeType::Other("image/gif".to_string()))
+        );
+        assert_eq!(MimeType::sniff(b"plain text"), None);
+        assert_eq!(MimeType::sniff(b""), None);
+    }
+
+    #[test]
+    fn test_detect_falls_back_to_extension() {
+        let path = Path::new("notes/README.MD");
+        assert_eq!(
+            MimeType::detect(b"# Title", Some(path)),
+            Some(MimeType::TextMarkdown)
+        );
+        assert_eq!(
+            MimeType::detect(b"%PDF-1.4", Some(Path::new("file.txt"))),
+            Some(MimeType::ApplicationPdf)
+        );
+        assert_eq!(MimeType::detect(b"data", Some(Path::new("file.xyz"))), None);
+    }
+
+    #[test]
+    fn test_part_from_reader_and_path() {
+        let part: Part = Part::from_reader(&b"\x89PNG\r\n\x1a\n"[..], None).unwrap();
+        let blob = part.inline_data().unwrap();
+        assert_eq!(blob.mime_type, MimeType::ImagePng);
+        assert_eq!(blob.data.to_bytes().unwrap().len(), 8);
+
+        let part: Part = Part::from_reader(&b"a,b\n1,2"[..], Some(MimeType::TextCsv)).unwrap();
+        assert_eq!(part.inline_data().unwrap().mime_type, MimeType::TextCsv);
+
+        assert!(Part::<String>::from_reader(&b"???"[..], None).is_err());
+
+        let path =
+            std::env::temp_dir().join(format!("gemini-media-test-{}.csv", std::process::id()));
+        std::fs::write(&path, "a,b\n1,2").unwrap();
+        let part: Part = Part::from_path(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(part.inline_data().unwrap().mime_type, MimeType::TextCsv);
+    }
+
+    #[test]
+    fn test_part_from_reader_rejects_oversized_data() {
+        let reader = std::io::repeat(0).take(MAX_INLINE_BYTES as u64 + 1);
+        let error = Part::<String>::from_reader(reader, Some(MimeType::VideoMp4)).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::FileTooLarge);
+
+        let reader = std::io::repeat(0).take(MAX_INLINE_BYTES as u64);
+        assert!(Part::<String>::from_reader(reader, Some(MimeType::VideoMp4)).is_ok());
+    }
+
+    #[test]
+    fn test_categories() {
+        assert!(MimeType::ImagePng.is_image());
+        assert!(MimeType::AudioFlac.is_audio());
+        assert!(MimeType::Video3gpp.is_video());
+        assert!(MimeType::ApplicationPdf.is_document());
+        assert!(MimeType::TextPython.is_document());
+
+        let gif: MimeType = "image/gif".parse().unwrap();
+        assert!(gif.is_image());
+        let docx: MimeType =
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+                .parse()
+                .unwrap();
+        assert!(docx.is_document());
+        assert_eq!(MimeType::Other("font/woff2".to_string()).category(), None);
+    }
+
+    #[test]
+    fn test_accepted_media() {
+        assert_eq!(accepted_media("models/gemini-2.5-flash"), Some(ALL_MEDIA));
+        assert_eq!(
+            accepted_media("gemini-2.5-flash-image-preview"),
+            Some(&[MediaCategory::Image][..])
+        );
+        assert_eq!(
+            accepted_media("gemini-2.5-flash-preview-tts"),
+            Some(&[][..])
+        );
+        assert_eq!(accepted_media("gemini-2.5-flashy"), None);
+        assert_eq!(accepted_media("unknown-model"), None);
+    }
+
+    #[test]
+    fn test_check_media_support() {
+        use crate::dto_content::Content;
+
+        let request: GenerateContentRequest = GenerateContentRequest::builder()
+            .add_content(Content::user(vec![
+                Part::from_bytes(&b"\x89PNG"[..], MimeType::ImagePng),
+                Part::from_bytes(&b"RIFF"[..], MimeType::AudioWav),
+            ]))
+            .build();
+
+        assert!(request.check_media_support("gemini-2.5-pro").is_ok());
+        assert!(request.check_media_support("my-tuned-model").is_ok());
+
+        let error = request
+            .check_media_support("gemini-2.5-flash-image")
+            .unwrap_err();
+        assert_eq!(error.mime_type, MimeType::AudioWav);
+        assert_eq!(
+            error.to_string(),
+            "Model gemini-2.5-flash-image does not accept audio/wav input"
+        );
+    }
+
+    #[test]
+    fn test_response_images() {
+        use crate::dto_content::{Content, JsonString};
+        use crate::dto_response::Candidate;
+
+        let png = b"\x89PNG\r\n\x1a\nimage";
+        let response = GenerateContentResponse::<String> {
+            candidates: vec![Candidate {
+                content: Content::model(vec![
+                    Part::builder()
+                        .text(JsonString::new("Here is a cat:".to_string()))
+                        .build(),
+                    Part::from_bytes(&png[..], MimeType::ImagePng),
+                    Part::from_bytes(&b"RIFF"[..], MimeType::AudioWav),
+                ]),
+                ..Default::default()
+            }],
+            prompt_feedback: None,
+            usage_metadata: None,
+        };
+
+        let images = response.images().unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].mime_type, MimeType::ImagePng);
+        assert_eq!(&images[0].data[..], &png[..]);
+
+        let dir = std::env::temp_dir().join(format!("gemini-images-test-{}", std::process::id()));
+        let paths = response.save_images(&dir).unwrap();
+        assert_eq!(paths, vec![dir.join("image-1.png")]);
+        assert_eq!(std::fs::read(&paths[0]).unwrap(), png);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,674 @@
+//! Long-running operation polling for the Gemini API
+//!
+//! Several Gemini endpoints (batches, file processing, tuned models) do not return
+//! their result directly. Instead they return an [`Operation`] resource that has to be
+//! polled until it reports `done`. This module provides the generic `Operation<T>`
+//! DTO and an [`OperationPoller`] that drives the polling loop with a configurable
+//! interval, exponential backoff, deadline and cancellation.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use gemini::{GeminiOperationsApi, GeminiV1Beta};
+//! use std::time::Duration;
+//!
+//! let client = GeminiV1Beta::from_env()?;
+//! let operation = client.get_operation::<serde_json::Value>("batches/abc123").await?;
+//!
+//! // Poll until the operation completes, giving up after ten minutes
+//! let result = client
+//!     .poll_operation(operation)
+//!     .interval(Duration::from_secs(2))
+//!     .max_interval(Duration::from_secs(30))
+//!     .deadline(Duration::from_secs(600))
+//!     .await?;
+//! ```
+
+use std::error::Error;
+use std::future::{Future, IntoFuture};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::GeminiOperationsApi;
+
+/// A long-running operation returned by the Gemini API
+///
+/// Generic over the response type `T`, which defaults to `serde_json::Value`.
+/// The `response` field is only populated once `done` is true and the operation succeeded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation<T = serde_json::Value> {
+    /// Server-assigned resource name, e.g. `operations/abc123` or `batches/abc123`
+    pub name: String,
+
+    /// Service-specific progress metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+
+    /// Whether the operation has finished
+    #[serde(default)]
+    pub done: bool,
+
+    /// Error status if the operation failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<OperationStatus>,
+
+    /// Result of the operation if it succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<T>,
+}
+
+/// Error status reported by a failed operation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OperationStatus {
+    /// The status code (a `google.rpc.Code` value)
+    #[serde(default)]
+    pub code: i32,
+
+    /// Developer-facing error message
+    #[serde(default)]
+    pub message: String,
+
+    /// Additional error details
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<serde_json::Value>,
+}
+
+impl<T> Operation<T> {
+    /// Returns the resource name of the operation
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns true if the operation has finished, successfully or not
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Converts a finished operation into its result
+    ///
+    /// # Errors
+    ///
+    /// Returns `OperationError::Failed` if the operation reported an error status,
+    /// `OperationError::NotDone` if it is still running, and
+    /// `OperationError::MissingResponse` if it finished without a response.
+    pub fn into_result(self) -> Result<T, OperationError> {
+        if let Some(status) = self.error {
+            return Err(OperationError::Failed {
+                name: self.name,
+                status,
+            });
+        }
+
+        if !self.done {
+            return Err(OperationError::NotDone { name: self.name });
+        }
+
+        self.response
+            .ok_or(OperationError::MissingResponse { name: self.name })
+    }
+}
+
+/// Error type for long-running operation polling
+#[derive(Debug, Clone, PartialEq)]
+pub enum OperationError {
+    /// The operation finished with an error status
+    Failed {
+        /// Name of the operation
+        name: String,
+        /// Error status reported by the server
+        status: OperationStatus,
+    },
+    /// The operation did not finish before the configured deadline
+    DeadlineExceeded {
+        /// Name of the operation
+        name: String,
+        /// Time spent polling before giving up
+        elapsed: Duration,
+    },
+    /// Polling was cancelled through a `CancellationToken`
+    Cancelled {
+        /// Name of the operation
+        name: String,
+    },
+    /// The operation has not finished yet
+    NotDone {
+        /// Name of the operation
+        name: String,
+    },
+    /// The operation finished successfully but carried no response
+    MissingResponse {
+        /// Name of the operation
+        name: String,
+    },
+}
+
+impl std::fmt::Display for OperationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationError::Failed { name, status } => {
+                write!(
+                    f,
+                    "Operation {} failed with code {}: {}",
+                    name, status.code, status.message
+                )
+            }
+            OperationError::DeadlineExceeded { name, elapsed } => {
+                write!(
+                    f,
+                    "Operation {} did not complete within {:?}",
+                    name, elapsed
+                )
+            }
+            OperationError::Cancelled { name } => {
+                write!(f, "Polling of operation {} was cancelled", name)
+            }
+            OperationError::NotDone { name } => {
+                write!(f, "Operation {} has not completed yet", name)
+            }
+            OperationError::MissingResponse { name } => {
+                write!(f, "Operation {} completed without a response", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OperationError {}
+
+/// Token used to cancel an in-flight [`OperationPoller`]
+///
+/// Clones share the same state, so a token can be handed to the poller while
+/// another task keeps a clone to trigger cancellation.
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    sender: Arc<tokio::sync::watch::Sender<bool>>,
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancellationToken {
+    /// Creates a new token in the non-cancelled state
+    pub fn new() -> Self {
+        let (sender, _) = tokio::sync::watch::channel(false);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+
+    /// Requests cancellation of every poller holding this token
+    pub fn cancel(&self) {
+        self.sender.send_replace(true);
+    }
+
+    /// Returns true if cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Resolves once cancellation has been requested
+    pub async fn cancelled(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives in `self`, so the channel cannot close while we wait
+        let _ = receiver.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+/// Default delay before the first poll
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default upper bound for the delay between polls
+const DEFAULT_MAX_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Shortest delay between polls, so a zero interval does not busy-loop
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Default growth factor applied to the delay after each poll
+const DEFAULT_BACKOFF: f64 = 1.5;
+
+/// Poller that waits for a long-running operation to complete
+///
+/// Created via [`GeminiOperationsApi::poll_operation`]. Configure it with the builder
+/// methods, then `.await` it (or call [`wait`](Self::wait)) to get the operation result.
+///
+/// # Type Parameters
+/// * `'a` - Lifetime of the API client reference
+/// * `A` - The GeminiOperationsApi implementor type
+/// * `T` - The operation response type
+pub struct OperationPoller<'a, A: ?Sized, T> {
+    api: &'a A,
+    operation: Operation<T>,
+    interval: Duration,
+    max_interval: Duration,
+    backoff: f64,
+    deadline: Option<Duration>,
+    cancellation: Option<CancellationToken>,
+}
+
+impl<'a, A, T> OperationPoller<'a, A, T>
+where
+    A: GeminiOperationsApi + Sync + ?Sized,
+    T: serde::de::DeserializeOwned + Send + 'static,
+{
+    /// Creates a new poller for the given operation
+    pub fn new(api: &'a A, operation: Operation<T>) -> Self {
+        Self {
+            api,
+            operation,
+            interval: DEFAULT_INTERVAL,
+            max_interval: DEFAULT_MAX_INTERVAL,
+            backoff: DEFAULT_BACKOFF,
+            deadline: None,
+            cancellation: None,
+        }
+    }
+
+    /// Sets the delay before the first poll
+    ///
+    /// Values below 100 ms are clamped to 100 ms.
+    #[must_use]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(MIN_INTERVAL);
+        self
+    }
+
+    /// Sets the upper bound for the delay between polls
+    ///
+    /// Values below 100 ms are clamped to 100 ms.
+    #[must_use]
+    pub fn max_interval(mut self, max_interval: Duration) -> Self {
+        self.max_interval = max_interval.max(MIN_INTERVAL);
+        self
+    }
+
+    /// Sets the factor the delay is multiplied by after each poll
+    ///
+    /// Use `1.0` for a fixed interval. Values below `1.0` are clamped to `1.0`;
+    /// non-finite values are ignored and keep the current factor.
+    #[must_use]
+    pub fn backoff(mut self, multiplier: f64) -> Self {
+        if multiplier.is_finite() {
+            self.backoff = multiplier.max(1.0);
+        }
+        self
+    }
+
+    /// Sets the maximum total time to spend polling
+    #[must_use]
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets a token that can cancel polling from another task
+    ///
+    /// Cancelling only stops the local polling loop; use
+    /// [`GeminiOperationsApi::cancel_operation`] to cancel the server-side operation.
+    #[must_use]
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Computes the next delay, growing the interval by the backoff factor
+    ///
+    /// Saturates at `max_interval` instead of overflowing `Duration`.
+    fn next_delay(&self, current: Duration) -> Duration {
+        Duration::try_from_secs_f64(current.as_secs_f64() * self.backoff)
+            .map_or(self.max_interval, |delay| delay.min(self.max_interval))
+    }
+
+    /// Sleeps for `delay`, returning early with `false` if polling was cancelled
+    async fn sleep(delay: Duration, cancellation: Option<&CancellationToken>) -> bool {
+        let sleep = Box::pin(tokio::time::sleep(delay));
+
+        match cancellation {
+            Some(token) => {
+                let cancelled = Box::pin(token.cancelled());
+                matches!(
+                    futures::future::select(sleep, cancelled).await,
+                    futures::future::Either::Left(_)
+                )
+            }
+            None => {
+                sleep.await;
+                true
+            }
+        }
+    }
+
+    /// Polls the operation until it completes and returns its result
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A poll request fails
+    /// - The operation finished with an error status (`OperationError::Failed`)
+    /// - The deadline elapsed first (`OperationError::DeadlineExceeded`)
+    /// - Polling was cancelled (`OperationError::Cancelled`)
+    pub async fn wait(mut self) -> Result<T, Box<dyn Error>> {
+        let started = Instant::now();
+        let mut delay = self.interval.min(self.max_interval);
+
+        loop {
+            if self.operation.is_done() {
+                return Ok(self.operation.into_result()?);
+            }
+
+            let name = self.operation.name.clone();
+
+            if self
+                .cancellation
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled)
+            {
+                return Err(Box::new(OperationError::Cancelled { name }));
+            }
+
+            let mut wait_for = delay;
+            if let Some(deadline) = self.deadline {
+                let elapsed = started.elapsed();
+                if elapsed >= deadline {
+                    return Err(Box::new(OperationError::DeadlineExceeded { name, elapsed }));
+                }
+                wait_for = wait_for.min(deadline - elapsed);
+            }
+
+            if !Self::sleep(wait_for, self.cancellation.as_ref()).await {
+                return Err(Box::new(OperationError::Cancelled { name }));
+            }
+
+            tracing::debug!("Polling operation {} after {:?}", name, wait_for);
+            self.operation = self.api.get_operation::<T>(&name).await?;
+            delay = self.next_delay(delay);
+        }
+    }
+}
+
+impl<'a, A, T> IntoFuture for OperationPoller<'a, A, T>
+where
+    A: GeminiOperationsApi + Sync + ?Sized,
+    T: serde::de::DeserializeOwned + Send + 'static,
+{
+    type Output = Result<T, Box<dyn Error>>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.wait())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    struct BatchResult {
+        count: u32,
+    }
+
+    /// Mock API that reports the operation as done after a fixed number of polls
+    struct MockOperationsApi {
+        polls_until_done: usize,
+        polls: Mutex<usize>,
+        fail: bool,
+    }
+
+    impl MockOperationsApi {
+        fn new(polls_until_done: usize) -> Self {
+            Self {
+                polls_until_done,
+                polls: Mutex::new(0),
+                fail: false,
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl GeminiOperationsApi for MockOperationsApi {
+        async fn get_operation<T>(&self, name: &str) -> Result<Operation<T>, Box<dyn Error>>
+        where
+            T: serde::de::DeserializeOwned + Send + 'static,
+        {
+            let polls = {
+                let mut polls = self.polls.lock().unwrap();
+                *polls += 1;
+                *polls
+            };
+
+            let json = if polls < self.polls_until_done {
+                serde_json::json!({ "name": name, "done": false })
+            } else if self.fail {
+                serde_json::json!({
+                    "name": name,
+                    "done": true,
+                    "error": { "code": 13, "message": "internal error" }
+                })
+            } else {
+                serde_json::json!({
+                    "name": name,
+                    "done": true,
+                    "response": { "count": 42 }
+                })
+            };
+
+            Ok(serde_json::from_value(json)?)
+        }
+
+        async fn cancel_operation(&self, _name: &str) -> Result<(), Box<dyn Error>> {
+            Ok(())
+        }
+    }
+
+    fn pending_operation<T>() -> Operation<T> {
+        Operation {
+            name: "operations/test".to_string(),
+            metadata: None,
+            done: false,
+            error: None,
+            response: None,
+        }
+    }
+
+    #[test]
+    fn test_operation_deserialization() {
+        let json = r#"{
+            "name": "batches/123",
+            "metadata": {"@type": "type.googleapis.com/BatchMetadata", "progress": 50},
+            "done": true,
+            "response": {"count": 7}
+        }"#;
+
+        let operation: Operation<BatchResult> = serde_json::from_str(json).unwrap();
+        assert_eq!(operation.name(), "batches/123");
+        assert!(operation.is_done());
+        assert!(operation.metadata.is_some());
+        assert_eq!(operation.into_result().unwrap(), BatchResult { count: 7 });
+    }
+
+    #[test]
+    fn test_operation_pending_defaults() {
+        let operation: Operation = serde_json::from_str(r#"{"name": "operations/1"}"#).unwrap();
+        assert!(!operation.is_done());
+        assert_eq!(
+            operation.into_result().unwrap_err(),
+            OperationError::NotDone {
+                name: "operations/1".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_operation_into_result_failed() {
+        let json = r#"{
+            "name": "operations/1",
+            "done": true,
+            "error": {"code": 3, "message": "bad input"}
+        }"#;
+
+        let operation: Operation = serde_json::from_str(json).unwrap();
+        let error = operation.into_result().unwrap_err();
+        assert!(matches!(error, OperationError::Failed { ref status, .. } if status.code == 3));
+        assert!(error.to_string().contains("bad input"));
+    }
+
+    #[tokio::test]
+    async fn test_poller_waits_until_done() {
+        let api = MockOperationsApi::new(3);
+
+        let result: BatchResult = api
+            .poll_operation(pending_operation())
+            .interval(Duration::from_millis(1))
+            .await
+            .expect("Polling failed");
+
+        assert_eq!(result, BatchResult { count: 42 });
+        assert_eq!(*api.polls.lock().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_poller_returns_immediately_when_done() {
+        let api = MockOperationsApi::new(1);
+        let operation = Operation {
+            response: Some(BatchResult { count: 1 }),
+            done: true,
+            ..pending_operation()
+        };
+
+        let result = api.poll_operation(operation).wait().await.unwrap();
+        assert_eq!(result.count, 1);
+        assert_eq!(*api.polls.lock().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_poller_reports_failed_operation() {
+        let api = MockOperationsApi {
+            fail: true,
+            ..MockOperationsApi::new(1)
+        };
+
+        let error = api
+            .poll_operation(pending_operation::<BatchResult>())
+            .interval(Duration::from_millis(1))
+            .await
+            .unwrap_err();
+
+        let error = error.downcast_ref::<OperationError>().unwrap();
+        assert!(matches!(error, OperationError::Failed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_poller_deadline_exceeded() {
+        let api = MockOperationsApi::new(usize::MAX);
+
+        let error = api
+            .poll_operation(pending_operation::<BatchResult>())
+            .interval(Duration::from_millis(5))
+            .backoff(1.0)
+            .deadline(Duration::from_millis(20))
+            .await
+            .unwrap_err();
+
+        let error = error.downcast_ref::<OperationError>().unwrap();
+        assert!(matches!(error, OperationError::DeadlineExceeded { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_poller_cancellation() {
+        let api = MockOperationsApi::new(usize::MAX);
+        let token = CancellationToken::new();
+
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            canceller.cancel();
+        });
+
+        let error = api
+            .poll_operation(pending_operation::<BatchResult>())
+            .interval(Duration::from_secs(60))
+            .cancellation(token)
+            .await
+            .unwrap_err();
+
+        let error = error.downcast_ref::<OperationError>().unwrap();
+        assert!(matches!(error, OperationError::Cancelled { .. }));
+    }
+
+    #[test]
+    fn test_poller_backoff_is_capped() {
+        let api = MockOperationsApi::new(1);
+        let poller = api
+            .poll_operation(pending_operation::<BatchResult>())
+            .backoff(2.0)
+            .max_interval(Duration::from_secs(5));
+
+        assert_eq!(
+            poller.next_delay(Duration::from_secs(2)),
+            Duration::from_secs(4)
+        );
+        assert_eq!(
+            poller.next_delay(Duration::from_secs(4)),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn test_poller_backoff_rejects_invalid_factors() {
+        let api = MockOperationsApi::new(1);
+
+        for factor in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let poller = api
+                .poll_operation(pending_operation::<BatchResult>())
+                .backoff(factor);
+            assert_eq!(poller.backoff, DEFAULT_BACKOFF);
+        }
+
+        let poller = api
+            .poll_operation(pending_operation::<BatchResult>())
+            .backoff(-3.0);
+        assert_eq!(poller.backoff, 1.0);
+    }
+
+    #[test]
+    fn test_poller_intervals_are_clamped() {
+        let api = MockOperationsApi::new(1);
+        let poller = api
+            .poll_operation(pending_operation::<BatchResult>())
+            .interval(Duration::ZERO)
+            .max_interval(Duration::ZERO);
+
+        assert_eq!(poller.interval, MIN_INTERVAL);
+        assert_eq!(poller.max_interval, MIN_INTERVAL);
+        assert_eq!(poller.next_delay(poller.interval), MIN_INTERVAL);
+    }
+
+    #[test]
+    fn test_poller_backoff_saturates_on_overflow() {
+        let api = MockOperationsApi::new(1);
+        let poller = api
+            .poll_operation(pending_operation::<BatchResult>())
+            .backoff(f64::MAX)
+            .max_interval(Duration::MAX);
+
+        assert_eq!(poller.next_delay(Duration::MAX), Duration::MAX);
+        assert_eq!(poller.next_delay(Duration::from_secs(1)), Duration::MAX);
+    }
+
+    #[test]
+    fn test_cancellation_token_state() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+
+        token.cancel();
+        assert!(clone.is_cancelled());
+    }
+}
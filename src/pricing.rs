@@ -0,0 +1,411 @@
+//! Cost estimation from token usage.
+//!
+//! A `PricingTable` maps model names to `ModelPricing`, which converts the
+//! `UsageMetadata` of a response into a `Cost` in US dollars. Rates are
+//! user-configurable; `PricingTable::gemini_defaults()` provides the published
+//! paid-tier list prices at the time of writing and should be checked against
+//! the current price list before being relied on for billing.
+
+use std::collections::HashMap;
+
+use super::dto_response::{Modality, UsageMetadata};
+
+/// Number of tokens the per-token rates are quoted for
+const TOKENS_PER_RATE: f64 = 1_000_000.0;
+
+/// Prices in US dollars per million tokens
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenRates {
+    /// Rate for uncached prompt tokens
+    pub input: f64,
+    /// Rate for candidate tokens
+    pub output: f64,
+    /// Rate for thinking tokens, defaults to the output rate
+    pub thinking: Option<f64>,
+    /// Rate for prompt tokens served from the context cache, defaults to the input rate
+    pub cached_input: Option<f64>,
+    /// Rate for uncached audio prompt tokens, defaults to the input rate
+    pub audio_input: Option<f64>,
+}
+
+impl TokenRates {
+    /// Creates rates with the given input and output prices per million tokens
+    pub fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            thinking: None,
+            cached_input: None,
+            audio_input: None,
+        }
+    }
+
+    /// Sets the price per million thinking tokens
+    #[must_use]
+    pub fn with_thinking(mut self, rate: f64) -> Self {
+        self.thinking = Some(rate);
+        self
+    }
+
+    /// Sets the price per million cached prompt tokens
+    #[must_use]
+    pub fn with_cached_input(mut self, rate: f64) -> Self {
+        self.cached_input = Some(rate);
+        self
+    }
+
+    /// Sets the price per million uncached audio prompt tokens
+    #[must_use]
+    pub fn with_audio_input(mut self, rate: f64) -> Self {
+        self.audio_input = Some(rate);
+        self
+    }
+}
+
+/// Pricing of a single model, optionally tiered by prompt length
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelPricing {
+    /// Rates for prompts that fall below every tier threshold
+    rates: TokenRates,
+    /// `(threshold, rates)` pairs sorted by threshold, applied to longer prompts
+    tiers: Vec<(u32, TokenRates)>,
+}
+
+impl ModelPricing {
+    /// Creates pricing that applies the same rates to every prompt length
+    pub fn new(rates: TokenRates) -> Self {
+        Self {
+            rates,
+            tiers: Vec::new(),
+        }
+    }
+
+    /// Adds rates that apply when the prompt is longer than `prompt_tokens`
+    #[must_use]
+    pub fn with_tier(mut self, prompt_tokens: u32, rates: TokenRates) -> Self {
+        self.tiers.push((prompt_tokens, rates));
+        self.tiers.sort_by_key(|(threshold, _)| *threshold);
+        self
+    }
+
+    /// Returns the rates that apply to a prompt of the given length
+    pub fn rates_for(&self, prompt_tokens: u32) -> &TokenRates {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|(threshold, _)| prompt_tokens > *threshold)
+            .map_or(&self.rates, |(_, rates)| rates)
+    }
+
+    /// Converts the usage of a single request into its cost
+    ///
+    /// Tiers are selected per request, so usage summed across several requests
+    /// should not be priced in one call.
+    pub fn cost(&self, usage: &UsageMetadata) -> Cost {
+        let prompt = usage.prompt_token_count.unwrap_or(0);
+        let rates = self.rates_for(prompt);
+
+        let prompt = prompt.saturating_add(usage.tool_use_prompt_token_count.unwrap_or(0));
+        let cached = usage.cached_content_token_count.unwrap_or(0).min(prompt);
+        let audio = match rates.audio_input {
+            Some(_) => {
+                let cached_audio = usage
+                    .cache_tokens_details
+                    .iter()
+                    .find(|d| d.modality == Modality::Audio)
+                    .map_or(0, |d| d.token_count);
+                let audio = usage.prompt_tokens_for(&Modality::Audio).unwrap_or(0);
+                audio.saturating_sub(cached_audio).min(prompt - cached)
+            }
+            None => 0,
+        };
+        let input = prompt - cached - audio;
+
+        let price = |tokens: u32, rate: f64| f64::from(tokens) * rate / TOKENS_PER_RATE;
+
+        Cost {
+            input: price(input, rates.input),
+            cached_input: price(cached, rates.cached_input.unwrap_or(rates.input)),
+            audio_input: price(audio, rates.audio_input.unwrap_or(rates.input)),
+            output: price(usage.candidates_token_count.unwrap_or(0), rates.output),
+            thinking: price(
+                usage.thoughts_token_count.unwrap_or(0),
+                rates.thinking.unwrap_or(rates.output),
+            ),
+        }
+    }
+}
+
+/// Pricing for a set of models, looked up by model name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PricingTable {
+    models: HashMap<String, ModelPricing>,
+}
+
+impl PricingTable {
+    /// Creates an empty pricing table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a table with the published paid-tier prices of current Gemini models
+    pub fn gemini_defaults() -> Self {
+        Self::new()
+            .with_model(
+                "gemini-2.5-pro",
+                ModelPricing::new(TokenRates::new(1.25, 10.0).with_cached_input(0.31))
+                    .with_tier(200_000, TokenRates::new(2.5, 15.0).with_cached_input(0.625)),
+            )
+            .with_model(
+                "gemini-2.5-flash",
+                ModelPricing::new(
+                    TokenRates::new(0.3, 2.5)
+                        .with_cached_input(0.075)
+                        .with_audio_input(1.0),
+                ),
+            )
+            .with_model(
+                "gemini-2.5-flash-lite",
+                ModelPricing::new(
+                    TokenRates::new(0.1, 0.4)
+                        .with_cached_input(0.025)
+                        .with_audio_input(0.3),
+                ),
+            )
+            .with_model(
+                "gemini-2.0-flash",
+                ModelPricing::new(
+                    TokenRates::new(0.1, 0.4)
+                        .with_cached_input(0.025)
+                        .with_audio_input(0.7),
+                ),
+            )
+    }
+
+    /// Adds or replaces the pricing of a model
+    #[must_use]
+    pub fn with_model(mut self, model: impl Into<String>, pricing: ModelPricing) -> Self {
+        self.insert(model, pricing);
+        self
+    }
+
+    /// Adds or replaces the pricing of a model
+    pub fn insert(&mut self, model: impl Into<String>, pricing: ModelPricing) {
+        self.models.insert(model.into(), pricing);
+    }
+
+    /// Returns the pricing for a model
+    ///
+    /// Accepts names with a `models/` prefix. Versioned names such as
+    /// `gemini-2.5-flash-preview-05-20` fall back to the longest configured name
+    /// they extend.
+    pub fn get(&self, model: &str) -> Option<&ModelPricing> {
+        let model = model.strip_prefix("models/").unwrap_or(model);
+
+        if let Some(pricing) = self.models.get(model) {
+            return Some(pricing);
+        }
+
+        self.models
+            .iter()
+            .filter(|(name, _)| {
+                model
+                    .strip_prefix(name.as_str())
+                    .is_some_and(|rest| rest.starts_with('-'))
+            })
+            .max_by_key(|(name, _)| name.len())
+            .map(|(_, pricing)| pricing)
+    }
+
+    /// Converts the usage of a single request to a model into its cost
+    ///
+    /// Returns `None` if the model has no configured pricing.
+    pub fn cost(&self, model: &str, usage: &UsageMetadata) -> Option<Cost> {
+        self.get(model).map(|pricing| pricing.cost(usage))
+    }
+}
+
+/// Cost of token usage in US dollars, broken down by token kind
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Cost {
+    /// Cost of uncached non-audio prompt tokens, including tool-use prompts
+    pub input: f64,
+    /// Cost of prompt tokens served from the context cache
+    pub cached_input: f64,
+    /// Cost of uncached audio prompt tokens
+    pub audio_input: f64,
+    /// Cost of candidate tokens
+    pub output: f64,
+    /// Cost of thinking tokens
+    pub thinking: f64,
+}
+
+impl Cost {
+    /// Returns the total cost
+    pub fn total(&self) -> f64 {
+        self.input + self.cached_input + self.audio_input + self.output + self.thinking
+    }
+}
+
+impl std::fmt::Display for Cost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "${:.6}", self.total())
+    }
+}
+
+impl std::ops::Add for Cost {
+    type Output = Cost;
+
+    fn add(self, other: Cost) -> Cost {
+        Cost {
+            input: self.input + other.input,
+            cached_input: self.cached_input + other.cached_input,
+            audio_input: self.audio_input + other.audio_input,
+            output: self.output + other.output,
+            thinking: self.thinking + other.thinking,
+        }
+    }
+}
+
+impl std::ops::Sub for Cost {
+    type Output = Cost;
+
+    fn sub(self, other: Cost) -> Cost {
+        Cost {
+            input: self.input - other.input,
+            cached_input: self.cached_input - other.cached_input,
+            audio_input: self.audio_input - other.audio_input,
+            output: self.output - other.output,
+            thinking: self.thinking - other.thinking,
+        }
+    }
+}
+
+impl std::ops::AddAssign for Cost {
+    fn add_assign(&mut self, other: Cost) {
+        *self = *self + other;
+    }
+}
+
+impl std::iter::Sum for Cost {
+    fn sum<I: Iterator<Item = Cost>>(iter: I) -> Cost {
+        iter.fold(Cost::default(), |acc, cost| acc + cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto_response::ModalityTokenCount;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-12,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_cost_breakdown() {
+        let pricing = ModelPricing::new(
+            TokenRates::new(1.0, 4.0)
+                .with_thinking(8.0)
+                .with_cached_input(0.5)
+                .with_audio_input(2.0),
+        );
+        let usage = UsageMetadata {
+            prompt_token_count: Some(1_000_000),
+            cached_content_token_count: Some(200_000),
+            candidates_token_count: Some(100_000),
+            thoughts_token_count: Some(50_000),
+            tool_use_prompt_token_count: Some(100_000),
+            prompt_tokens_details: vec![ModalityTokenCount {
+                modality: Modality::Audio,
+                token_count: 300_000,
+            }],
+            ..Default::default()
+        };
+
+        let cost = pricing.cost(&usage);
+        assert_close(cost.input, 0.6);
+        assert_close(cost.cached_input, 0.1);
+        assert_close(cost.audio_input, 0.6);
+        assert_close(cost.output, 0.4);
+        assert_close(cost.thinking, 0.4);
+        assert_close(cost.total(), 2.1);
+    }
+
+    #[test]
+    fn test_cost_defaults_to_input_and_output_rates() {
+        let pricing = ModelPricing::new(TokenRates::new(1.0, 4.0));
+        let usage = UsageMetadata {
+            prompt_token_count: Some(1_000_000),
+            cached_content_token_count: Some(500_000),
+            thoughts_token_count: Some(1_000_000),
+            ..Default::default()
+        };
+
+        let cost = pricing.cost(&usage);
+        assert_close(cost.input, 0.5);
+        assert_close(cost.cached_input, 0.5);
+        assert_close(cost.thinking, 4.0);
+    }
+
+    #[test]
+    fn test_tiered_rates() {
+        let pricing = ModelPricing::new(TokenRates::new(1.0, 2.0))
+            .with_tier(500_000, TokenRates::new(3.0, 4.0))
+            .with_tier(100_000, TokenRates::new(2.0, 3.0));
+
+        assert_eq!(pricing.rates_for(100_000).input, 1.0);
+        assert_eq!(pricing.rates_for(100_001).input, 2.0);
+        assert_eq!(pricing.rates_for(600_000).input, 3.0);
+
+        let usage = UsageMetadata {
+            prompt_token_count: Some(200_000),
+            candidates_token_count: Some(1_000_000),
+            ..Default::default()
+        };
+        assert_close(pricing.cost(&usage).output, 3.0);
+    }
+
+    #[test]
+    fn test_pricing_table_lookup() {
+        let table = PricingTable::gemini_defaults();
+
+        assert!(table.get("gemini-2.5-flash").is_some());
+        assert_eq!(
+            table.get("models/gemini-2.5-flash-lite-preview-06-17"),
+            table.get("gemini-2.5-flash-lite")
+        );
+        assert_eq!(
+            table.get("gemini-2.5-flash-preview-05-20"),
+            table.get("gemini-2.5-flash")
+        );
+        assert!(table.get("gemini-2.5-flashy").is_none());
+        assert!(
+            table
+                .cost("unknown-model", &UsageMetadata::default())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_cost_arithmetic() {
+        let a = Cost {
+            input: 1.0,
+            output: 2.0,
+            ..Default::default()
+        };
+        let b = Cost {
+            input: 0.5,
+            thinking: 1.0,
+            ..Default::default()
+        };
+
+        let sum: Cost = [a, b].into_iter().sum();
+        assert_close(sum.total(), 4.5);
+        assert_close((sum - b).total(), a.total());
+        assert_eq!(sum.to_string(), "$4.500000");
+    }
+}
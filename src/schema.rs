@@ -0,0 +1,1153 @@
+//! Normalization of derived schemas to the subset accepted by the Gemini API.
+//!
+//! `schemars` and `utoipa` emit schemas for the full JSON Schema and OpenAPI 3.1
+//! vocabularies, while `responseJsonSchema` and `responseSchema` only understand
+//! a subset of them. [`normalize_schema`] rewrites a derived schema so the API
+//! interprets it the way the Rust type deserializes:
+//!
+//! - local `$ref`s are inlined; recursive ones are kept as `$defs` references
+//!   for `responseJsonSchema` and reported as unsupported for `responseSchema`
+//! - `Option<T>` unions with `null` become `"type": [T, "null"]` or `nullable: true`
+//! - `const` and `oneOf` lists of constants become `enum`s, other `oneOf`s become `anyOf`
+//! - unsupported keywords and formats are removed with a [`SchemaIssue`] warning
+//! - every object gets a `propertyOrdering` matching its declared properties
+//!
+//! Structures the API cannot express, such as maps in `responseSchema` or
+//! unions of several primitive types, fail with an [`UnsupportedSchemaError`].
+//!
+//! For response shapes only known at runtime, [`Schema`] builds schemas in the
+//! supported subset without a Rust type to derive from.
+
+use serde_json::{Map, Value, json};
+
+/// The schema field a schema is normalized for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaDialect {
+    /// `responseJsonSchema`, a subset of JSON Schema
+    JsonSchema,
+    /// `responseSchema`, a subset of the OpenAPI schema object
+    OpenApi,
+}
+
+/// A problem found at a location in a schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaIssue {
+    /// JSON pointer to the offending schema or value, e.g. `#/properties/tags`
+    pub path: String,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// A schema rewritten for the Gemini API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedSchema {
+    /// The normalized schema
+    pub schema: Value,
+    /// Keywords and formats that were removed because the API does not support them
+    pub warnings: Vec<SchemaIssue>,
+}
+
+/// Error returned when a schema uses structures the API cannot express
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedSchemaError {
+    /// Every unsupported structure found in the schema
+    pub issues: Vec<SchemaIssue>,
+}
+
+impl std::fmt::Display for UnsupportedSchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Schema is not supported by the Gemini API")?;
+        for issue in &self.issues {
+            write!(f, "; {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnsupportedSchemaError {}
+
+/// Rewrites a schema into the subset supported by `dialect`
+///
+/// Local references are resolved against `schema` itself, so definitions may live
+/// under `$defs`, `definitions` or `components/schemas`.
+///
+/// # Errors
+/// Returns every structure the API cannot express if there is at least one.
+pub fn normalize_schema(
+    schema: &Value,
+    dialect: SchemaDialect,
+) -> Result<NormalizedSchema, UnsupportedSchemaError> {
+    let mut normalizer = Normalizer {
+        root: schema,
+        dialect,
+        stack: vec!["#".to_string()],
+        defs: Vec::new(),
+        warnings: Vec::new(),
+        errors: Vec::new(),
+    };
+    let mut normalized = normalizer.node(schema, "#");
+
+    // Recursive definitions referenced while normalizing, which may reference more
+    let mut defs = Map::new();
+    let mut index = 0;
+    while index < normalizer.defs.len() {
+        let (pointer, name) = normalizer.defs[index].clone();
+        let target = resolve(schema, &pointer).unwrap_or(&Value::Null);
+        normalizer.stack = vec![pointer];
+        let def = normalizer.node(target, &format!("#/$defs/{}", name));
+        defs.insert(name, def);
+        index += 1;
+    }
+    if !defs.is_empty()
+        && let Value::Object(root) = &mut normalized
+    {
+        root.insert("$defs".to_string(), Value::Object(defs));
+    }
+
+    if normalizer.errors.is_empty() {
+        Ok(NormalizedSchema {
+            schema: normalized,
+            warnings: normalizer.warnings,
+        })
+    } else {
+        Err(UnsupportedSchemaError {
+            issues: normalizer.errors,
+        })
+    }
+}
+
+/// Resolves a local `#/...` reference against the root schema
+fn resolve<'a>(root: &'a Value, pointer: &str) -> Option<&'a Value> {
+    root.pointer(pointer.strip_prefix('#')?)
+}
+
+struct Normalizer<'a> {
+    root: &'a Value,
+    dialect: SchemaDialect,
+    /// References currently being inlined, used to detect recursion
+    stack: Vec<String>,
+    /// Recursive references emitted as `$defs`, as `(pointer, name)`
+    defs: Vec<(String, String)>,
+    warnings: Vec<SchemaIssue>,
+    errors: Vec<SchemaIssue>,
+}
+
+impl Normalizer<'_> {
+    fn warn(&mut self, path: &str, message: String) {
+        self.warnings.push(SchemaIssue {
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    fn error(&mut self, path: &str, message: String) {
+        self.errors.push(SchemaIssue {
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    fn node(&mut self, schema: &Value, path: &str) -> Value {
+        let mut out = Map::new();
+        let mut nullable = false;
+        let errors = self.errors.len();
+        match schema {
+            Value::Object(object) => self.collect(object, path, &mut out, &mut nullable),
+            Value::Bool(true) => {}
+            _ => self.error(path, "schema never matches any value".to_string()),
+        }
+        // A schema that already failed would also be reported as untyped
+        let failed = self.errors.len() > errors;
+        self.finish(out, nullable, failed, path)
+    }
+
+    /// Merges the keywords of `object` into `out`, inlining references and single-branch unions
+    fn collect(
+        &mut self,
+        object: &Map<String, Value>,
+        path: &str,
+        out: &mut Map<String, Value>,
+        nullable: &mut bool,
+    ) {
+        // Referenced keywords come first so sibling keywords such as `description` win
+        if let Some(reference) = object.get("$ref") {
+            self.reference(reference, path, out, nullable);
+        }
+
+        for (key, value) in object {
+            let key_path = format!("{}/{}", path, key);
+            match key.as_str() {
+                "$ref" | "$schema" | "$id" | "$comment" | "$defs" | "definitions"
+                | "components" => {}
+                "type" => self.types(value, &key_path, out, nullable),
+                "const" => self.enum_values(std::slice::from_ref(value), &key_path, out, nullable),
+                "enum" => match value {
+                    Value::Array(values) => self.enum_values(values, &key_path, out, nullable),
+                    _ => self.error(&key_path, "`enum` must be an array".to_string()),
+                },
+                "nullable" => *nullable |= value == &Value::Bool(true),
+                "allOf" => match value {
+                    Value::Array(branches) => {
+                        for (index, branch) in branches.iter().enumerate() {
+                            let branch_path = format!("{}/{}", key_path, index);
+                            match branch {
+                                Value::Object(branch) => {
+                                    self.collect(branch, &branch_path, out, nullable)
+                                }
+                                Value::Bool(true) => {}
+                                _ => self.error(&branch_path, "invalid schema".to_string()),
+                            }
+                        }
+                    }
+                    _ => self.error(&key_path, "`allOf` must be an array".to_string()),
+                },
+                "anyOf" | "oneOf" => match value {
+                    Value::Array(branches) => self.alternatives(branches, &key_path, out, nullable),
+                    _ => self.error(&key_path, format!("`{}` must be an array", key)),
+                },
+                "properties" => match value {
+                    Value::Object(properties) => {
+                        for (name, property) in properties {
+                            let property_path = format!("{}/{}", key_path, name);
+                            let property = self.node(property, &property_path);
+                            let target = out
+                                .entry("properties")
+                                .or_insert_with(|| Value::Object(Map::new()));
+                            if let Value::Object(target) = target {
+                                target.insert(name.clone(), property);
+                            }
+                        }
+                    }
+                    _ => self.error(&key_path, "`properties` must be an object".to_string()),
+                },
+                "required" => {
+                    let target = out
+                        .entry("required")
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    if let (Value::Array(target), Value::Array(names)) = (target, value) {
+                        for name in names {
+                            if !target.contains(name) {
+                                target.push(name.clone());
+                            }
+                        }
+                    }
+                }
+                "items" => match value {
+                    Value::Array(_) => self.prefix_items(value, &key_path, out),
+                    _ => {
+                        let items = self.node(value, &key_path);
+                        out.insert("items".to_string(), items);
+                    }
+                },
+                "prefixItems" => self.prefix_items(value, &key_path, out),
+                "additionalProperties" => {
+                    let additional = match value {
+                        Value::Bool(_) => value.clone(),
+                        _ => self.node(value, &key_path),
+                    };
+                    out.insert(key.clone(), additional);
+                }
+                "format" | "propertyOrdering" | "title" | "description" | "minItems"
+                | "maxItems" | "minimum" | "maximum" => {
+                    out.insert(key.clone(), value.clone());
+                }
+                "minProperties" | "maxProperties" | "minLength" | "maxLength" | "pattern"
+                | "example" | "default"
+                    if self.dialect == SchemaDialect::OpenApi =>
+                {
+                    out.insert(key.clone(), value.clone());
+                }
+                _ => self.warn(
+                    &key_path,
+                    format!("unsupported keyword `{}` was removed", key),
+                ),
+            }
+        }
+    }
+
+    fn reference(
+        &mut self,
+        reference: &Value,
+        path: &str,
+        out: &mut Map<String, Value>,
+        nullable: &mut bool,
+    ) {
+        let Some(pointer) = reference.as_str() else {
+            self.error(path, "`$ref` must be a string".to_string());
+            return;
+        };
+        let Some(target) = resolve(self.root, pointer) else {
+            let message = if pointer.starts_with('#') {
+                format!("reference `{}` does not resolve", pointer)
+            } else {
+                format!("external reference `{}` is not supported", pointer)
+            };
+            self.error(path, message);
+            return;
+        };
+
+        if self.stack.iter().any(|entry| entry == pointer) {
+            match self.dialect {
+                SchemaDialect::OpenApi => self.error(
+                    path,
+                    format!("recursive reference to `{}` cannot be expressed", pointer),
+                ),
+                SchemaDialect::JsonSchema => {
+                    let target = if pointer == "#" {
+                        pointer.to_string()
+                    } else {
+                        format!("#/$defs/{}", self.def_name(pointer))
+                    };
+                    out.insert("$ref".to_string(), Value::String(target));
+                }
+            }
+            return;
+        }
+
+        self.stack.push(pointer.to_string());
+        match target {
+            Value::Object(target) => self.collect(target, path, out, nullable),
+            Value::Bool(true) => {}
+            _ => self.error(path, format!("reference `{}` is not a schema", pointer)),
+        }
+        self.stack.pop();
+    }
+
+    /// Returns the `$defs` name for a recursive reference, registering it on first use
+    fn def_name(&mut self, pointer: &str) -> String {
+        if let Some((_, name)) = self.defs.iter().find(|(known, _)| known == pointer) {
+            return name.clone();
+        }
+
+        let base = pointer.rsplit('/').next().unwrap_or(pointer).to_string();
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while self.defs.iter().any(|(_, known)| known == &name) {
+            name = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+        self.defs.push((pointer.to_string(), name.clone()));
+        name
+    }
+
+    fn types(
+        &mut self,
+        value: &Value,
+        path: &str,
+        out: &mut Map<String, Value>,
+        nullable: &mut bool,
+    ) {
+        let names: Vec<&str> = match value {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let (nulls, types): (Vec<&str>, Vec<&str>) =
+            names.into_iter().partition(|name| *name == "null");
+        *nullable |= !nulls.is_empty();
+
+        match types.as_slice() {
+            [] => self.error(
+                path,
+                "a schema that only allows null cannot be expressed".to_string(),
+            ),
+            [name] => {
+                out.insert("type".to_string(), Value::String(name.to_string()));
+            }
+            _ => self.error(
+                path,
+                format!(
+                    "a union of the types {} cannot be expressed",
+                    types.join(", ")
+                ),
+            ),
+        }
+    }
+
+    fn enum_values(
+        &mut self,
+        values: &[Value],
+        path: &str,
+        out: &mut Map<String, Value>,
+        nullable: &mut bool,
+    ) {
+        let mut kept = Vec::new();
+        for value in values {
+            match value {
+                Value::Null => *nullable = true,
+                Value::String(_) => kept.push(value.clone()),
+                Value::Number(_) if self.dialect == SchemaDialect::JsonSchema => {
+                    kept.push(value.clone())
+                }
+                _ => {
+                    self.error(path, format!("enum value {} cannot be expressed", value));
+                    return;
+                }
+            }
+        }
+
+        let value_type = if kept.iter().all(Value::is_string) {
+            "string"
+        } else if kept.iter().all(|value| value.is_i64() || value.is_u64()) {
+            "integer"
+        } else if kept.iter().all(Value::is_number) {
+            "number"
+        } else {
+            self.error(
+                path,
+                "enums mixing strings and numbers cannot be expressed".to_string(),
+            );
+            return;
+        };
+
+        if !kept.is_empty() {
+            out.entry("type")
+                .or_insert_with(|| Value::String(value_type.to_string()));
+            if self.dialect == SchemaDialect::OpenApi {
+                out.insert("format".to_string(), json!("enum"));
+            }
+        }
+        match out.get_mut("enum") {
+            // Merging two enums, e.g. through `allOf`, keeps the values both allow
+            Some(Value::Array(existing)) => existing.retain(|value| kept.contains(value)),
+            _ => {
+                out.insert("enum".to_string(), Value::Array(kept));
+            }
+        }
+    }
+
+    fn alternatives(
+        &mut self,
+        branches: &[Value],
+        path: &str,
+        out: &mut Map<String, Value>,
+        nullable: &mut bool,
+    ) {
+        let mut remaining = Vec::new();
+        for (index, branch) in branches.iter().enumerate() {
+            if is_null_schema(branch) {
+                *nullable = true;
+            } else {
+                remaining.push((format!("{}/{}", path, index), branch));
+            }
+        }
+
+        match remaining.as_slice() {
+            [] => self.error(
+                path,
+                "a schema that only allows null cannot be expressed".to_string(),
+            ),
+            [(branch_path, Value::Object(branch))] => {
+                self.collect(branch, branch_path, out, nullable)
+            }
+            [(_, Value::Bool(true))] => {}
+            _ => {
+                if let Some(values) = constant_values(&remaining) {
+                    self.enum_values(&values, path, out, nullable);
+                    return;
+                }
+                let normalized: Vec<Value> = remaining
+                    .iter()
+                    .map(|(branch_path, branch)| self.node(branch, branch_path))
+                    .collect();
+                let target = out
+                    .entry("anyOf")
+                    .or_insert_with(|| Value::Array(Vec::new()));
+                if let Value::Array(target) = target {
+                    target.extend(normalized);
+                }
+            }
+        }
+    }
+
+    fn prefix_items(&mut self, value: &Value, path: &str, out: &mut Map<String, Value>) {
+        if self.dialect == SchemaDialect::OpenApi {
+            self.error(path, "tuples cannot be expressed".to_string());
+            return;
+        }
+        let Value::Array(items) = value else {
+            self.error(path, "`prefixItems` must be an array".to_string());
+            return;
+        };
+        let items = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| self.node(item, &format!("{}/{}", path, index)))
+            .collect();
+        out.insert("prefixItems".to_string(), Value::Array(items));
+    }
+
+    /// Validates formats and object keywords once all keywords are merged, then applies nullability
+    fn finish(
+        &mut self,
+        mut out: Map<String, Value>,
+        nullable: bool,
+        failed: bool,
+        path: &str,
+    ) -> Value {
+        let value_type = out.get("type").and_then(Value::as_str).map(str::to_string);
+
+        if let Some(format) = out
+            .get("format")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+        {
+            let supported = match (value_type.as_deref(), format.as_str()) {
+                (Some("string"), "date-time" | "date" | "time") => true,
+                (Some("string"), "enum") => self.dialect == SchemaDialect::OpenApi,
+                (Some("number"), "float" | "double") => true,
+                (Some("integer"), "int32" | "int64") => true,
+                // Other integer formats only describe a range, which `minimum` and `maximum` carry
+                (Some("integer"), _) => {
+                    out.remove("format");
+                    if format.starts_with("uint") {
+                        out.entry("minimum").or_insert(json!(0));
+                    }
+                    true
+                }
+                _ => false,
+            };
+            if !supported {
+                out.remove("format");
+                self.warn(
+                    &format!("{}/format", path),
+                    format!("unsupported format `{}` was removed", format),
+                );
+            }
+        }
+
+        let names: Vec<Value> = match out.get("properties") {
+            Some(Value::Object(properties)) => {
+                properties.keys().cloned().map(Value::String).collect()
+            }
+            _ => Vec::new(),
+        };
+        if let Some(Value::Array(required)) = out.get_mut("required") {
+            required.retain(|name| names.contains(name));
+        }
+        if names.is_empty() {
+            out.remove("propertyOrdering");
+        } else {
+            let matches = match out.get("propertyOrdering") {
+                Some(Value::Array(ordering)) => {
+                    ordering.len() == names.len()
+                        && names.iter().all(|name| ordering.contains(name))
+                }
+                Some(_) => false,
+                None => true,
+            };
+            if !matches {
+                self.warn(
+                    &format!("{}/propertyOrdering", path),
+                    "`propertyOrdering` did not list the properties and was replaced".to_string(),
+                );
+            }
+            if !matches || !out.contains_key("propertyOrdering") {
+                out.insert("propertyOrdering".to_string(), Value::Array(names.clone()));
+            }
+        }
+
+        if self.dialect == SchemaDialect::OpenApi
+            && let Some(additional) = out.remove("additionalProperties")
+            && additional != Value::Bool(false)
+        {
+            let additional_path = format!("{}/additionalProperties", path);
+            if names.is_empty() {
+                self.error(
+                    &additional_path,
+                    "maps with arbitrary keys cannot be expressed".to_string(),
+                );
+            } else {
+                self.warn(
+                    &additional_path,
+                    "additional properties are not supported and were removed".to_string(),
+                );
+            }
+        }
+
+        if self.dialect == SchemaDialect::OpenApi
+            && !failed
+            && !out.contains_key("type")
+            && !out.contains_key("anyOf")
+        {
+            self.error(
+                path,
+                "a schema without a type cannot be expressed".to_string(),
+            );
+        }
+
+        if !nullable {
+            return Value::Object(out);
+        }
+        match self.dialect {
+            SchemaDialect::OpenApi => {
+                out.insert("nullable".to_string(), Value::Bool(true));
+            }
+            SchemaDialect::JsonSchema => {
+                if let Some(Value::Array(values)) = out.get_mut("enum") {
+                    values.push(Value::Null);
+                }
+                if let Some(value_type) = value_type {
+                    out.insert("type".to_string(), json!([value_type, "null"]));
+                } else if let Some(Value::Array(branches)) = out.get_mut("anyOf") {
+                    branches.push(json!({"type": "null"}));
+                } else if out.contains_key("$ref") {
+                    return json!({"anyOf": [out, {"type": "null"}]});
+                }
+            }
+        }
+        Value::Object(out)
+    }
+}
+
+/// Returns true for a schema that only matches `null`
+fn is_null_schema(schema: &Value) -> bool {
+    match schema {
+        Value::Object(object) => {
+            object.get("type") == Some(&json!("null")) || object.get("const") == Some(&Value::Null)
+        }
+        _ => false,
+    }
+}
+
+/// Returns the values of a union whose branches are all constants, as emitted for documented enums
+fn constant_values(branches: &[(String, &Value)]) -> Option<Vec<Value>> {
+    let mut values = Vec::new();
+    for (_, branch) in branches {
+        let branch = branch.as_object()?;
+        if branch.contains_key("properties") || branch.contains_key("$ref") {
+            return None;
+        }
+        match (branch.get("const"), branch.get("enum")) {
+            (Some(value), None) => values.push(value.clone()),
+            (None, Some(Value::Array(options))) => values.extend(options.iter().cloned()),
+            _ => return None,
+        }
+    }
+    Some(values)
+}
+
+/// String formats understood by the API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringFormat {
+    /// RFC 3339 timestamp, e.g. `2024-05-01T12:00:00Z`
+    DateTime,
+    /// Calendar date, e.g. `2024-05-01`
+    Date,
+    /// Time of day, e.g. `12:00:00`
+    Time,
+}
+
+impl StringFormat {
+    /// Returns the JSON Schema `format` value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StringFormat::DateTime => "date-time",
+            StringFormat::Date => "date",
+            StringFormat::Time => "time",
+        }
+    }
+}
+
+/// A JSON Schema built at runtime, for response shapes only known while the program runs
+///
+/// Produces schemas within the subset accepted by `responseJsonSchema`. Objects list their
+/// properties in insertion order through `propertyOrdering`.
+///
+/// ```
+/// use gemini::schema::{Schema, StringFormat};
+///
+/// let invoice = Schema::object()
+///     .property("number", Schema::string())
+///     .property("issued", Schema::string().with_format(StringFormat::Date))
+///     .property("status", Schema::enumeration(["paid", "open"]))
+///     .optional_property("lines", Schema::array(Schema::number()).with_min_items(1));
+/// let value = serde_json::Value::from(invoice);
+/// assert_eq!(value["required"], serde_json::json!(["number", "issued", "status"]));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    keywords: Map<String, Value>,
+    nullable: bool,
+}
+
+impl Schema {
+    fn typed(value_type: &str) -> Self {
+        let mut keywords = Map::new();
+        keywords.insert("type".to_string(), json!(value_type));
+        Self {
+            keywords,
+            nullable: false,
+        }
+    }
+
+    /// A string
+    pub fn string() -> Self {
+        Self::typed("string")
+    }
+
+    /// An integer
+    pub fn integer() -> Self {
+        Self::typed("integer")
+    }
+
+    /// A floating point number
+    pub fn number() -> Self {
+        Self::typed("number")
+    }
+
+    /// A boolean
+    pub fn boolean() -> Self {
+        Self::typed("boolean")
+    }
+
+    /// An object without properties; add them with [`Schema::property`]
+    pub fn object() -> Self {
+        Self::typed("object")
+    }
+
+    /// An array of `items`
+    pub fn array(items: Schema) -> Self {
+        let mut schema = Self::typed("array");
+        schema
+            .keywords
+            .insert("items".to_string(), Value::from(items));
+        schema
+    }
+
+    /// A string restricted to `values`
+    pub fn enumeration<I, S>(values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let values = values.into_iter().map(|value| Value::String(value.into()));
+        let mut schema = Self::string();
+        schema
+            .keywords
+            .insert("enum".to_string(), Value::Array(values.collect()));
+        schema
+    }
+
+    /// A value matching any of `schemas`
+    pub fn any_of(schemas: impl IntoIterator<Item = Schema>) -> Self {
+        let mut keywords = Map::new();
+        let branches = schemas.into_iter().map(Value::from).collect();
+        keywords.insert("anyOf".to_string(), Value::Array(branches));
+        Self {
+            keywords,
+            nullable: false,
+        }
+    }
+
+    /// Adds a required property to an object
+    #[must_use]
+    pub fn property(self, name: impl Into<String>, schema: Schema) -> Self {
+        let name = name.into();
+        let mut schema = self.optional_property(name.clone(), schema);
+        push(&mut schema.keywords, "required", Value::String(name));
+        schema
+    }
+
+    /// Adds a property the model may omit to an object
+    #[must_use]
+    pub fn optional_property(mut self, name: impl Into<String>, schema: Schema) -> Self {
+        let name = name.into();
+        let properties = self
+            .keywords
+            .entry("properties")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(properties) = properties {
+            properties.insert(name.clone(), Value::from(schema));
+        }
+        push(&mut self.keywords, "propertyOrdering", Value::String(name));
+        self
+    }
+
+    /// Sets the format of a string
+    #[must_use]
+    pub fn with_format(mut self, format: StringFormat) -> Self {
+        self.keywords
+            .insert("format".to_string(), json!(format.as_str()));
+        self
+    }
+
+    /// Sets the title
+    #[must_use]
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.keywords
+            .insert("title".to_string(), Value::String(title.into()));
+        self
+    }
+
+    /// Sets the description, which guides the model when filling the value
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.keywords
+            .insert("description".to_string(), Value::String(description.into()));
+        self
+    }
+
+    /// Sets the minimum number of array items
+    #[must_use]
+    pub fn with_min_items(mut self, min_items: u64) -> Self {
+        self.keywords
+            .insert("minItems".to_string(), json!(min_items));
+        self
+    }
+
+    /// Sets the maximum number of array items
+    #[must_use]
+    pub fn with_max_items(mut self, max_items: u64) -> Self {
+        self.keywords
+            .insert("maxItems".to_string(), json!(max_items));
+        self
+    }
+
+    /// Sets the inclusive minimum of a number
+    #[must_use]
+    pub fn with_minimum(mut self, minimum: f64) -> Self {
+        self.keywords.insert("minimum".to_string(), json!(minimum));
+        self
+    }
+
+    /// Sets the inclusive maximum of a number
+    #[must_use]
+    pub fn with_maximum(mut self, maximum: f64) -> Self {
+        self.keywords.insert("maximum".to_string(), json!(maximum));
+        self
+    }
+
+    /// Also allows `null`
+    #[must_use]
+    pub fn nullable(mut self) -> Self {
+        self.nullable = true;
+        self
+    }
+}
+
+impl From<Schema> for Value {
+    fn from(schema: Schema) -> Self {
+        let mut keywords = schema.keywords;
+        if schema.nullable {
+            if let Some(Value::Array(values)) = keywords.get_mut("enum") {
+                values.push(Value::Null);
+            }
+            if let Some(Value::Array(branches)) = keywords.get_mut("anyOf") {
+                branches.push(json!({"type": "null"}));
+            } else if let Some(value_type) = keywords.get("type").cloned() {
+                keywords.insert("type".to_string(), json!([value_type, "null"]));
+            }
+        }
+        Value::Object(keywords)
+    }
+}
+
+/// Appends `value` to the array keyword `key`, creating it if needed
+fn push(keywords: &mut Map<String, Value>, key: &str, value: Value) {
+    let target = keywords
+        .entry(key)
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if let Value::Array(target) = target
+        && !target.contains(&value)
+    {
+        target.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(schema: Value, dialect: SchemaDialect) -> NormalizedSchema {
+        normalize_schema(&schema, dialect).unwrap()
+    }
+
+    #[test]
+    fn test_inlines_refs_and_rewrites_nullability() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Order",
+            "type": "object",
+            "properties": {
+                "customer": {"anyOf": [{"$ref": "#/$defs/Customer"}, {"type": "null"}]},
+                "id": {"type": "integer", "format": "uint32", "minimum": 0},
+                "note": {"type": ["string", "null"]}
+            },
+            "required": ["id"],
+            "$defs": {
+                "Customer": {
+                    "type": "object",
+                    "properties": {"name": {"type": "string"}},
+                    "required": ["name"]
+                }
+            }
+        });
+
+        let normalized = normalize(schema.clone(), SchemaDialect::JsonSchema);
+        assert!(normalized.warnings.is_empty());
+        assert_eq!(
+            normalized.schema,
+            json!({
+                "title": "Order",
+                "type": "object",
+                "properties": {
+                    "customer": {
+                        "type": ["object", "null"],
+                        "properties": {"name": {"type": "string"}},
+                        "required": ["name"],
+                        "propertyOrdering": ["name"]
+                    },
+                    "id": {"type": "integer", "minimum": 0},
+                    "note": {"type": ["string", "null"]}
+                },
+                "required": ["id"],
+                "propertyOrdering": ["customer", "id", "note"]
+            })
+        );
+
+        let normalized = normalize(schema, SchemaDialect::OpenApi);
+        assert_eq!(
+            normalized.schema["properties"]["note"],
+            json!({"type": "string", "nullable": true})
+        );
+        assert_eq!(
+            normalized.schema["properties"]["customer"]["nullable"],
+            true
+        );
+        assert_eq!(
+            normalized.schema["properties"]["customer"]["type"],
+            json!("object")
+        );
+    }
+
+    #[test]
+    fn test_maps_enums() {
+        let schema = json!({
+            "oneOf": [
+                {"type": "string", "const": "low", "description": "Low priority"},
+                {"type": "string", "const": "high", "description": "High priority"}
+            ]
+        });
+        assert_eq!(
+            normalize(schema.clone(), SchemaDialect::JsonSchema).schema,
+            json!({"type": "string", "enum": ["low", "high"]})
+        );
+        assert_eq!(
+            normalize(schema, SchemaDialect::OpenApi).schema,
+            json!({"type": "string", "format": "enum", "enum": ["low", "high"]})
+        );
+
+        let schema = json!({"const": 3});
+        assert_eq!(
+            normalize(schema.clone(), SchemaDialect::JsonSchema).schema,
+            json!({"type": "integer", "enum": [3]})
+        );
+        assert!(normalize_schema(&schema, SchemaDialect::OpenApi).is_err());
+
+        let schema = json!({"type": ["string", "null"], "enum": ["a", "b", null]});
+        assert_eq!(
+            normalize(schema, SchemaDialect::JsonSchema).schema,
+            json!({"type": ["string", "null"], "enum": ["a", "b", null]})
+        );
+    }
+
+    #[test]
+    fn test_rewrites_unions() {
+        let schema = json!({
+            "oneOf": [
+                {"type": "object", "properties": {"Circle": {"type": "number", "format": "double"}}},
+                {"type": "object", "properties": {"Square": {"type": "number", "format": "float"}}}
+            ]
+        });
+        let normalized = normalize(schema, SchemaDialect::JsonSchema).schema;
+        assert!(normalized.get("oneOf").is_none());
+        assert_eq!(normalized["anyOf"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            normalized["anyOf"][0]["properties"]["Circle"],
+            json!({"type": "number", "format": "double"})
+        );
+    }
+
+    #[test]
+    fn test_strips_unsupported_keywords_with_warnings() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "email": {"type": "string", "format": "email", "pattern": ".+@.+"},
+                "tags": {"type": "array", "items": {"type": "string"}, "uniqueItems": true}
+            },
+            "propertyOrdering": ["tags"]
+        });
+
+        let normalized = normalize(schema.clone(), SchemaDialect::JsonSchema);
+        assert_eq!(
+            normalized.schema["properties"]["email"],
+            json!({"type": "string"})
+        );
+        assert_eq!(
+            normalized.schema["propertyOrdering"],
+            json!(["email", "tags"])
+        );
+        let paths: Vec<&str> = normalized
+            .warnings
+            .iter()
+            .map(|warning| warning.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "#/properties/email/pattern",
+                "#/properties/email/format",
+                "#/properties/tags/uniqueItems",
+                "#/propertyOrdering"
+            ]
+        );
+
+        // `pattern` is part of the OpenAPI subset
+        let normalized = normalize(schema, SchemaDialect::OpenApi);
+        assert_eq!(
+            normalized.schema["properties"]["email"],
+            json!({"type": "string", "pattern": ".+@.+"})
+        );
+    }
+
+    #[test]
+    fn test_recursive_refs() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"root": {"$ref": "#/components/schemas/Node"}},
+            "components": {"schemas": {"Node": {
+                "type": "object",
+                "properties": {
+                    "children": {"type": "array", "items": {"$ref": "#/components/schemas/Node"}}
+                }
+            }}}
+        });
+
+        let normalized = normalize(schema.clone(), SchemaDialect::JsonSchema).schema;
+        assert_eq!(
+            normalized["properties"]["root"]["properties"]["children"]["items"],
+            json!({"$ref": "#/$defs/Node"})
+        );
+        assert_eq!(
+            normalized["$defs"]["Node"]["properties"]["children"]["items"],
+            json!({"$ref": "#/$defs/Node"})
+        );
+        assert!(normalized.get("components").is_none());
+
+        let error = normalize_schema(&schema, SchemaDialect::OpenApi).unwrap_err();
+        assert_eq!(
+            error.issues,
+            vec![SchemaIssue {
+                path: "#/properties/root/properties/children/items".to_string(),
+                message: "recursive reference to `#/components/schemas/Node` cannot be expressed"
+                    .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_reports_unsupported_structures() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "missing": {"$ref": "#/$defs/Missing"},
+                "pair": {"type": "array", "prefixItems": [{"type": "string"}, {"type": "integer"}]},
+                "scores": {"type": "object", "additionalProperties": {"type": "integer"}},
+                "value": {"type": ["string", "integer"]}
+            }
+        });
+
+        let error = normalize_schema(&schema, SchemaDialect::OpenApi).unwrap_err();
+        let paths: Vec<&str> = error
+            .issues
+            .iter()
+            .map(|issue| issue.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "#/properties/missing",
+                "#/properties/pair/prefixItems",
+                "#/properties/scores/additionalProperties",
+                "#/properties/value/type",
+            ]
+        );
+        assert!(error.to_string().contains("maps with arbitrary keys"));
+
+        // JSON Schema supports maps and tuples, but not the union or the dangling reference
+        let error = normalize_schema(&schema, SchemaDialect::JsonSchema).unwrap_err();
+        assert_eq!(error.issues.len(), 2);
+    }
+
+    #[test]
+    fn test_schema_builder() {
+        let schema = Schema::object()
+            .with_title("Receipt")
+            .property("merchant", Schema::string().with_description("Store name"))
+            .property("date", Schema::string().with_format(StringFormat::Date))
+            .optional_property(
+                "category",
+                Schema::enumeration(["food", "travel"]).nullable(),
+            )
+            .property(
+                "items",
+                Schema::array(
+                    Schema::object()
+                        .property("name", Schema::string())
+                        .property("price", Schema::number().with_minimum(0.0)),
+                )
+                .with_min_items(1),
+            );
+
+        let value = Value::from(schema);
+        assert_eq!(
+            value,
+            json!({
+                "type": "object",
+                "title": "Receipt",
+                "properties": {
+                    "merchant": {"type": "string", "description": "Store name"},
+                    "date": {"type": "string", "format": "date"},
+                    "category": {"type": ["string", "null"], "enum": ["food", "travel", null]},
+                    "items": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "name": {"type": "string"},
+                                "price": {"type": "number", "minimum": 0.0}
+                            },
+                            "required": ["name", "price"],
+                            "propertyOrdering": ["name", "price"]
+                        },
+                        "minItems": 1
+                    }
+                },
+                "required": ["merchant", "date", "items"],
+                "propertyOrdering": ["merchant", "date", "category", "items"]
+            })
+        );
+
+        // Built schemas are already in the supported subset
+        let normalized = normalize(value.clone(), SchemaDialect::JsonSchema);
+        assert_eq!(normalized.schema, value);
+        assert!(normalized.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_schema_builder_nullable_union() {
+        let schema = Schema::any_of([Schema::string(), Schema::integer()]).nullable();
+        assert_eq!(
+            Value::from(schema),
+            json!({"anyOf": [{"type": "string"}, {"type": "integer"}, {"type": "null"}]})
+        );
+    }
+}
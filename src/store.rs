@@ -0,0 +1,698 @@
+//! Saving and restoring complete chat sessions
+//!
+//! A [`ChatSession`] holds everything needed to resume a chat: its history with
+//! every branch, the session-wide request options, token usage and timestamps.
+//! A [`ChatStore`] keeps sessions under an id. [`JsonFileStore`] writes one JSON
+//! file per session, and `SqliteStore` (with the `sqlite` feature) keeps them in
+//! an embedded database.
+//!
+//! Sessions are written with a format version, and older formats are migrated
+//! when loaded. This includes history saved directly as a `Vec<Content<String>>`.
+//!
+//! ```ignore
+//! let store = JsonFileStore::new("chats");
+//! store.save(&chat.to_session("support-42")).await?;
+//!
+//! if let Some(session) = store.load("support-42").await? {
+//!     let chat = GeminiChat::from_session(client, session);
+//! }
+//! ```
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::chat_tree::ChatTree;
+use crate::dto_content::Content;
+use crate::dto_request::{GenerationConfig, SafetySetting, Tool};
+use crate::dto_response::UsageMetadata;
+
+/// Version of the session format written by [`ChatSession::to_json`]
+///
+/// Version 0 is history saved as a bare `Vec<Content<String>>`.
+pub const SESSION_FORMAT_VERSION: u64 = 1;
+
+/// A saved chat, with its history, options, usage and timestamps
+///
+/// Created by `GeminiChat::to_session` and restored by `GeminiChat::from_session`.
+/// History strategies and partial turn policies are not saved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatSession {
+    /// Key the session is stored under
+    #[serde(skip)]
+    pub id: String,
+    /// History with every branch
+    pub tree: ChatTree,
+    /// System instruction sent with every turn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_instruction: Option<Content>,
+    /// Default generation config, merged with any config set on a message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation_config: Option<GenerationConfig>,
+    /// Default safety settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safety_settings: Option<Vec<SafetySetting>>,
+    /// Tools the model may use on every turn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+    /// Cached content used as context, e.g. `cachedContents/abc123`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_content: Option<String>,
+    /// Token usage of each request made by the chat
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub turn_usage: Vec<UsageMetadata>,
+    /// When the conversation was started
+    #[serde(with = "unix_millis")]
+    pub created_at: SystemTime,
+    /// When the session was last saved
+    #[serde(with = "unix_millis")]
+    pub updated_at: SystemTime,
+}
+
+/// Error returned when a saved session cannot be read
+#[derive(Debug)]
+pub enum SessionFormatError {
+    /// The session is not valid JSON or does not match its format version
+    Json(serde_json::Error),
+    /// The session is neither a history array nor an object with a version
+    MissingVersion,
+    /// The session was written by a newer version of this crate
+    UnsupportedVersion(u64),
+}
+
+impl std::fmt::Display for SessionFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionFormatError::Json(e) => write!(f, "Invalid chat session: {e}"),
+            SessionFormatError::MissingVersion => {
+                write!(f, "Chat session has no format version")
+            }
+            SessionFormatError::UnsupportedVersion(version) => write!(
+                f,
+                "Chat session format version {version} is newer than the supported version {SESSION_FORMAT_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SessionFormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SessionFormatError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for SessionFormatError {
+    fn from(e: serde_json::Error) -> Self {
+        SessionFormatError::Json(e)
+    }
+}
+
+impl ChatSession {
+    /// Returns the messages of the active branch
+    pub fn history(&self) -> &[Content] {
+        self.tree.history()
+    }
+
+    /// Returns the token usage summed over all requests of the chat
+    pub fn usage(&self) -> UsageMetadata {
+        self.turn_usage.iter().cloned().sum()
+    }
+
+    /// Returns the id and timestamps of the session
+    pub fn info(&self) -> SessionInfo {
+        SessionInfo {
+            id: self.id.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// Serializes the session in the current format version
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        let mut value = serde_json::to_value(self)?;
+        if let Value::Object(map) = &mut value {
+            map.insert("version".to_string(), SESSION_FORMAT_VERSION.into());
+        }
+        serde_json::to_string(&value)
+    }
+
+    /// Reads a session saved in any format version, migrating it to the current one
+    ///
+    /// History saved as a bare `Vec<Content<String>>` becomes a session without
+    /// options or usage, with both timestamps set to now. Use
+    /// [`from_json_saved_at`](Self::from_json_saved_at) when the save time is known.
+    ///
+    /// # Errors
+    /// Returns an error if the JSON is invalid or its version is not supported.
+    pub fn from_json(id: impl Into<String>, json: &str) -> Result<Self, SessionFormatError> {
+        Self::from_json_saved_at(id, json, SystemTime::now())
+    }
+
+    /// Like [`from_json`](Self::from_json), but history saved as a bare
+    /// `Vec<Content<String>>` gets `saved_at` as both timestamps
+    ///
+    /// Stores pass the time the data was last written, such as a file's modification
+    /// time, so legacy sessions keep a stable position in [`ChatStore::list`].
+    ///
+    /// # Errors
+    /// Returns an error if the JSON is invalid or its version is not supported.
+    pub fn from_json_saved_at(
+        id: impl Into<String>,
+        json: &str,
+        saved_at: SystemTime,
+    ) -> Result<Self, SessionFormatError> {
+        let mut value: Value = serde_json::from_str(json)?;
+        let version = match &mut value {
+            Value::Array(_) => 0,
+            Value::Object(map) => map
+                .remove("version")
+                .and_then(|version| version.as_u64())
+                .ok_or(SessionFormatError::MissingVersion)?,
+            _ => return Err(SessionFormatError::MissingVersion),
+        };
+
+        let mut session = match version {
+            0 => {
+                let history: Vec<Content> = serde_json::from_value(value)?;
+                ChatSession {
+                    id: String::new(),
+                    tree: ChatTree::from_history(history),
+                    system_instruction: None,
+                    generation_config: None,
+                    safety_settings: None,
+                    tools: None,
+                    cached_content: None,
+                    turn_usage: Vec::new(),
+                    created_at: saved_at,
+                    updated_at: saved_at,
+                }
+            }
+            1 => serde_json::from_value::<ChatSession>(value)?,
+            version => return Err(SessionFormatError::UnsupportedVersion(version)),
+        };
+        session.id = id.into();
+        Ok(session)
+    }
+}
+
+/// Serializes timestamps as milliseconds since the Unix epoch
+mod unix_millis {
+    use super::*;
+
+    pub fn serialize<S: serde::Serializer>(time: &SystemTime, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(to_millis(*time))
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> Result<SystemTime, D::Error> {
+        u64::deserialize(d).map(from_millis)
+    }
+}
+
+fn to_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+fn from_millis(millis: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis)
+}
+
+/// Id and timestamps of a stored session, as returned by [`ChatStore::list`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    /// Key the session is stored under
+    pub id: String,
+    /// When the conversation was started
+    pub created_at: SystemTime,
+    /// When the session was last saved
+    pub updated_at: SystemTime,
+}
+
+/// Storage for chat sessions, keyed by session id
+#[async_trait::async_trait]
+pub trait ChatStore: Send + Sync {
+    /// Saves a session, replacing any session with the same id
+    async fn save(&self, session: &ChatSession) -> Result<(), Box<dyn Error>>;
+
+    /// Loads a session, or returns `None` if there is none with this id
+    async fn load(&self, id: &str) -> Result<Option<ChatSession>, Box<dyn Error>>;
+
+    /// Lists the stored sessions, most recently updated first
+    async fn list(&self) -> Result<Vec<SessionInfo>, Box<dyn Error>>;
+
+    /// Deletes a session, returning whether it existed
+    async fn delete(&self, id: &str) -> Result<bool, Box<dyn Error>>;
+}
+
+/// Error returned when a session id cannot be used as a file name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidSessionIdError(pub String);
+
+impl std::fmt::Display for InvalidSessionIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid session id `{}`: use letters, digits, `-`, `_` and `.`, not starting with `.`",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidSessionIdError {}
+
+/// Stores each session as `{id}.json` in a directory
+///
+/// The directory is created on the first save. Files are replaced atomically, so a
+/// crash while saving never leaves a partly written session, and concurrent saves of
+/// the same session each write their own temporary file.
+///
+/// Listing reads only the timestamps of each file and skips, with a warning, files
+/// that are not valid sessions.
+#[derive(Debug, Clone)]
+pub struct JsonFileStore {
+    dir: PathBuf,
+}
+
+impl JsonFileStore {
+    /// Creates a store keeping its files in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Reads the id and timestamps of a session file without parsing its history
+    async fn read_info(path: &Path, id: &str) -> Result<SessionInfo, Box<dyn Error>> {
+        /// The fields of a saved session that `list` needs
+        #[derive(Deserialize)]
+        struct Header {
+            version: u64,
+            #[serde(with = "unix_millis")]
+            created_at: SystemTime,
+            #[serde(with = "unix_millis")]
+            updated_at: SystemTime,
+        }
+
+        let json = tokio::fs::read_to_string(path).await?;
+        let (created_at, updated_at) = if json.trim_start().starts_with('[') {
+            // Legacy history carries no timestamps, as in `load`
+            let modified = tokio::fs::metadata(path).await?.modified()?;
+            (modified, modified)
+        } else {
+            let header: Header = serde_json::from_str(&json).map_err(SessionFormatError::from)?;
+            if header.version > SESSION_FORMAT_VERSION {
+                return Err(SessionFormatError::UnsupportedVersion(header.version).into());
+            }
+            (header.created_at, header.updated_at)
+        };
+        Ok(SessionInfo {
+            id: id.to_string(),
+            created_at,
+            updated_at,
+        })
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf, InvalidSessionIdError> {
+        let valid = !id.is_empty()
+            && !id.starts_with('.')
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(InvalidSessionIdError(id.to_string()));
+        }
+        Ok(self.dir.join(format!("{id}.json")))
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatStore for JsonFileStore {
+    async fn save(&self, session: &ChatSession) -> Result<(), Box<dyn Error>> {
+        static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+
+        let path = self.path(&session.id)?;
+        let temp_path = path.with_extension(format!(
+            "json.{}-{}.tmp",
+            std::process::id(),
+            NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+        ));
+        tokio::fs::create_dir_all(&self.dir).await?;
+        if let Err(e) = tokio::fs::write(&temp_path, session.to_json()?).await {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(e.into());
+        }
+        tokio::fs::rename(&temp_path, &path).await?;
+        Ok(())
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<ChatSession>, Box<dyn Error>> {
+        let path = self.path(id)?;
+        let json = match tokio::fs::read_to_string(&path).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        // Legacy history carries no timestamps, so it keeps the file's
+        let modified = tokio::fs::metadata(&path).await?.modified()?;
+        Ok(Some(ChatSession::from_json_saved_at(id, &json, modified)?))
+    }
+
+    async fn list(&self) -> Result<Vec<SessionInfo>, Box<dyn Error>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut sessions = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                tracing::warn!(
+                    "Skipping session file with a non-UTF-8 name: {}",
+                    path.display()
+                );
+                continue;
+            };
+            if let Err(e) = self.path(id) {
+                tracing::warn!("Skipping session file {}: {}", path.display(), e);
+                continue;
+            }
+            // One unreadable session must not hide the others
+            match Self::read_info(&path, id).await {
+                Ok(info) => sessions.push(info),
+                Err(e) => tracing::warn!("Skipping session file {}: {}", path.display(), e),
+            }
+        }
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.updated_at));
+        Ok(sessions)
+    }
+
+    async fn delete(&self, id: &str) -> Result<bool, Box<dyn Error>> {
+        match tokio::fs::remove_file(self.path(id)?).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    use rusqlite::{Connection, OptionalExtension, params};
+
+    use super::*;
+
+    /// Version of the database schema, kept in SQLite's `user_version`
+    const SCHEMA_VERSION: i64 = 1;
+
+    /// Stores sessions in an embedded SQLite database
+    ///
+    /// Each session is a row holding its JSON, in the same versioned format as
+    /// [`JsonFileStore`], with its timestamps in separate columns for listing.
+    /// Queries run on Tokio's blocking thread pool.
+    #[derive(Clone)]
+    pub struct SqliteStore {
+        connection: Arc<Mutex<Connection>>,
+    }
+
+    impl SqliteStore {
+        /// Opens or creates the database at `path`, creating its tables if needed
+        pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, rusqlite::Error> {
+            Self::from_connection(Connection::open(path)?)
+        }
+
+        /// Creates a store in a database that lives as long as the store
+        pub fn in_memory() -> Result<Self, rusqlite::Error> {
+            Self::from_connection(Connection::open_in_memory()?)
+        }
+
+        /// Uses an existing connection, creating the store's tables if needed
+        pub fn from_connection(connection: Connection) -> Result<Self, rusqlite::Error> {
+            Self::migrate(&connection)?;
+            Ok(Self {
+                connection: Arc::new(Mutex::new(connection)),
+            })
+        }
+
+        /// Brings the database schema up to `SCHEMA_VERSION`
+        fn migrate(connection: &Connection) -> Result<(), rusqlite::Error> {
+            let version: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+            if version < 1 {
+                connection.execute_batch(
+                    "CREATE TABLE IF NOT EXISTS chat_sessions (
+                        id TEXT PRIMARY KEY,
+                        data TEXT NOT NULL,
+                        created_at INTEGER NOT NULL,
+                        updated_at INTEGER NOT NULL
+                    );",
+                )?;
+            }
+            if version < SCHEMA_VERSION {
+                connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+            }
+            Ok(())
+        }
+
+        /// Runs `query` on the blocking thread pool with the locked connection
+        async fn run<R, F>(&self, query: F) -> Result<R, Box<dyn Error>>
+        where
+            R: Send + 'static,
+            F: FnOnce(&Connection) -> Result<R, rusqlite::Error> + Send + 'static,
+        {
+            let connection = Arc::clone(&self.connection);
+            let result = tokio::task::spawn_blocking(move || {
+                let connection = connection.lock().unwrap_or_else(PoisonError::into_inner);
+                query(&connection)
+            })
+            .await?;
+            Ok(result?)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ChatStore for SqliteStore {
+        async fn save(&self, session: &ChatSession) -> Result<(), Box<dyn Error>> {
+            let id = session.id.clone();
+            let data = session.to_json()?;
+            let created_at = to_millis(session.created_at) as i64;
+            let updated_at = to_millis(session.updated_at) as i64;
+            self.run(move |connection| {
+                connection.execute(
+                    "INSERT INTO chat_sessions (id, data, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT(id) DO UPDATE SET
+                        data = excluded.data,
+                        created_at = excluded.created_at,
+                        updated_at = excluded.updated_at",
+                    params![id, data, created_at, updated_at],
+                )
+            })
+            .await?;
+            Ok(())
+        }
+
+        async fn load(&self, id: &str) -> Result<Option<ChatSession>, Box<dyn Error>> {
+            let key = id.to_string();
+            let data: Option<String> = self
+                .run(move |connection| {
+                    connection
+                        .query_row(
+                            "SELECT data FROM chat_sessions WHERE id = ?1",
+                            [key],
+                            |row| row.get(0),
+                        )
+                        .optional()
+                })
+                .await?;
+            match data {
+                Some(json) => Ok(Some(ChatSession::from_json(id, &json)?)),
+                None => Ok(None),
+            }
+        }
+
+        async fn list(&self) -> Result<Vec<SessionInfo>, Box<dyn Error>> {
+            self.run(|connection| {
+                let mut statement = connection.prepare(
+                    "SELECT id, created_at, updated_at FROM chat_sessions
+                     ORDER BY updated_at DESC",
+                )?;
+                let rows = statement.query_map([], |row| {
+                    Ok(SessionInfo {
+                        id: row.get(0)?,
+                        created_at: from_millis(row.get::<_, i64>(1)?.max(0) as u64),
+                        updated_at: from_millis(row.get::<_, i64>(2)?.max(0) as u64),
+                    })
+                })?;
+                rows.collect()
+            })
+            .await
+        }
+
+        async fn delete(&self, id: &str) -> Result<bool, Box<dyn Error>> {
+            let key = id.to_string();
+            let deleted = self
+                .run(move |connection| {
+                    connection.execute("DELETE FROM chat_sessions WHERE id = ?1", [key])
+                })
+                .await?;
+            Ok(deleted > 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto_content::{JsonString, Part};
+
+    fn session(id: &str, updated_millis: u64) -> ChatSession {
+        let history = vec![
+            Content::user(vec![
+                Part::builder()
+                    .text(JsonString::new("Hello".to_string()))
+                    .build(),
+            ]),
+            Content::model(vec![
+                Part::builder()
+                    .text(JsonString::new("Hi".to_string()))
+                    .build(),
+            ]),
+        ];
+        let mut session =
+            ChatSession::from_json(id, &serde_json::to_string(&history).unwrap()).unwrap();
+        session.cached_content = Some("cachedContents/abc".to_string());
+        session.created_at = from_millis(1_000);
+        session.updated_at = from_millis(updated_millis);
+        session
+    }
+
+    #[test]
+    fn test_session_format_versions() {
+        let legacy = r#"[{"role": "user", "parts": [{"text": "Hello"}]}]"#;
+        let migrated = ChatSession::from_json("old", legacy).unwrap();
+        assert_eq!(migrated.id, "old");
+        assert_eq!(migrated.history().len(), 1);
+
+        let json = session("s", 2_000).to_json().unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], SESSION_FORMAT_VERSION);
+        assert_eq!(value["updated_at"], 2_000);
+        assert!(value.get("id").is_none());
+
+        let restored = ChatSession::from_json("s", &json).unwrap();
+        assert_eq!(restored.history().len(), 2);
+        assert_eq!(
+            restored.cached_content.as_deref(),
+            Some("cachedContents/abc")
+        );
+        assert_eq!(restored.info(), session("s", 2_000).info());
+
+        assert!(matches!(
+            ChatSession::from_json("s", r#"{"version": 9}"#),
+            Err(SessionFormatError::UnsupportedVersion(9))
+        ));
+        assert!(matches!(
+            ChatSession::from_json("s", r#"{"tree": {"nodes": []}}"#),
+            Err(SessionFormatError::MissingVersion)
+        ));
+    }
+
+    async fn exercise_store(store: &dyn ChatStore) {
+        assert!(store.load("a").await.unwrap().is_none());
+        assert!(store.list().await.unwrap().is_empty());
+
+        store.save(&session("a", 2_000)).await.unwrap();
+        store.save(&session("b", 3_000)).await.unwrap();
+        store.save(&session("a", 4_000)).await.unwrap();
+
+        let ids: Vec<String> = store
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|info| info.id)
+            .collect();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        let loaded = store.load("a").await.unwrap().unwrap();
+        assert_eq!(loaded.history().len(), 2);
+        assert_eq!(loaded.updated_at, from_millis(4_000));
+
+        assert!(store.delete("a").await.unwrap());
+        assert!(!store.delete("a").await.unwrap());
+        assert_eq!(store.list().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_json_file_store() {
+        let dir = std::env::temp_dir().join(format!("gemini-store-test-{}", std::process::id()));
+        let store = JsonFileStore::new(&dir);
+        exercise_store(&store).await;
+
+        // History saved before sessions existed is migrated on load, dated by the file
+        std::fs::write(
+            dir.join("legacy.json"),
+            r#"[{"role": "user", "parts": []}]"#,
+        )
+        .unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(dir.join("legacy.json"))
+            .unwrap()
+            .set_modified(from_millis(1_500))
+            .unwrap();
+        let legacy = store.load("legacy").await.unwrap().unwrap();
+        assert_eq!(legacy.history().len(), 1);
+        assert_eq!(legacy.created_at, from_millis(1_500));
+        assert_eq!(legacy.updated_at, from_millis(1_500));
+
+        assert!(store.load("../escape").await.is_err());
+
+        // Unreadable files are skipped when listing
+        std::fs::write(dir.join("broken.json"), "{not json").unwrap();
+        std::fs::write(dir.join("bad name.json"), "[]").unwrap();
+        let ids: Vec<String> = store
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|info| info.id)
+            .collect();
+        assert_eq!(ids, vec!["b", "legacy"]);
+        assert_eq!(
+            store.list().await.unwrap()[1].updated_at,
+            from_millis(1_500)
+        );
+
+        // Concurrent saves of one session never share a temporary file
+        let saves = (0..8).map(|i| {
+            let store = store.clone();
+            tokio::spawn(async move { store.save(&session("busy", 5_000 + i)).await.is_ok() })
+        });
+        for save in futures::future::join_all(saves).await {
+            assert!(save.unwrap());
+        }
+        assert!(store.load("busy").await.unwrap().is_some());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        exercise_store(&SqliteStore::in_memory().unwrap()).await;
+    }
+}
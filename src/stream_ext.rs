@@ -10,6 +10,8 @@ use std::pin::Pin;
 pub use futures::stream::BoxStream;
 use futures::{Stream, StreamExt, stream};
 
+use super::api::StreamTextMode;
+use super::dto_content::TextPayload;
 use super::dto_response::GenerateContentResponse;
 
 /// Type alias for boxed error used in stream results
@@ -57,10 +59,7 @@ impl<T> IntoInnerStreamExt<T> for BoxResponseStream<T> {
                 match stream.next().await {
                     Some(Ok(response)) => {
                         // Extract typed data from the response
-                        if let Some(candidate) = response.first_candidate()
-                            && let Some(first_part) = candidate.content.parts().first()
-                            && let Some(data) = first_part.text()
-                        {
+                        if let Some(data) = response.first_text() {
                             return Some((Ok(data.clone()), stream));
                         }
                         // Continue to next item if no data in this response
@@ -76,3 +75,150 @@ impl<T> IntoInnerStreamExt<T> for BoxResponseStream<T> {
         }))
     }
 }
+
+/// A piece of a streamed response from a thinking model
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThinkingEvent<T> {
+    /// A chunk of a thought summary
+    Thought(String),
+    /// A chunk of the answer
+    Answer(T),
+}
+
+/// Extension trait for separating thought summaries from the answer in a stream.
+pub trait ThinkingStreamExt<T>: Sized {
+    /// Splits each streamed response into thought and answer events.
+    ///
+    /// Every text part of the first candidate becomes one event, in order. Parts
+    /// without text are skipped. Events carry only new text: pass the
+    /// [`StreamTextMode`] of the API that produced the stream, and with
+    /// `StreamTextMode::Cumulative` the answer text already yielded is cut off before
+    /// parsing as `T`. A typed answer whose new text does not parse on its own is
+    /// yielded as parsed so far.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// use gemini::{GeminiStreamingApi, ThinkingEvent, ThinkingStreamExt};
+    ///
+    /// let mut events = response_stream.into_thinking_events(client.stream_text_mode());
+    /// while let Some(event) = events.next().await {
+    ///     match event? {
+    ///         ThinkingEvent::Thought(text) => eprint!("{text}"),
+    ///         ThinkingEvent::Answer(text) => print!("{text}"),
+    ///     }
+    /// }
+    /// ```
+    fn into_thinking_events(
+        self,
+        text_mode: StreamTextMode,
+    ) -> BoxStream<'static, Result<ThinkingEvent<T>, BoxError>>
+    where
+        T: TextPayload + Send + 'static;
+}
+
+impl<T> ThinkingStreamExt<T> for BoxResponseStream<T> {
+    fn into_thinking_events(
+        self,
+        text_mode: StreamTextMode,
+    ) -> BoxStream<'static, Result<ThinkingEvent<T>, BoxError>>
+    where
+        T: TextPayload + Send + 'static,
+    {
+        // Length of the answer text received so far, with `StreamTextMode::Cumulative`
+        let mut received = 0;
+        Box::pin(self.flat_map(move |result| {
+            let events: Vec<Result<ThinkingEvent<T>, BoxError>> = match result {
+                Ok(response) => {
+                    let parts = response
+                        .candidates
+                        .into_iter()
+                        .next()
+                        .map(|candidate| candidate.content.into_parts())
+                        .unwrap_or_default();
+                    // Only the first answer text of a chunk is cumulative
+                    let mut cumulative = text_mode == StreamTextMode::Cumulative;
+                    parts
+                        .into_iter()
+                        .filter_map(|part| {
+                            if let Some(thought) = part.thought_text() {
+                                return Some(ThinkingEvent::Thought(thought.to_string()));
+                            }
+                            let raw = part.raw_text().filter(|_| part.text().is_some());
+                            let delta = match raw {
+                                Some(raw) if std::mem::take(&mut cumulative) => {
+                                    raw.get(received..)?.to_string()
+                                }
+                                _ => return part.into_text().map(ThinkingEvent::Answer),
+                            };
+                            received += delta.len();
+                            if delta.is_empty() {
+                                return None;
+                            }
+                            match T::from_text(delta) {
+                                Ok(value) => Some(ThinkingEvent::Answer(value)),
+                                Err(_) => part.into_text().map(ThinkingEvent::Answer),
+                            }
+                        })
+                        .map(Ok)
+                        .collect()
+                }
+                Err(e) => vec![Err(e)],
+            };
+            stream::iter(events)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Streams one chunk per part, as `GeminiV1Beta` yields them
+    fn response_stream(parts: Vec<serde_json::Value>) -> BoxResponseStream<String> {
+        let chunks: Vec<Result<GenerateContentResponse<String>, BoxError>> = parts
+            .into_iter()
+            .map(|part| {
+                let json = serde_json::json!({"candidates": [{"content": {
+                    "role": "model", "parts": [part]
+                }}]});
+                Ok(serde_json::from_value(json).unwrap())
+            })
+            .collect();
+        Box::pin(stream::iter(chunks))
+    }
+
+    #[tokio::test]
+    async fn test_thinking_events_are_deltas() {
+        let parts = || {
+            vec![
+                serde_json::json!({"text": "Hmm, ", "thought": true}),
+                serde_json::json!({"text": "sure.", "thought": true}),
+                serde_json::json!({"text": "Ans"}),
+                serde_json::json!({"text": "Answer"}),
+            ]
+        };
+        let expected = vec![
+            ThinkingEvent::Thought("Hmm, ".to_string()),
+            ThinkingEvent::Thought("sure.".to_string()),
+            ThinkingEvent::Answer("Ans".to_string()),
+            ThinkingEvent::Answer("wer".to_string()),
+        ];
+
+        let events: Vec<_> = response_stream(parts())
+            .into_thinking_events(StreamTextMode::Cumulative)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(events, expected);
+
+        // Streams of deltas are passed through
+        let mut parts = parts();
+        parts[3] = serde_json::json!({"text": "wer"});
+        let events: Vec<_> = response_stream(parts)
+            .into_thinking_events(StreamTextMode::Delta)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(events, expected);
+    }
+}
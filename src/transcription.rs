@@ -0,0 +1,199 @@
+//! Timestamped transcription of audio and video.
+//!
+//! [`Transcription`] builds a prompt that asks the model for one line per segment,
+//! e.g. `[01:05 - 01:12] Speaker 1: Hello there`, and parses responses in that
+//! format back into [`TimedSegment`]s keyed by `Duration`.
+
+use std::time::Duration;
+
+use super::dto_content::{JsonString, Part};
+use super::dto_response::GenerateContentResponse;
+
+/// A transcribed segment of audio or video
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimedSegment {
+    /// Offset at which the segment starts
+    pub start: Duration,
+    /// Offset at which the segment ends, if the model gave one
+    pub end: Option<Duration>,
+    /// Speaker label, when speakers were requested
+    pub speaker: Option<String>,
+    /// Transcribed text
+    pub text: String,
+}
+
+/// Prompt and parser for timestamped transcriptions
+///
+/// # Example
+/// ```ignore
+/// let transcription = Transcription::new().with_speakers(true);
+/// let request = GenerateContentRequest::builder()
+///     .add_content(Content::user(vec![
+///         Part::from_uri(file.uri.clone(), file.mime_type.clone()),
+///         transcription.to_part(),
+///     ]))
+///     .build();
+///
+/// let response = client.generate_content(request).await?;
+/// for segment in transcription.parse_response(&response) {
+///     println!("{:?}: {}", segment.start, segment.text);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Transcription {
+    speakers: bool,
+}
+
+impl Transcription {
+    /// Creates a transcription without speaker labels
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether each segment is labelled with its speaker
+    #[must_use]
+    pub fn with_speakers(mut self, speakers: bool) -> Self {
+        self.speakers = speakers;
+        self
+    }
+
+    /// Returns the prompt asking for a transcription in the parsed format
+    pub fn prompt(&self) -> String {
+        let line = if self.speakers {
+            "[MM:SS - MM:SS] Speaker: text"
+        } else {
+            "[MM:SS - MM:SS] text"
+        };
+        let mut prompt = format!(
+            "Transcribe the audio. Write one segment per line in the format `{line}`, \
+             where the timestamps are the start and end of the segment. \
+             Use HH:MM:SS for offsets of an hour or more. Do not add any other text."
+        );
+        if self.speakers {
+            prompt.push_str(
+                " Identify the speakers by name when they are introduced, \
+                 otherwise as Speaker 1, Speaker 2 and so on.",
+            );
+        }
+        prompt
+    }
+
+    /// Returns the prompt as a text part
+    pub fn to_part(&self) -> Part {
+        Part::builder().text(JsonString::new(self.prompt())).build()
+    }
+
+    /// Parses a transcription, skipping lines without a leading timestamp
+    pub fn parse(&self, text: &str) -> Vec<TimedSegment> {
+        text.lines()
+            .filter_map(|line| self.parse_line(line))
+            .collect()
+    }
+
+    /// Parses the first candidate's answer text of a response
+    pub fn parse_response(&self, response: &GenerateContentResponse) -> Vec<TimedSegment> {
+        let Some(content) = response.first_content() else {
+            return Vec::new();
+        };
+        let text: String = content
+            .answer_parts()
+            .filter_map(|part| part.text())
+            .map(String::as_str)
+            .collect();
+        self.parse(&text)
+    }
+
+    fn parse_line(&self, line: &str) -> Option<TimedSegment> {
+        let line = line.trim().trim_start_matches(['-', '*']).trim_start();
+        let (timestamps, rest) = line.strip_prefix('[')?.split_once(']')?;
+
+        let (start, end) = match timestamps.split_once(['-', '–']) {
+            Some((start, end)) => (parse_timestamp(start)?, Some(parse_timestamp(end)?)),
+            None => (parse_timestamp(timestamps)?, None),
+        };
+
+        let rest = rest.trim();
+        let (speaker, text) = match rest.split_once(':') {
+            Some((speaker, text)) if self.speakers && !speaker.trim().is_empty() => {
+                (Some(speaker.trim().to_string()), text.trim())
+            }
+            _ => (None, rest),
+        };
+
+        Some(TimedSegment {
+            start,
+            end,
+            speaker,
+            text: text.to_string(),
+        })
+    }
+}
+
+/// Parses `MM:SS`, `HH:MM:SS` or either with fractional seconds
+fn parse_timestamp(text: &str) -> Option<Duration> {
+    let fields: Vec<&str> = text.trim().split(':').collect();
+    let (hours, minutes, seconds) = match fields[..] {
+        [minutes, seconds] => ("0", minutes, seconds),
+        [hours, minutes, seconds] => (hours, minutes, seconds),
+        _ => return None,
+    };
+
+    let whole_minutes = hours
+        .parse::<u64>()
+        .ok()?
+        .checked_mul(60)?
+        .checked_add(minutes.parse().ok()?)?;
+    let seconds: f64 = seconds.parse().ok()?;
+    if !(0.0..60.0).contains(&seconds) {
+        return None;
+    }
+    Duration::from_secs(whole_minutes.checked_mul(60)?)
+        .checked_add(Duration::from_secs_f64(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamps() {
+        assert_eq!(parse_timestamp("01:05"), Some(Duration::from_secs(65)));
+        assert_eq!(
+            parse_timestamp(" 1:02:03 "),
+            Some(Duration::from_secs(3723))
+        );
+        assert_eq!(
+            parse_timestamp("00:01.5"),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(parse_timestamp("00:75"), None);
+        assert_eq!(parse_timestamp("5"), None);
+
+        // Timestamps too large for a `Duration` are rejected instead of overflowing
+        assert_eq!(parse_timestamp(&format!("{}:00:00", u64::MAX)), None);
+        assert_eq!(parse_timestamp(&format!("{}:59", u64::MAX / 60)), None);
+    }
+
+    #[test]
+    fn test_parse_transcription() {
+        let text = "\
+[00:00 - 00:04] Alice: Welcome to the show.
+[00:04 - 00:09] Bob: Thanks: glad to be here.
+
+Some commentary the model added anyway
+- [01:00:00] Alice: Goodbye";
+
+        let segments = Transcription::new().with_speakers(true).parse(text);
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].start, Duration::ZERO);
+        assert_eq!(segments[0].end, Some(Duration::from_secs(4)));
+        assert_eq!(segments[0].speaker.as_deref(), Some("Alice"));
+        assert_eq!(segments[1].text, "Thanks: glad to be here.");
+        assert_eq!(segments[2].start, Duration::from_secs(3600));
+        assert_eq!(segments[2].end, None);
+
+        let segments = Transcription::new().parse("[00:10 - 00:12] Note: read slowly");
+        assert_eq!(segments[0].speaker, None);
+        assert_eq!(segments[0].text, "Note: read slowly");
+    }
+}
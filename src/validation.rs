@@ -0,0 +1,1167 @@
+//! Validation and repair of structured responses.
+//!
+//! A typed request fails as a whole when the model's JSON does not deserialize
+//! into `T`. [`GeminiApi::generate_content_validated`] instead checks the raw
+//! answer against the schema sent in the `GenerationConfig` and, depending on the
+//! [`RepairStrategy`], fixes malformed JSON locally or re-prompts the model with
+//! the validation errors. The returned [`RepairDiagnostics`] record every repair.
+
+use std::error::Error;
+
+use serde_json::Value;
+
+use super::api::GeminiApi;
+use super::dto_content::{Content, JsonString, Part, TextPayload};
+use super::dto_request::{GenerateContentRequest, ResponseMimeType};
+use super::dto_response::GenerateContentResponse;
+use super::schema::SchemaIssue;
+
+/// How invalid structured responses are repaired
+///
+/// The default strategy repairs nothing and fails on the first invalid response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RepairStrategy {
+    /// Fix code fences, trailing commas, truncation and trailing text locally
+    pub local_fixes: bool,
+    /// Number of times the model is re-prompted with the validation errors
+    pub max_reprompts: u32,
+}
+
+impl RepairStrategy {
+    /// Fails on the first invalid response
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Fixes malformed JSON locally without further requests
+    pub fn local() -> Self {
+        Self {
+            local_fixes: true,
+            max_reprompts: 0,
+        }
+    }
+
+    /// Re-prompts the model with the validation errors up to `max_reprompts` times
+    pub fn reprompt(max_reprompts: u32) -> Self {
+        Self {
+            local_fixes: false,
+            max_reprompts,
+        }
+    }
+
+    /// Also fixes malformed JSON locally before re-prompting
+    #[must_use]
+    pub fn with_local_fixes(mut self) -> Self {
+        self.local_fixes = true;
+        self
+    }
+}
+
+/// A local fix applied to malformed JSON
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalFix {
+    /// Removed a Markdown code fence around the JSON
+    StrippedCodeFence,
+    /// Removed the given number of commas before closing brackets
+    RemovedTrailingCommas(usize),
+    /// Completed output that ended in the middle of a value
+    CompletedTruncatedOutput,
+    /// Removed text following the JSON value
+    RemovedTrailingText(String),
+}
+
+impl std::fmt::Display for LocalFix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocalFix::StrippedCodeFence => write!(f, "stripped a code fence"),
+            LocalFix::RemovedTrailingCommas(count) => {
+                write!(f, "removed {} trailing comma(s)", count)
+            }
+            LocalFix::CompletedTruncatedOutput => write!(f, "completed truncated output"),
+            LocalFix::RemovedTrailingText(text) => write!(f, "removed trailing text {:?}", text),
+        }
+    }
+}
+
+/// What was repaired to obtain a valid response
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairDiagnostics {
+    /// Local fixes applied to the returned response
+    pub fixes: Vec<LocalFix>,
+    /// Validation issues of every response that was rejected and re-prompted, in order
+    pub rejected: Vec<Vec<SchemaIssue>>,
+}
+
+impl RepairDiagnostics {
+    /// Returns true if the returned response needed any repair
+    pub fn is_repaired(&self) -> bool {
+        !self.fixes.is_empty() || !self.rejected.is_empty()
+    }
+
+    /// Returns the number of re-prompts that were made
+    pub fn reprompts(&self) -> usize {
+        self.rejected.len()
+    }
+}
+
+/// A response whose structured answer passed validation
+#[derive(Debug)]
+pub struct ValidatedResponse<T> {
+    /// The response, with the repaired answer if a local fix was applied
+    pub response: GenerateContentResponse<T>,
+    /// What was repaired to obtain the response
+    pub diagnostics: RepairDiagnostics,
+}
+
+/// Error returned when a response is still invalid once the repair strategy is exhausted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseValidationError {
+    /// Validation issues of the last response
+    pub issues: Vec<SchemaIssue>,
+    /// Repairs attempted before giving up
+    pub diagnostics: RepairDiagnostics,
+}
+
+impl std::fmt::Display for ResponseValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Response does not match the schema after {} re-prompt(s)",
+            self.diagnostics.reprompts()
+        )?;
+        for issue in &self.issues {
+            write!(f, "; {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ResponseValidationError {}
+
+/// Generates content, validating and repairing the answer as described by `strategy`
+pub(crate) async fn generate_validated<A, T>(
+    api: &A,
+    request: GenerateContentRequest<T>,
+    strategy: RepairStrategy,
+) -> Result<ValidatedResponse<T>, Box<dyn Error>>
+where
+    A: GeminiApi + Sync + ?Sized,
+    T: TextPayload + Send + 'static,
+{
+    let schema = request
+        .generation_config()
+        .and_then(|config| config.schema())
+        .map(|(schema, _)| schema.clone());
+    let enum_response = request
+        .generation_config()
+        .is_some_and(|config| config.response_mime_type == Some(ResponseMimeType::TextEnum));
+    let mut request = request.into_untyped();
+    let mut diagnostics = RepairDiagnostics::default();
+
+    loop {
+        let response = api.generate_content(request.clone()).await?;
+        let Some(text) = answer_text(&response) else {
+            // Blocked or empty responses have nothing to validate
+            let response = retype(response, None)?;
+            return Ok(ValidatedResponse {
+                response,
+                diagnostics,
+            });
+        };
+
+        let checked = if enum_response {
+            check_variant::<T>(&text, schema.as_ref())
+        } else {
+            check::<T>(&text, schema.as_ref(), strategy.local_fixes)
+        };
+        match checked {
+            Ok((text, fixes)) => {
+                diagnostics.fixes = fixes;
+                let response = retype(response, Some(text))?;
+                return Ok(ValidatedResponse {
+                    response,
+                    diagnostics,
+                });
+            }
+            Err(issues) if diagnostics.reprompts() < strategy.max_reprompts as usize => {
+                tracing::debug!(
+                    "Re-prompting after invalid response: {} issue(s)",
+                    issues.len()
+                );
+                if let Some(candidate) = response.candidates.into_iter().next() {
+                    request.push_content(candidate.content);
+                }
+                request.push_content(feedback(&issues, enum_response));
+                diagnostics.rejected.push(issues);
+            }
+            Err(issues) => {
+                return Err(Box::new(ResponseValidationError {
+                    issues,
+                    diagnostics,
+                }));
+            }
+        }
+    }
+}
+
+/// Returns the first candidate's answer text, leaving out thought summaries
+fn answer_text(response: &GenerateContentResponse<String>) -> Option<String> {
+    let texts: Vec<&str> = response
+        .first_content()?
+        .parts()
+        .iter()
+        .filter(|part| !part.is_thought())
+        .filter_map(|part| part.text().map(String::as_str))
+        .collect();
+    (!texts.is_empty()).then(|| texts.concat())
+}
+
+/// Parses, repairs and validates an answer, returning the text to deserialize
+fn check<T: TextPayload>(
+    text: &str,
+    schema: Option<&Value>,
+    local_fixes: bool,
+) -> Result<(String, Vec<LocalFix>), Vec<SchemaIssue>> {
+    let invalid = |error: &dyn std::fmt::Display| {
+        vec![SchemaIssue {
+            path: "#".to_string(),
+            message: format!("invalid JSON: {}", error),
+        }]
+    };
+
+    let (value, fixes) = match serde_json::from_str::<Value>(text) {
+        Ok(value) => (value, Vec::new()),
+        Err(error) if local_fixes => repair_json(text).map_err(|_| invalid(&error))?,
+        Err(error) => return Err(invalid(&error)),
+    };
+
+    if let Some(schema) = schema {
+        let issues = validate_json(&value, schema);
+        if !issues.is_empty() {
+            return Err(issues);
+        }
+    }
+
+    let text = if fixes.is_empty() {
+        text.to_string()
+    } else {
+        value.to_string()
+    };
+    // The schema may be looser than `T`, e.g. when it was written by hand
+    if let Err(error) = T::from_text(text.clone()) {
+        return Err(vec![SchemaIssue {
+            path: "#".to_string(),
+            message: error.to_string(),
+        }]);
+    }
+    Ok((text, fixes))
+}
+
+/// Validates a `text/x.enum` answer, which is a bare variant name rather than JSON
+fn check_variant<T: TextPayload>(
+    text: &str,
+    schema: Option<&Value>,
+) -> Result<(String, Vec<LocalFix>), Vec<SchemaIssue>> {
+    if let Some(schema) = schema {
+        let issues = validate_json(&Value::String(text.trim().to_string()), schema);
+        if !issues.is_empty() {
+            return Err(issues);
+        }
+    }
+    if let Err(error) = T::from_text(text.to_string()) {
+        return Err(vec![SchemaIssue {
+            path: "#".to_string(),
+            message: error.to_string(),
+        }]);
+    }
+    Ok((text.to_string(), Vec::new()))
+}
+
+/// Builds the user turn asking the model to correct its previous answer
+fn feedback(issues: &[SchemaIssue], enum_response: bool) -> Content {
+    let mut text = "Your previous response did not match the required JSON schema:\n".to_string();
+    for issue in issues {
+        text.push_str(&format!("- {}\n", issue));
+    }
+    if enum_response {
+        text.push_str("Respond again with only one of the allowed values.");
+    } else {
+        text.push_str("Respond again with only the corrected JSON.");
+    }
+    Content::user(vec![Part::builder().text(JsonString::new(text)).build()])
+}
+
+/// Converts a raw response into one typed as `T`, replacing the answer text if given
+fn retype<T: TextPayload>(
+    response: GenerateContentResponse<String>,
+    text: Option<String>,
+) -> Result<GenerateContentResponse<T>, serde_json::Error> {
+    let mut value = serde_json::to_value(&response)?;
+    if let Some(text) = text
+        && let Some(Value::Array(parts)) = value.pointer_mut("/candidates/0/content/parts")
+    {
+        let mut text = Some(text);
+        parts.retain_mut(|part| {
+            if part.get("thought") == Some(&Value::Bool(true)) || part.get("text").is_none() {
+                return true;
+            }
+            match text.take() {
+                Some(text) => {
+                    part["text"] = Value::String(text);
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+    serde_json::from_value(value)
+}
+
+/// Fixes common defects of model-written JSON
+///
+/// Strips Markdown code fences, removes trailing commas and text after the value,
+/// and completes output that was cut off, e.g. by `max_output_tokens`.
+///
+/// # Errors
+/// Returns the parse error if the text cannot be repaired.
+pub fn repair_json(text: &str) -> Result<(Value, Vec<LocalFix>), serde_json::Error> {
+    let mut fixes = Vec::new();
+    let mut text = text.trim();
+    if let Some(inner) = strip_code_fence(text) {
+        text = inner;
+        fixes.push(LocalFix::StrippedCodeFence);
+    }
+
+    let (cleaned, removed) = remove_trailing_commas(text);
+    if removed > 0 {
+        fixes.push(LocalFix::RemovedTrailingCommas(removed));
+    }
+
+    let mut values = serde_json::Deserializer::from_str(&cleaned).into_iter::<Value>();
+    match values.next() {
+        Some(Ok(value)) => {
+            let rest = cleaned[values.byte_offset()..].trim();
+            if !rest.is_empty() {
+                fixes.push(LocalFix::RemovedTrailingText(rest.to_string()));
+            }
+            Ok((value, fixes))
+        }
+        Some(Err(error)) if error.is_eof() => {
+            let value = complete_truncated(&cleaned).ok_or(error)?;
+            fixes.push(LocalFix::CompletedTruncatedOutput);
+            Ok((value, fixes))
+        }
+        Some(Err(error)) => Err(error),
+        None => serde_json::from_str(&cleaned).map(|value| (value, fixes)),
+    }
+}
+
+/// What the scanner in `complete_truncated` expects next inside a container
+#[derive(Clone, Copy, PartialEq)]
+enum Expect {
+    Key,
+    Colon,
+    Value,
+    CommaOrEnd,
+}
+
+/// Closes the strings and brackets left open by truncated JSON
+///
+/// Keeps a partial last string if that yields valid JSON, and otherwise drops
+/// everything after the last complete value. At most two completions are parsed:
+/// both are built from the brackets open at the end of the text, since the
+/// brackets open after the last complete value are a prefix of those.
+fn complete_truncated(text: &str) -> Option<Value> {
+    // Open containers as (closing bracket, what comes next)
+    let mut frames: Vec<(char, Expect)> = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut string_is_key = false;
+    let mut literal_start: Option<usize> = None;
+    // Prefix length and container depth after the last complete value or opened container
+    let mut last_complete: Option<(usize, usize)> = None;
+
+    let value_done = |frames: &mut Vec<(char, Expect)>, end: usize| {
+        if let Some((_, expect)) = frames.last_mut() {
+            *expect = Expect::CommaOrEnd;
+        }
+        Some((end, frames.len()))
+    };
+
+    for (index, c) in text.char_indices() {
+        let end = index + c.len_utf8();
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    in_string = false;
+                    if string_is_key {
+                        if let Some((_, expect)) = frames.last_mut() {
+                            *expect = Expect::Colon;
+                        }
+                    } else {
+                        last_complete = value_done(&mut frames, end);
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        if c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.') {
+            literal_start.get_or_insert(index);
+            continue;
+        }
+        if literal_start.take().is_some() {
+            last_complete = value_done(&mut frames, index);
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                string_is_key = frames.last().is_some_and(|frame| frame.1 == Expect::Key);
+            }
+            '{' | '[' => {
+                frames.push(if c == '{' {
+                    ('}', Expect::Key)
+                } else {
+                    (']', Expect::Value)
+                });
+                last_complete = Some((end, frames.len()));
+            }
+            '}' | ']' => {
+                frames.pop();
+                last_complete = value_done(&mut frames, end);
+            }
+            ':' => {
+                if let Some((_, expect)) = frames.last_mut() {
+                    *expect = Expect::Value;
+                }
+            }
+            ',' => {
+                if let Some((closer, expect)) = frames.last_mut() {
+                    *expect = if *closer == '}' {
+                        Expect::Key
+                    } else {
+                        Expect::Value
+                    };
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let closers: Vec<char> = frames.iter().map(|(closer, _)| *closer).collect();
+    let close = |prefix: &str, closers: &[char]| -> Option<Value> {
+        let mut candidate = prefix.to_string();
+        candidate.extend(closers.iter().rev());
+        serde_json::from_str(&candidate).ok()
+    };
+
+    let mut prefix = text.to_string();
+    if in_string {
+        if escaped {
+            prefix.pop();
+        }
+        prefix.push('"');
+    }
+    close(&prefix, &closers).or_else(|| {
+        let (end, depth) = last_complete?;
+        close(&text[..end], &closers[..depth])
+    })
+}
+
+/// Returns the contents of a Markdown code fence, if `text` is one
+fn strip_code_fence(text: &str) -> Option<&str> {
+    let rest = text.strip_prefix("```")?;
+    let (_, body) = rest.split_once('\n')?;
+    let body = body.trim_end();
+    Some(body.strip_suffix("```").unwrap_or(body).trim())
+}
+
+/// Removes commas directly followed by a closing bracket, outside of strings
+fn remove_trailing_commas(text: &str) -> (String, usize) {
+    let mut cleaned = String::with_capacity(text.len());
+    let mut removed = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ','
+            && matches!(
+                text[index + 1..].trim_start().chars().next(),
+                Some('}' | ']')
+            )
+        {
+            removed += 1;
+            continue;
+        }
+        cleaned.push(c);
+    }
+    (cleaned, removed)
+}
+
+/// Validates a JSON value against a schema in either dialect
+///
+/// Supports the keywords the API accepts: types and nullability, `enum`, `const`,
+/// object properties, array items, length and range bounds, unions and local `$ref`s.
+/// Returns one issue per violation, with paths pointing into `value`.
+pub fn validate_json(value: &Value, schema: &Value) -> Vec<SchemaIssue> {
+    let mut validator = Validator {
+        root: schema,
+        issues: Vec::new(),
+        resolving: Vec::new(),
+    };
+    validator.check(value, schema, "#");
+    validator.issues
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    issues: Vec<SchemaIssue>,
+    /// `$ref`s being resolved, with the value path they are resolved at
+    ///
+    /// A reference that is already being resolved at the same path is a cycle
+    /// that never reaches a schema constraining the value, so it is skipped.
+    resolving: Vec<(String, String)>,
+}
+
+impl Validator<'_> {
+    fn issue(&mut self, path: &str, message: String) {
+        self.issues.push(SchemaIssue {
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    fn matches(&self, value: &Value, schema: &Value, path: &str) -> bool {
+        let mut validator = Validator {
+            root: self.root,
+            issues: Vec::new(),
+            resolving: self.resolving.clone(),
+        };
+        validator.check(value, schema, path);
+        validator.issues.is_empty()
+    }
+
+    fn check(&mut self, value: &Value, schema: &Value, path: &str) {
+        let schema = match schema {
+            Value::Object(schema) => schema,
+            Value::Bool(false) => return self.issue(path, "no value is allowed".to_string()),
+            _ => return,
+        };
+
+        if let Some(Value::String(reference)) = schema.get("$ref")
+            && let Some(target) = reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.root.pointer(pointer))
+        {
+            let key = (reference.clone(), path.to_string());
+            if !self.resolving.contains(&key) {
+                self.resolving.push(key);
+                self.check(value, target, path);
+                self.resolving.pop();
+            }
+        }
+
+        if value.is_null() && schema.get("nullable") == Some(&Value::Bool(true)) {
+            return;
+        }
+
+        if let Some(types) = schema.get("type") {
+            let names: Vec<String> = match types {
+                Value::String(name) => vec![name.to_lowercase()],
+                Value::Array(names) => names
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_lowercase)
+                    .collect(),
+                _ => Vec::new(),
+            };
+            if !names.iter().any(|name| matches_type(value, name)) {
+                return self.issue(
+                    path,
+                    format!(
+                        "expected {}, found {}",
+                        names.join(" or "),
+                        type_name(value)
+                    ),
+                );
+            }
+        }
+
+        if let Some(Value::Array(options)) = schema.get("enum")
+            && !options.contains(value)
+        {
+            let options: Vec<String> = options.iter().map(Value::to_string).collect();
+            self.issue(
+                path,
+                format!("{} is not one of {}", value, options.join(", ")),
+            );
+        }
+        if let Some(constant) = schema.get("const")
+            && constant != value
+        {
+            self.issue(path, format!("expected {}, found {}", constant, value));
+        }
+
+        for key in ["anyOf", "oneOf"] {
+            if let Some(Value::Array(branches)) = schema.get(key)
+                && !branches
+                    .iter()
+                    .any(|branch| self.matches(value, branch, path))
+            {
+                self.issue(
+                    path,
+                    "does not match any of the allowed alternatives".to_string(),
+                );
+            }
+        }
+        if let Some(Value::Array(branches)) = schema.get("allOf") {
+            for branch in branches {
+                self.check(value, branch, path);
+            }
+        }
+
+        match value {
+            Value::Object(object) => self.check_object(object, schema, path),
+            Value::Array(items) => self.check_array(items, schema, path),
+            Value::String(text) => self.check_string(text, schema, path),
+            Value::Number(number) => {
+                let number = number.as_f64().unwrap_or_default();
+                if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64)
+                    && number < minimum
+                {
+                    self.issue(
+                        path,
+                        format!("{} is less than the minimum {}", number, minimum),
+                    );
+                }
+                if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64)
+                    && number > maximum
+                {
+                    self.issue(
+                        path,
+                        format!("{} is greater than the maximum {}", number, maximum),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_object(
+        &mut self,
+        object: &serde_json::Map<String, Value>,
+        schema: &serde_json::Map<String, Value>,
+        path: &str,
+    ) {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    self.issue(path, format!("missing required property `{}`", name));
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, property) in object {
+            let property_path = format!("{}/{}", path, name);
+            match (
+                properties.and_then(|properties| properties.get(name)),
+                schema.get("additionalProperties"),
+            ) {
+                (Some(property_schema), _) => self.check(property, property_schema, &property_path),
+                (None, Some(Value::Bool(false))) => {
+                    self.issue(&property_path, "unexpected property".to_string())
+                }
+                (None, Some(additional @ Value::Object(_))) => {
+                    self.check(property, additional, &property_path)
+                }
+                (None, _) => {}
+            }
+        }
+
+        bounds(
+            self,
+            path,
+            object.len(),
+            schema,
+            "minProperties",
+            "maxProperties",
+            "properties",
+        );
+    }
+
+    fn check_array(
+        &mut self,
+        items: &[Value],
+        schema: &serde_json::Map<String, Value>,
+        path: &str,
+    ) {
+        let prefix = match schema.get("prefixItems") {
+            Some(Value::Array(prefix)) => prefix.as_slice(),
+            _ => &[],
+        };
+        for (index, item) in items.iter().enumerate() {
+            let item_path = format!("{}/{}", path, index);
+            match (prefix.get(index), schema.get("items")) {
+                (Some(item_schema), _) | (None, Some(item_schema)) => {
+                    self.check(item, item_schema, &item_path)
+                }
+                (None, None) => {}
+            }
+        }
+
+        bounds(
+            self,
+            path,
+            items.len(),
+            schema,
+            "minItems",
+            "maxItems",
+            "items",
+        );
+    }
+
+    fn check_string(&mut self, text: &str, schema: &serde_json::Map<String, Value>, path: &str) {
+        let length = text.chars().count();
+        bounds(
+            self,
+            path,
+            length,
+            schema,
+            "minLength",
+            "maxLength",
+            "characters",
+        );
+
+        if let Some(format) = schema.get("format").and_then(Value::as_str)
+            && !matches_format(text, format)
+        {
+            self.issue(path, format!("{:?} is not a valid {}", text, format));
+        }
+    }
+}
+
+/// Checks a count against a pair of `min*`/`max*` keywords
+fn bounds(
+    validator: &mut Validator<'_>,
+    path: &str,
+    count: usize,
+    schema: &serde_json::Map<String, Value>,
+    min_key: &str,
+    max_key: &str,
+    noun: &str,
+) {
+    let limit = |key: &str| {
+        schema.get(key).and_then(|limit| match limit {
+            // The OpenAPI dialect encodes these limits as strings
+            Value::String(limit) => limit.parse::<u64>().ok(),
+            _ => limit.as_u64(),
+        })
+    };
+    if let Some(min) = limit(min_key)
+        && (count as u64) < min
+    {
+        validator.issue(
+            path,
+            format!("expected at least {} {}, found {}", min, noun, count),
+        );
+    }
+    if let Some(max) = limit(max_key)
+        && (count as u64) > max
+    {
+        validator.issue(
+            path,
+            format!("expected at most {} {}, found {}", max, noun, count),
+        );
+    }
+}
+
+fn matches_type(value: &Value, name: &str) -> bool {
+    match name {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64()
+                || value.is_u64()
+                || value.as_f64().is_some_and(|number| number.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Loosely checks the formats the API supports; unknown formats always match
+fn matches_format(text: &str, format: &str) -> bool {
+    fn digits(text: &str, pattern: &str) -> bool {
+        text.len() >= pattern.len()
+            && text.bytes().zip(pattern.bytes()).all(|(c, p)| {
+                if p == b'd' {
+                    c.is_ascii_digit()
+                } else {
+                    c == p
+                }
+            })
+    }
+
+    match format {
+        "date" => text.len() == 10 && digits(text, "dddd-dd-dd"),
+        "time" => digits(text, "dd:dd:dd"),
+        "date-time" => {
+            digits(text, "dddd-dd-dd")
+                && matches!(text.as_bytes().get(10), Some(b'T' | b't' | b' '))
+                && digits(&text[11..], "dd:dd:dd")
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_json() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "maxLength": 5},
+                "born": {"type": "string", "format": "date"},
+                "tags": {"type": "array", "items": {"type": "string"}, "minItems": 1},
+                "role": {"type": "string", "enum": ["admin", "user"], "nullable": true},
+                "score": {"type": ["number", "null"], "maximum": 10}
+            },
+            "required": ["name", "tags"],
+            "additionalProperties": false
+        });
+
+        let valid = json!({"name": "Ada", "born": "1815-12-10", "tags": ["math"], "role": null});
+        assert!(validate_json(&valid, &schema).is_empty());
+
+        let invalid = json!({
+            "name": "Augusta",
+            "born": "10 Dec 1815",
+            "tags": [1],
+            "role": "owner",
+            "score": 11.5,
+            "extra": true
+        });
+        let mut issues: Vec<String> = validate_json(&invalid, &schema)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        issues.sort();
+        assert_eq!(
+            issues,
+            [
+                "#/born: \"10 Dec 1815\" is not a valid date",
+                "#/extra: unexpected property",
+                "#/name: expected at most 5 characters, found 7",
+                "#/role: \"owner\" is not one of \"admin\", \"user\"",
+                "#/score: 11.5 is greater than the maximum 10",
+                "#/tags/0: expected string, found number",
+            ]
+        );
+
+        let issues = validate_json(&json!({"name": "Ada"}), &schema);
+        assert_eq!(issues[0].message, "missing required property `tags`");
+    }
+
+    #[test]
+    fn test_validate_json_recursive_refs() {
+        let schema = json!({"$ref": "#"});
+        assert!(validate_json(&json!({"a": 1}), &schema).is_empty());
+
+        let schema = json!({
+            "$defs": {
+                "a": {"$ref": "#/$defs/b"},
+                "b": {"anyOf": [{"$ref": "#/$defs/a"}]}
+            },
+            "$ref": "#/$defs/a"
+        });
+        // A cycle without a base case constrains nothing
+        assert!(validate_json(&json!(1), &schema).is_empty());
+
+        let tree = json!({
+            "type": "object",
+            "properties": {
+                "children": {"type": "array", "items": {"$ref": "#"}}
+            }
+        });
+        let value = json!({"children": [{"children": [{"children": 1}]}]});
+        let issues = validate_json(&value, &tree);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "#/children/0/children/0/children");
+    }
+
+    #[test]
+    fn test_repair_json() {
+        let (value, fixes) =
+            repair_json("```json\n{\"name\": \"Ada\", \"tags\": [\"a\",],}\n```").unwrap();
+        assert_eq!(value, json!({"name": "Ada", "tags": ["a"]}));
+        assert_eq!(
+            fixes,
+            [
+                LocalFix::StrippedCodeFence,
+                LocalFix::RemovedTrailingCommas(2)
+            ]
+        );
+
+        let (value, fixes) = repair_json(r#"{"name": "Ada", "age": 36} Hope this helps!"#).unwrap();
+        assert_eq!(value, json!({"name": "Ada", "age": 36}));
+        assert_eq!(
+            fixes,
+            [LocalFix::RemovedTrailingText(
+                "Hope this helps!".to_string()
+            )]
+        );
+
+        let (value, fixes) = repair_json(r#"{"name": "Ada", "tags": ["math", "poe"#).unwrap();
+        assert_eq!(value, json!({"name": "Ada", "tags": ["math", "poe"]}));
+        assert_eq!(fixes, [LocalFix::CompletedTruncatedOutput]);
+
+        // A dangling key is dropped along with everything after the last complete value
+        let (value, _) = repair_json(r#"{"name": "Ada", "age": 36, "tag"#).unwrap();
+        assert_eq!(value, json!({"name": "Ada", "age": 36}));
+        let (value, _) = repair_json(r#"{"name": "Ada", "note": "line\"#).unwrap();
+        assert_eq!(value, json!({"name": "Ada", "note": "line"}));
+        let (value, _) = repair_json(r#"{"a": 1, "b": [true, {"c":"#).unwrap();
+        assert_eq!(value, json!({"a": 1, "b": [true, {}]}));
+        let (value, _) = repair_json(r#"{"a": [1, 2, nul"#).unwrap();
+        assert_eq!(value, json!({"a": [1, 2]}));
+
+        // Commas inside strings are left alone
+        let (value, fixes) = repair_json(r#"{"note": "a,]"}"#).unwrap();
+        assert_eq!(value, json!({"note": "a,]"}));
+        assert!(fixes.is_empty());
+
+        assert!(repair_json("not json").is_err());
+    }
+
+    #[cfg(feature = "json")]
+    mod generate_tests {
+        use super::*;
+        use crate::dto_content::Json;
+        use std::sync::Mutex;
+
+        /// Replays canned responses and records the requests it receives
+        struct ScriptedApi {
+            responses: Mutex<Vec<&'static str>>,
+            requests: Mutex<Vec<Value>>,
+        }
+
+        impl ScriptedApi {
+            fn new(answers: &[&'static str]) -> Self {
+                Self {
+                    responses: Mutex::new(answers.iter().rev().copied().collect()),
+                    requests: Mutex::new(Vec::new()),
+                }
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl GeminiApi for ScriptedApi {
+            async fn generate_content<T>(
+                &self,
+                request: GenerateContentRequest<T>,
+            ) -> Result<GenerateContentResponse<T>, Box<dyn Error>>
+            where
+                T: TextPayload + Send + 'static,
+            {
+                self.requests
+                    .lock()
+                    .unwrap()
+                    .push(serde_json::to_value(&request)?);
+                let answer = self
+                    .responses
+                    .lock()
+                    .unwrap()
+                    .pop()
+                    .expect("no response left");
+                let response = json!({
+                    "candidates": [{
+                        "content": {
+                            "role": "model",
+                            "parts": [
+                                {"text": "Checking the fields", "thought": true},
+                                {"text": answer}
+                            ]
+                        }
+                    }]
+                });
+                Ok(serde_json::from_value(response)?)
+            }
+        }
+
+        #[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema, PartialEq)]
+        struct Person {
+            name: String,
+            age: u32,
+        }
+
+        fn person_request() -> GenerateContentRequest<Json<Person>> {
+            let config = crate::GenerationConfig::<String>::builder()
+                .response_json_schema::<Person>()
+                .build()
+                .unwrap();
+            GenerateContentRequest::builder()
+                .add_content(Content::user(vec![
+                    Part::builder()
+                        .text(JsonString::new("Describe Ada".to_string()))
+                        .build(),
+                ]))
+                .generation_config(config)
+                .build()
+        }
+
+        #[tokio::test]
+        async fn test_valid_response_passes() {
+            let api = ScriptedApi::new(&[r#"{"name": "Ada", "age": 36}"#]);
+            let validated = api
+                .generate_content_validated(person_request(), RepairStrategy::none())
+                .await
+                .unwrap();
+
+            assert!(!validated.diagnostics.is_repaired());
+            assert_eq!(validated.response.first_text().unwrap().age, 36);
+            assert_eq!(
+                validated.response.thought_summary().unwrap(),
+                "Checking the fields"
+            );
+        }
+
+        #[tokio::test]
+        async fn test_local_repair() {
+            let api = ScriptedApi::new(&[r#"{"name": "Ada", "age": 36,}"#]);
+            let validated = api
+                .generate_content_validated(person_request(), RepairStrategy::local())
+                .await
+                .unwrap();
+
+            assert_eq!(
+                validated.diagnostics.fixes,
+                [LocalFix::RemovedTrailingCommas(1)]
+            );
+            assert_eq!(
+                validated.response.first_text().unwrap().0,
+                Person {
+                    name: "Ada".to_string(),
+                    age: 36
+                }
+            );
+        }
+
+        #[tokio::test]
+        async fn test_reprompt_with_validation_errors() {
+            let api = ScriptedApi::new(&[
+                r#"{"name": "Ada"}"#,
+                r#"{"name": "Ada", "age": -1}"#,
+                r#"{"name": "Ada", "age": 36}"#,
+            ]);
+            let validated = api
+                .generate_content_validated(person_request(), RepairStrategy::reprompt(2))
+                .await
+                .unwrap();
+
+            assert_eq!(validated.diagnostics.reprompts(), 2);
+            assert_eq!(
+                validated.diagnostics.rejected[0][0].message,
+                "missing required property `age`"
+            );
+            assert_eq!(validated.response.first_text().unwrap().age, 36);
+
+            // The last request carries both rejected answers and the feedback on each
+            let requests = api.requests.lock().unwrap();
+            let contents = requests[2]["contents"].as_array().unwrap();
+            assert_eq!(contents.len(), 5);
+            assert_eq!(contents[1]["role"], "model");
+            let feedback = contents[4]["parts"][0]["text"].as_str().unwrap();
+            assert!(feedback.contains("#/age: -1 is less than the minimum 0"));
+        }
+
+        #[tokio::test]
+        async fn test_enum_response_checked_against_variants() {
+            #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+            #[serde(rename_all = "lowercase")]
+            enum Sentiment {
+                Positive,
+                Negative,
+            }
+
+            let config = crate::GenerationConfig::<String>::builder()
+                .enum_response::<Sentiment>()
+                .build()
+                .unwrap();
+            let request = GenerateContentRequest::builder()
+                .add_content(Content::user(vec![
+                    Part::builder()
+                        .text(JsonString::new("Great movie!".to_string()))
+                        .build(),
+                ]))
+                .generation_config(config)
+                .build();
+
+            let api = ScriptedApi::new(&["excited", "positive\n"]);
+            let validated = api
+                .generate_content_validated(request, RepairStrategy::reprompt(1))
+                .await
+                .unwrap();
+
+            assert_eq!(
+                validated.diagnostics.rejected[0][0].to_string(),
+                r#"#: "excited" is not one of "positive", "negative""#
+            );
+            assert_eq!(
+                **validated.response.first_text().unwrap(),
+                Sentiment::Positive
+            );
+            let requests = api.requests.lock().unwrap();
+            let feedback = requests[1]["contents"][2]["parts"][0]["text"]
+                .as_str()
+                .unwrap();
+            assert!(feedback.ends_with("only one of the allowed values."));
+        }
+
+        #[tokio::test]
+        async fn test_gives_up_when_strategy_is_exhausted() {
+            let api = ScriptedApi::new(&[r#"{"name": 1, "age": 3}"#, r#"{"name": 2, "age": 3}"#]);
+            let error = api
+                .generate_content_validated(person_request(), RepairStrategy::reprompt(1))
+                .await
+                .unwrap_err();
+
+            let error = error.downcast_ref::<ResponseValidationError>().unwrap();
+            assert_eq!(error.diagnostics.reprompts(), 1);
+            assert_eq!(
+                error.issues[0].to_string(),
+                "#/name: expected string, found number"
+            );
+        }
+    }
+}